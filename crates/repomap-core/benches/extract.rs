@@ -0,0 +1,95 @@
+//! Extraction throughput and allocation counts over a synthetic corpus.
+//!
+//! Run with `cargo bench -p repomap-core --bench extract`.
+
+use repomap_core::{Language, formatter};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ITERATIONS: usize = 20;
+
+fn rust_source(items: usize) -> String {
+    let mut source = String::from("use std::collections::HashMap;\nuse crate::parser::Symbol;\n\n");
+    for i in 0..items {
+        source.push_str(&format!(
+            "pub struct Widget{i} {{ id: u32 }}\n\nimpl Widget{i} {{\n    pub fn render_component(&self) -> u32 {{ self.id }}\n    fn update_state(&mut self) {{}}\n}}\n\nfn helper_function_{i}() {{}}\n\n"
+        ));
+    }
+    source
+}
+
+fn python_source(items: usize) -> String {
+    let mut source = String::from("import os\nfrom typing import Optional\n\n");
+    for i in 0..items {
+        source.push_str(&format!(
+            "class Service{i}:\n    def handle_request(self):\n        pass\n\n    def shutdown(self):\n        pass\n\ndef module_function_{i}():\n    pass\n\n"
+        ));
+    }
+    source
+}
+
+fn typescript_source(items: usize) -> String {
+    let mut source = String::from("import { api } from './api';\nimport React from 'react';\n\n");
+    for i in 0..items {
+        source.push_str(&format!(
+            "export class Store{i} {{\n  dispatchAction() {{ return 1; }}\n}}\n\nexport interface Props{i} {{ id: number }}\n\nfunction selectState{i}() {{}}\n\n"
+        ));
+    }
+    source
+}
+
+fn bench(name: &str, source: &str, lang: Language) {
+    // Warm up the compiled queries and the thread's parser
+    black_box(formatter::process_source("bench", source, lang));
+
+    let allocs_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes_before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let mut elapsed = Duration::ZERO;
+    let mut symbols = 0;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let file = black_box(formatter::process_source("bench", source, lang));
+        elapsed += start.elapsed();
+        symbols = file.symbols.len();
+    }
+
+    let allocs = (ALLOCATIONS.load(Ordering::Relaxed) - allocs_before) / ITERATIONS;
+    let bytes = (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes_before) / ITERATIONS;
+    println!(
+        "{:<12} {:>6} symbols  {:>9.2?}/file  {:>8} allocs/file  {:>10} bytes/file",
+        name,
+        symbols,
+        elapsed / ITERATIONS as u32,
+        allocs,
+        bytes
+    );
+}
+
+fn main() {
+    bench("rust", &rust_source(200), Language::Rust);
+    bench("python", &python_source(200), Language::Python);
+    bench("typescript", &typescript_source(200), Language::Typescript);
+}
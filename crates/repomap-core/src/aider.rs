@@ -0,0 +1,88 @@
+use crate::formatter::{FileSymbols, RepoStats};
+use std::fs;
+
+const ELLIPSIS: &str = "⋮...";
+
+fn render_file(file: &FileSymbols, output: &mut String) {
+    let path = file.path.strip_prefix("./").unwrap_or(&file.path);
+
+    if file.symbols.is_empty() {
+        output.push_str(path);
+        output.push('\n');
+        return;
+    }
+
+    output.push_str(&format!("{}:\n", path));
+
+    let source = fs::read_to_string(&file.path).unwrap_or_default();
+    let lines: Vec<&str> = source.lines().collect();
+
+    let mut sym_lines: Vec<usize> = file.symbols.iter().map(|s| s.line).collect();
+    sym_lines.sort_unstable();
+    sym_lines.dedup();
+
+    let mut previous = None;
+    for line in sym_lines {
+        // Aider only marks elisions between lines that aren't adjacent
+        if previous.is_none_or(|p| line > p + 1) {
+            output.push_str(ELLIPSIS);
+            output.push('\n');
+        }
+        let text = match lines.get(line - 1) {
+            Some(text) => text.trim_end().to_string(),
+            None => file
+                .symbols
+                .iter()
+                .find(|s| s.line == line)
+                .map(|s| s.name.to_string())
+                .unwrap_or_default(),
+        };
+        output.push_str(&format!("│{}\n", text));
+        previous = Some(line);
+    }
+
+    if previous.is_some_and(|p| p < lines.len()) {
+        output.push_str(ELLIPSIS);
+        output.push('\n');
+    }
+}
+
+/// Renders the map in aider's repo-map layout: each file followed by the
+/// source lines that define its symbols, with elided code marked by `⋮...`.
+pub fn assemble_aider(stats: &RepoStats) -> String {
+    let mut output = String::new();
+    for file in &stats.files {
+        render_file(file, &mut output);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter;
+    use crate::languages::Language;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_aider_layout() {
+        let mut file = NamedTempFile::with_suffix(".py").unwrap();
+        write!(
+            file,
+            "import os\n\nclass Coder:\n    def run(self):\n        pass\n\ndef helper():\n    return 1\n"
+        )
+        .unwrap();
+
+        let mut stats = RepoStats::new();
+        stats.add_file(formatter::process_file(file.path(), Language::Python).unwrap());
+
+        let output = assemble_aider(&stats);
+        let expected = format!(
+            "{}:\n⋮...\n│class Coder:\n│    def run(self):\n⋮...\n│def helper():\n⋮...\n\n",
+            file.path().display()
+        );
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,382 @@
+use crate::formatter::{self, FileSymbols, RepoStats};
+use crate::languages::Language;
+use crate::parser::Symbol;
+use crate::rank;
+use crate::testing::is_test_path;
+use crate::tokens::TokenCountingWriter;
+use std::io::{self, Write};
+
+/// Held back from the budget for the note that says what was left out.
+const NOTE_TOKENS: usize = 40;
+
+/// What [`fit_to_budget`] left out of the map.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Omitted {
+    /// Test and private symbols dropped from files that are still listed.
+    pub symbols: usize,
+    /// Files reduced to a one-line entry.
+    pub collapsed_files: usize,
+    /// Files left out entirely because even their one-line entry did not fit.
+    pub dropped_files: usize,
+}
+
+impl Omitted {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// A Markdown line for the end of the map, unless nothing was omitted.
+    pub fn note(&self, max_tokens: usize) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut parts = Vec::new();
+        if self.symbols > 0 {
+            parts.push(format!("{} low-priority symbols omitted", self.symbols));
+        }
+        if self.collapsed_files > 0 {
+            parts.push(format!("{} files collapsed", self.collapsed_files));
+        }
+        if self.dropped_files > 0 {
+            parts.push(format!("{} files left out", self.dropped_files));
+        }
+        Some(format!(
+            "> Trimmed to fit {} tokens: {}.",
+            max_tokens,
+            parts.join(", ")
+        ))
+    }
+}
+
+/// Trims `stats` until its Markdown map, with the note saying what was left
+/// out, fits in `max_tokens`, cheapest losses first: test symbols, then
+/// private helpers, then whole files collapsed to one line, and only then
+/// files dropped outright. Within each step test files go first, then files
+/// in order of [`rank::rank_files`], least important first. Each step stops
+/// as soon as the map fits.
+pub fn fit_to_budget(
+    root: &str,
+    stats: &mut RepoStats,
+    max_tokens: usize,
+    show_summary: bool,
+) -> Omitted {
+    // Counted as the map is written out, line by line, note and all
+    let measure = |stats: &RepoStats, note: Option<&str>| {
+        let mut writer = TokenCountingWriter::new(io::sink(), stats.tokenizer);
+        formatter::write_final_map(&mut writer, root, stats, show_summary)
+            .and_then(|()| match note {
+                Some(note) => write!(writer, "\n{note}\n"),
+                None => Ok(()),
+            })
+            .expect("writing to a sink cannot fail");
+        writer.tokens()
+    };
+    if measure(stats, None) <= max_tokens {
+        return Omitted::default();
+    }
+
+    // The steps go by per-file estimates, so measure the map as written and
+    // try again from the start with more held back if it's still too long
+    let original = stats.clone();
+    let mut reserve = NOTE_TOKENS;
+    loop {
+        let omitted = trim(root, stats, max_tokens.saturating_sub(reserve), |stats| {
+            measure(stats, None)
+        });
+        let total = measure(stats, omitted.note(max_tokens).as_deref());
+        if total <= max_tokens || stats.files.is_empty() {
+            return omitted;
+        }
+        reserve += total - max_tokens;
+        *stats = original.clone();
+    }
+}
+
+/// One pass of [`fit_to_budget`], trimming until `measure` estimates the
+/// map at no more than `budget`.
+fn trim(
+    root: &str,
+    stats: &mut RepoStats,
+    budget: usize,
+    measure: impl Fn(&RepoStats) -> usize,
+) -> Omitted {
+    let mut omitted = Omitted::default();
+    let order = trim_order(root, stats);
+    // Symbols this function dropped from each file, as opposed to ones
+    // already counted in `FileSymbols::omitted` by `cap_symbols`
+    let mut trimmed = vec![0; stats.files.len()];
+
+    let low_priority: [fn(&FileSymbols, &Symbol) -> bool; 2] =
+        [|_, sym| sym.test, is_private_symbol];
+    for is_low_priority in low_priority {
+        let mut total = measure(stats);
+        for &i in &order {
+            if total <= budget {
+                return omitted;
+            }
+            let file = &stats.files[i];
+            let before = stats.file_tokens(file);
+            let kept: Vec<Symbol> = file
+                .symbols
+                .iter()
+                .filter(|sym| !is_low_priority(file, sym))
+                .cloned()
+                .collect();
+            let removed = file.symbols.len() - kept.len();
+            if removed == 0 {
+                continue;
+            }
+
+            let file = &mut stats.files[i];
+            file.symbols = kept;
+            file.omitted += removed;
+            trimmed[i] += removed;
+            omitted.symbols += removed;
+            let after = stats.file_tokens(&stats.files[i]);
+            total = (total + after).saturating_sub(before);
+        }
+    }
+
+    let mut total = measure(stats);
+    for &i in &order {
+        if total <= budget {
+            return omitted;
+        }
+        let before = stats.file_tokens(&stats.files[i]);
+        let file = &mut stats.files[i];
+        omitted.symbols -= trimmed[i];
+        file.omitted += file.symbols.len();
+        file.symbols.clear();
+        file.imports.clear();
+        omitted.collapsed_files += 1;
+        let after = stats.file_tokens(&stats.files[i]);
+        total = (total + after).saturating_sub(before);
+    }
+
+    let mut total = measure(stats);
+    let mut dropped = vec![false; stats.files.len()];
+    for &i in &order {
+        if total <= budget {
+            break;
+        }
+        total = total.saturating_sub(stats.file_tokens(&stats.files[i]));
+        dropped[i] = true;
+        omitted.collapsed_files -= 1;
+        omitted.dropped_files += 1;
+    }
+    let mut index = 0;
+    stats.files.retain(|_| {
+        index += 1;
+        !dropped[index - 1]
+    });
+    stats.file_count -= omitted.dropped_files;
+
+    omitted
+}
+
+/// Keeps at most `max` symbols in each file, preferring top-level, public,
+/// and longer ones, and counts the rest in [`FileSymbols::omitted`]. Kept
+/// symbols stay in source order. Returns how many symbols were dropped.
+pub fn cap_symbols(stats: &mut RepoStats, max: usize) -> usize {
+    let mut dropped = 0;
+    for file in &mut stats.files {
+        if file.symbols.len() <= max {
+            continue;
+        }
+        let mut order: Vec<usize> = (0..file.symbols.len()).collect();
+        order.sort_by_key(|&i| {
+            let sym = &file.symbols[i];
+            (
+                sym.parent.is_some(),
+                is_private_symbol(file, sym),
+                std::cmp::Reverse(sym.end_line - sym.line),
+            )
+        });
+        let mut keep = vec![false; file.symbols.len()];
+        for &i in &order[..max] {
+            keep[i] = true;
+        }
+        let mut index = 0;
+        file.symbols.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+        let removed = order.len() - max;
+        file.omitted += removed;
+        dropped += removed;
+    }
+    dropped
+}
+
+/// Test files first, then the rest, each from least to most important.
+/// Equally ranked files go largest first.
+fn trim_order(root: &str, stats: &RepoStats) -> Vec<usize> {
+    let ranks = rank::rank_files(root, &stats.files, &stats.weights);
+    let costs: Vec<usize> = stats.files.iter().map(|f| stats.file_tokens(f)).collect();
+    let mut order: Vec<usize> = (0..stats.files.len()).collect();
+    order.sort_by(|&a, &b| {
+        let is_test = |i: usize| is_test_path(&stats.files[i].path);
+        is_test(b)
+            .cmp(&is_test(a))
+            .then(ranks[a].total_cmp(&ranks[b]))
+            .then(costs[b].cmp(&costs[a]))
+    });
+    order
+}
+
+/// Private by naming convention: a leading underscore or `#` (but not Python
+/// dunders), or an unexported Go identifier.
+fn is_private_symbol(file: &FileSymbols, sym: &Symbol) -> bool {
+    let name = sym.name.as_str();
+    if file.language == Language::Go {
+        return name.starts_with(|c: char| c.is_lowercase() || c == '_');
+    }
+    name.starts_with('#') || name.starts_with('_') && !name.ends_with("__")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapOutput;
+    use crate::tokens::Tokenizer;
+
+    fn sample() -> MapOutput {
+        let mut lib = String::new();
+        for i in 0..20 {
+            lib.push_str(&format!(
+                "def public_{i}():\n    pass\n\ndef _helper_{i}():\n    pass\n\n"
+            ));
+        }
+        let mut map = MapOutput::from_sources(
+            ".",
+            [
+                ("src/lib.py", lib.as_str()),
+                ("tests/test_lib.py", "def test_a():\n    pass\n"),
+                ("src/small.py", "def tiny():\n    pass\n"),
+            ],
+        );
+        map.stats.tokenizer = Tokenizer::Heuristic;
+        map
+    }
+
+    fn tokens(map: &MapOutput) -> usize {
+        Tokenizer::Heuristic.count(&map.to_markdown(false))
+    }
+
+    #[test]
+    fn test_fitting_map_is_untouched() {
+        let mut map = sample();
+        let before = map.to_markdown(false);
+        let omitted = map.fit_to_budget(100_000, false);
+        assert!(omitted.is_empty());
+        assert_eq!(omitted.note(100_000), None);
+        assert_eq!(map.to_markdown(false), before);
+    }
+
+    #[test]
+    fn test_drops_low_priority_symbols_before_collapsing() {
+        let mut map = sample();
+        let budget = tokens(&map) - 100;
+        let omitted = map.fit_to_budget(budget, false);
+
+        assert_eq!(omitted.collapsed_files, 0);
+        assert!(omitted.symbols > 0);
+        assert!(tokens(&map) <= budget);
+        let lib = &map.files()[0];
+        assert!(lib.symbols.iter().any(|s| s.name == "public_0"));
+        assert!(!lib.symbols.iter().any(|s| s.name == "_helper_19"));
+        assert!(map.to_markdown(false).contains("_… and "));
+        assert!(
+            omitted
+                .note(budget)
+                .unwrap()
+                .starts_with("> Trimmed to fit")
+        );
+    }
+
+    #[test]
+    fn test_collapses_then_drops_files() {
+        let mut map = sample();
+        let omitted = map.fit_to_budget(80, false);
+        assert!(omitted.collapsed_files > 0);
+        assert!(map.to_markdown(false).contains("symbols collapsed)"));
+        assert!(tokens(&map) <= 80);
+
+        let mut map = sample();
+        let omitted = map.fit_to_budget(0, false);
+        assert_eq!(omitted.dropped_files, 3);
+        assert_eq!(map.stats.file_count, 0);
+    }
+
+    #[test]
+    fn test_written_map_fits_with_its_note() {
+        for budget in [80, 150, 250, 400] {
+            let mut map = sample();
+            let note = map.fit_to_budget(budget, false).note(budget).unwrap();
+            // Counted the way the map is written out to a file
+            let mut writer = TokenCountingWriter::new(io::sink(), Tokenizer::Heuristic);
+            map.write_markdown(&mut writer, false).unwrap();
+            write!(writer, "\n{note}\n").unwrap();
+            assert!(writer.tokens() <= budget, "{budget}");
+        }
+    }
+
+    #[test]
+    fn test_budget_after_cap() {
+        let mut map = sample();
+        assert_eq!(map.cap_symbols(2), 38);
+        let omitted = map.fit_to_budget(80, false);
+        assert!(omitted.collapsed_files > 0);
+        // Only the test symbol was trimmed, and its file is collapsed now
+        assert_eq!(omitted.symbols, 0);
+        assert!(tokens(&map) <= 80);
+    }
+
+    #[test]
+    fn test_cap_prefers_top_level_public_and_long_symbols() {
+        let mut map = MapOutput::from_sources(
+            ".",
+            [(
+                "app.py",
+                "def _hidden():\n    pass\n\nclass Big:\n    def method(self):\n        pass\n\n\
+                 def short():\n    pass\n\ndef longer():\n    x = 1\n    return x\n",
+            )],
+        );
+        assert_eq!(map.cap_symbols(2), 3);
+        let names: Vec<&str> = map.files()[0]
+            .symbols
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Big", "longer"]);
+        assert!(map.to_markdown(false).contains("_… and 3 more symbols_"));
+        assert_eq!(map.cap_symbols(2), 0);
+    }
+
+    #[test]
+    fn test_low_priority_rules() {
+        let file = |path: &str, language| FileSymbols {
+            path: path.to_string(),
+            language,
+            lines: 1,
+            imports: vec![],
+            symbols: vec![],
+            omitted: 0,
+            references: vec![],
+        };
+        let sym = |name: &str| Symbol {
+            name: name.into(),
+            parent: None,
+            line: 1,
+            kind: "function".into(),
+            end_line: 1,
+            test: false,
+        };
+        let py = file("app.py", Language::Python);
+        let go = file("app.go", Language::Go);
+        assert!(is_private_symbol(&py, &sym("_cache")));
+        assert!(!is_private_symbol(&py, &sym("__init__")));
+        assert!(is_private_symbol(&go, &sym("parseArgs")));
+        assert!(!is_private_symbol(&go, &sym("ParseArgs")));
+    }
+}
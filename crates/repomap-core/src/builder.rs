@@ -0,0 +1,1268 @@
+use crate::budget::{self, Omitted};
+use crate::cache::SymbolCache;
+use crate::focus::{self, Detail};
+use crate::formatter::{self, FileSymbols, OutputFormat, RepoStats};
+use crate::generated::{self, GeneratedFiles};
+use crate::graph::DependencyGraph;
+use crate::languages::{self, Language, LanguageDetector};
+use crate::parser::{Symbol, SymbolKind};
+use crate::queries::QueryRegistry;
+use crate::rank::Weights;
+#[cfg(feature = "sqlite")]
+use crate::sqlite;
+use crate::testing::TestFilter;
+use crate::tokens::Tokenizer;
+use crate::walk::{SkipReason, Skipped, WalkOptions, WalkRule};
+use crate::{aider, collapse, ctags, html, markdown, rank, split, template, testing, walk};
+use anyhow::{Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Configures a repository walk and produces a [`MapOutput`].
+///
+/// ```no_run
+/// use repomap_core::{OutputFormat, RepoMapBuilder};
+///
+/// let map = RepoMapBuilder::new("src").depth(Some(2)).build()?;
+/// println!("{}", map.render(OutputFormat::Json)?);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct RepoMapBuilder {
+    root: String,
+    walk: WalkOptions,
+    language: Option<Language>,
+    detector: LanguageDetector,
+    queries: QueryRegistry,
+    skip_file_names: Vec<String>,
+    skip_paths: Vec<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    tokenizer: Tokenizer,
+    weights: Weights,
+    tests: TestFilter,
+    include_generated: bool,
+    doc_links: bool,
+    code_blocks: bool,
+    max_file_size: Option<u64>,
+}
+
+impl RepoMapBuilder {
+    pub fn new(root: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            walk: WalkOptions::default(),
+            language: None,
+            detector: LanguageDetector::default(),
+            queries: QueryRegistry::default(),
+            skip_file_names: Vec::new(),
+            skip_paths: Vec::new(),
+            cache_dir: None,
+            tokenizer: Tokenizer::default(),
+            weights: Weights::default(),
+            tests: TestFilter::Include,
+            include_generated: false,
+            doc_links: false,
+            code_blocks: false,
+            max_file_size: None,
+        }
+    }
+
+    /// Limits traversal depth below the root.
+    pub fn depth(mut self, depth: Option<usize>) -> Self {
+        self.walk.depth = depth;
+        self
+    }
+
+    /// Leaves out files and directories matching a gitignore-style glob
+    /// relative to the root, like `**/*.gen.ts` or `fixtures/`.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.walk.excludes.push(pattern.into());
+        self
+    }
+
+    pub fn excludes<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.walk
+            .excludes
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Restricts the walk to files matching a gitignore-style glob relative
+    /// to the root, like `src/**/*.rs`. May be given several times.
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.walk.includes.push(pattern.into());
+        self
+    }
+
+    pub fn includes<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.walk
+            .includes
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Forces a single parser for every file instead of inferring by extension.
+    pub fn language(mut self, language: Option<Language>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Parses files with extension `ext` as `language`; see
+    /// [`LanguageDetector::map_extension`].
+    pub fn map_extension(mut self, ext: &str, language: Language) -> Self {
+        self.detector.map_extension(ext, language);
+        self
+    }
+
+    pub fn map_extensions<I, S>(mut self, mappings: I) -> Self
+    where
+        I: IntoIterator<Item = (S, Language)>,
+        S: AsRef<str>,
+    {
+        for (ext, language) in mappings {
+            self.detector.map_extension(ext.as_ref(), language);
+        }
+        self
+    }
+
+    /// Guesses the language of extensionless or `.ts` files from their
+    /// content; see [`LanguageDetector::detect_content`].
+    pub fn detect_content(mut self, detect: bool) -> Self {
+        self.detector.detect_content(detect);
+        self
+    }
+
+    /// Extracts symbols and imports with the queries in `registry` instead
+    /// of the built-in ones.
+    pub fn queries(mut self, registry: QueryRegistry) -> Self {
+        self.queries = registry;
+        self
+    }
+
+    /// Skips any file with this exact name, wherever it appears in the tree.
+    pub fn skip_file_name(mut self, name: impl Into<String>) -> Self {
+        self.skip_file_names.push(name.into());
+        self
+    }
+
+    /// Skips one specific path. It is compared canonically during the walk,
+    /// so it may be created after the builder is configured.
+    pub fn skip_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.skip_paths.push(path.into());
+        self
+    }
+
+    /// Reuses results for unchanged files from a [`SymbolCache`] in `dir`,
+    /// and updates it after each walk.
+    pub fn cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.cache_dir = dir;
+        self
+    }
+
+    /// Tokenizer for the per-file token counts in the summary table.
+    pub fn tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Biases the importance ranking towards the files in play.
+    pub fn weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Keeps or drops test code; see [`TestFilter`].
+    pub fn tests(mut self, tests: TestFilter) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Descends into dependency and build output directories like
+    /// `node_modules` and `target`; see [`walk::VENDORED_DIRS`].
+    pub fn include_vendored(mut self, include: bool) -> Self {
+        self.walk.include_vendored = include;
+        self
+    }
+
+    /// Maps files git ignores, like checked-out but untracked generated SDKs.
+    pub fn no_gitignore(mut self, no_gitignore: bool) -> Self {
+        self.walk.no_gitignore = no_gitignore;
+        self
+    }
+
+    /// Maps dotfiles and dot directories.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.walk.hidden = hidden;
+        self
+    }
+
+    /// Stops ignore files above the root from applying, useful when
+    /// mapping a subdirectory that a parent `.gitignore` hides.
+    pub fn no_ignore_parent(mut self, no_ignore_parent: bool) -> Self {
+        self.walk.no_ignore_parent = no_ignore_parent;
+        self
+    }
+
+    /// Follows symlinked files and directories, as in pnpm or Bazel-style
+    /// layouts; see [`WalkOptions::follow_symlinks`].
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.walk.follow_symlinks = follow;
+        self
+    }
+
+    /// Maps generated code too. By default files marked `linguist-generated`
+    /// in `.gitattributes`, or recognized by [`generated`]'s name and header
+    /// heuristics, are skipped.
+    pub fn include_generated(mut self, include: bool) -> Self {
+        self.include_generated = include;
+        self
+    }
+
+    /// Counts Markdown files' links to other files in the repo, like
+    /// `[setup](./setup.md)`, as their imports, so docs join the dependency
+    /// graph and the ranking. Off by default.
+    pub fn doc_links(mut self, include: bool) -> Self {
+        self.doc_links = include;
+        self
+    }
+
+    /// Extracts the fenced code blocks in Markdown files that name their
+    /// language, like ```` ```rust ````, so documentation examples add their
+    /// symbols to the file's. Off by default.
+    pub fn code_blocks(mut self, include: bool) -> Self {
+        self.code_blocks = include;
+        self
+    }
+
+    /// Skips files over `bytes` without reading them, reporting each in
+    /// [`RepoStats::skipped`].
+    pub fn max_file_size(mut self, bytes: Option<u64>) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    /// Walks the repository and hands each mapped file to `f` as soon as it
+    /// is parsed. Returns the number of files visited.
+    pub fn for_each_file(&self, f: impl FnMut(FileSymbols) -> Result<()>) -> Result<usize> {
+        self.walk_files(f, |_| {})
+    }
+
+    /// [`Self::for_each_file`], also reporting each file skipped for its
+    /// content to `on_skip`.
+    fn walk_files(
+        &self,
+        mut on_file: impl FnMut(FileSymbols) -> Result<()>,
+        mut on_skip: impl FnMut(Skipped),
+    ) -> Result<usize> {
+        let mut cache = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| SymbolCache::load(dir, self.queries.clone()));
+        let mut count = 0;
+
+        self.for_each_candidate(|path, lang| {
+            match self.read_candidate(path, lang, cache.as_mut()) {
+                Candidate::Parsed(file) => {
+                    on_file(file)?;
+                    count += 1;
+                }
+                Candidate::Skipped(skipped) => on_skip(skipped),
+                Candidate::Empty => {}
+            }
+            Ok(())
+        })?;
+
+        // The map is done by now, so a checkout the cache can't be written
+        // to, like a read-only one, still gets it
+        if let Some(cache) = cache
+            && let Err(e) = cache.save()
+        {
+            eprintln!("Warning: the symbol cache was not saved: {:#}", e);
+        }
+        Ok(count)
+    }
+
+    /// Reads and parses one file the walk found, unless its content rules it
+    /// out.
+    fn read_candidate(
+        &self,
+        path: &Path,
+        lang: Language,
+        cache: Option<&mut SymbolCache>,
+    ) -> Candidate {
+        let path_str = path.display().to_string();
+        let content = match self.read_content(path, Some(lang)) {
+            Ok(content) => content,
+            Err(Exclusion::Content(reason)) => {
+                return Candidate::Skipped(Skipped {
+                    path: path_str,
+                    reason,
+                });
+            }
+            Err(_) => return Candidate::Empty,
+        };
+        let mut file = match cache {
+            Some(cache) => cache.get_or_parse(&path_str, &content, lang),
+            None => formatter::process_source_with(&path_str, &content, lang, &self.queries),
+        };
+        if self.code_blocks && lang == Language::Markdown {
+            markdown::add_code_blocks(&content, &mut file.symbols, &self.queries);
+        }
+        self.filter_symbols(&mut file);
+        if file.is_empty() {
+            Candidate::Empty
+        } else {
+            Candidate::Parsed(file)
+        }
+    }
+
+    /// Reads a file the walk found, to be parsed as `lang`, unless its size or
+    /// content rules it out.
+    fn read_content(&self, path: &Path, lang: Option<Language>) -> Result<String, Exclusion> {
+        if let Some(max) = self.max_file_size
+            && fs::metadata(path).is_ok_and(|meta| meta.len() > max)
+        {
+            return Err(Exclusion::Content(SkipReason::TooLarge));
+        }
+        let bytes = fs::read(path).map_err(|_| Exclusion::Unreadable)?;
+        if walk::is_binary_head(&bytes) {
+            return Err(Exclusion::Content(SkipReason::Binary));
+        }
+        let content = String::from_utf8(bytes).map_err(|_| Exclusion::Unreadable)?;
+        // A notebook's outputs, like a base64 image, are long lines of JSON
+        // that say nothing about its cells
+        if lang != Some(Language::Jupyter) && walk::is_minified(path, &content) {
+            return Err(Exclusion::Content(SkipReason::Minified));
+        }
+        Ok(content)
+    }
+
+    /// Lists every file the walk finds, with the language it would be parsed
+    /// as and, for files that wouldn't be mapped, why not. Nothing is parsed,
+    /// so files that would turn out to have no symbols are listed as mapped.
+    pub fn list(&self) -> Result<Vec<ListedFile>> {
+        let mut files = Vec::new();
+        self.for_each_entry(|mut file| {
+            if file.exclusion.is_none()
+                && let Err(exclusion) = self.read_content(&file.path, file.language)
+            {
+                file.exclusion = Some(exclusion);
+            }
+            files.push(file);
+            Ok(())
+        })?;
+        Ok(files)
+    }
+
+    /// Why the file at `path` would or wouldn't be mapped: the first walk
+    /// rule, path check or content check that rules it out, as [`Self::list`]
+    /// would report it. Nothing is parsed.
+    pub fn explain(&self, path: &Path) -> Result<ListedFile> {
+        let language = self.language.or_else(|| self.detector.detect(path));
+        let mut file = ListedFile {
+            path: path.to_path_buf(),
+            language,
+            exclusion: walk::explain(&self.root, path, &self.walk)?.map(Exclusion::Walk),
+        };
+        if file.exclusion.is_none() {
+            if path.is_dir() {
+                bail!("{} is a directory", path.display());
+            }
+            let (skip_canonical, generated) = self.path_filters()?;
+            file.exclusion = self
+                .path_exclusion(path, language, &skip_canonical, &generated)
+                .or_else(|| self.read_content(path, language).err());
+        }
+        Ok(file)
+    }
+
+    /// Visits every file the walk would map, with the language it would be
+    /// parsed as, without parsing it.
+    fn for_each_candidate(&self, mut f: impl FnMut(&Path, Language) -> Result<()>) -> Result<()> {
+        self.for_each_entry(|file| match (file.language, file.exclusion) {
+            (Some(lang), None) => f(&file.path, lang),
+            _ => Ok(()),
+        })
+    }
+
+    /// Visits every file the walk finds, noting why the path alone rules it
+    /// out, if it does.
+    fn for_each_entry(&self, mut f: impl FnMut(ListedFile) -> Result<()>) -> Result<()> {
+        let (skip_canonical, generated) = self.path_filters()?;
+
+        for entry in walk::collect_entries(&self.root, &self.walk)? {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let language = self.language.or_else(|| self.detector.detect(path));
+            let exclusion = self.path_exclusion(path, language, &skip_canonical, &generated);
+            f(ListedFile {
+                path: path.to_path_buf(),
+                language,
+                exclusion,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// The canonical skip paths and `.gitattributes` markings that
+    /// [`Self::path_exclusion`] checks against, loaded once per walk.
+    fn path_filters(&self) -> Result<(Vec<PathBuf>, GeneratedFiles)> {
+        let skip_canonical = self
+            .skip_paths
+            .iter()
+            .filter_map(|p| p.canonicalize().ok())
+            .collect();
+        let generated = if self.include_generated {
+            GeneratedFiles::default()
+        } else {
+            GeneratedFiles::load(Path::new(&self.root))?
+        };
+        Ok((skip_canonical, generated))
+    }
+
+    fn path_exclusion(
+        &self,
+        path: &Path,
+        language: Option<Language>,
+        skip_canonical: &[PathBuf],
+        generated: &GeneratedFiles,
+    ) -> Option<Exclusion> {
+        if let Some(file_name) = path.file_name().and_then(|n| n.to_str())
+            && self.skip_file_names.iter().any(|n| n == file_name)
+        {
+            return Some(Exclusion::Output);
+        }
+        if self
+            .cache_dir
+            .as_ref()
+            .is_some_and(|dir| path.starts_with(dir))
+        {
+            return Some(Exclusion::Cache);
+        }
+        if !skip_canonical.is_empty()
+            && let Ok(canonical) = path.canonicalize()
+            && skip_canonical.contains(&canonical)
+        {
+            return Some(Exclusion::Output);
+        }
+        if self.tests == TestFilter::Skip && testing::is_test_path(&path.to_string_lossy()) {
+            return Some(Exclusion::Test);
+        }
+        if language.is_none() {
+            return Some(Exclusion::UnsupportedLanguage);
+        }
+        let Some(head) = walk::read_head(path, generated::HEADER_BYTES) else {
+            return Some(Exclusion::Unreadable);
+        };
+        if !self.include_generated && self.is_generated(path, &head, generated) {
+            return Some(Exclusion::Generated);
+        }
+        None
+    }
+
+    fn is_generated(&self, path: &Path, head: &[u8], attributes: &GeneratedFiles) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        attributes.is_marked(relative)
+            || generated::has_generated_name(path)
+            || generated::has_generated_header(head)
+    }
+
+    /// Returns true if `path` names a file this builder deliberately skips,
+    /// such as the map it is writing to.
+    pub fn is_skipped(&self, path: &Path) -> bool {
+        if let Some(file_name) = path.file_name().and_then(|n| n.to_str())
+            && self.skip_file_names.iter().any(|n| n == file_name)
+        {
+            return true;
+        }
+        let Ok(canonical) = path.canonicalize() else {
+            return false;
+        };
+        self.skip_paths
+            .iter()
+            .filter_map(|p| p.canonicalize().ok())
+            .any(|p| p == canonical)
+    }
+
+    /// Walks the repository again, re-parsing only files that are new or
+    /// whose canonical path is in `changed`. Everything else is carried over
+    /// from `previous`, and files that disappeared are dropped.
+    pub fn rebuild(&self, previous: MapOutput, changed: &HashSet<PathBuf>) -> Result<MapOutput> {
+        let mut cached: HashMap<String, FileSymbols> = previous
+            .stats
+            .files
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect();
+        let mut stats = self.new_stats();
+
+        self.for_each_candidate(|path, lang| {
+            let key = path.display().to_string();
+            let is_changed = path
+                .canonicalize()
+                .is_ok_and(|canonical| changed.contains(&canonical));
+            let candidate = match cached.remove(&key) {
+                Some(file) if !is_changed => Candidate::Parsed(file),
+                _ => self.read_candidate(path, lang, None),
+            };
+            match candidate {
+                Candidate::Parsed(file) => stats.add_file(file),
+                Candidate::Skipped(skipped) => stats.skipped.push(skipped),
+                Candidate::Empty => {}
+            }
+            Ok(())
+        })?;
+
+        Ok(MapOutput {
+            root: self.root.clone(),
+            stats,
+        })
+    }
+
+    /// Applies the symbol-level filters to a freshly parsed file.
+    fn filter_symbols(&self, file: &mut FileSymbols) {
+        if !self.doc_links && file.language == Language::Markdown {
+            file.imports.clear();
+        }
+        match self.tests {
+            TestFilter::Include => {}
+            TestFilter::Skip => file.symbols.retain(|sym| !sym.test),
+            TestFilter::Only => {
+                file.symbols.retain(|sym| sym.test);
+                if file.symbols.is_empty() {
+                    file.imports.clear();
+                }
+            }
+        }
+    }
+
+    fn new_stats(&self) -> RepoStats {
+        let mut stats = RepoStats::with_tokenizer(self.tokenizer);
+        stats.weights = self.weights.clone();
+        stats
+    }
+
+    pub fn build(&self) -> Result<MapOutput> {
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        self.walk_files(
+            |file| {
+                files.push(file);
+                Ok(())
+            },
+            |file| skipped.push(file),
+        )?;
+
+        let mut stats = self.new_stats();
+        for file in files {
+            stats.add_file(file);
+        }
+        stats.skipped = skipped;
+
+        Ok(MapOutput {
+            root: self.root.clone(),
+            stats,
+        })
+    }
+}
+
+/// A file [`RepoMapBuilder::list`] found.
+#[derive(Clone, Debug)]
+pub struct ListedFile {
+    pub path: PathBuf,
+    /// What it would be parsed as, if its extension is supported.
+    pub language: Option<Language>,
+    /// Why it would be left out of the map, or `None` if it would be mapped.
+    pub exclusion: Option<Exclusion>,
+}
+
+/// Why a file would be left out of the map.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Exclusion {
+    /// Never visited by the walk; only [`RepoMapBuilder::explain`] reports
+    /// this.
+    Walk(WalkRule),
+    /// The map being written, or another path passed to
+    /// [`RepoMapBuilder::skip_path`] or [`RepoMapBuilder::skip_file_name`].
+    Output,
+    /// Inside the symbol cache directory.
+    Cache,
+    /// A test file, with [`TestFilter::Skip`].
+    Test,
+    /// No supported language for its extension.
+    UnsupportedLanguage,
+    /// Generated code; see [`RepoMapBuilder::include_generated`].
+    Generated,
+    /// Couldn't be read, or isn't UTF-8.
+    Unreadable,
+    /// Ruled out for its size or content, and reported in
+    /// [`RepoStats::skipped`] when mapping.
+    Content(SkipReason),
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Walk(rule) => rule.fmt(f),
+            Self::Output => f.write_str("output file"),
+            Self::Cache => f.write_str("cache"),
+            Self::Test => f.write_str("test file"),
+            Self::UnsupportedLanguage => f.write_str("unsupported extension"),
+            Self::Generated => f.write_str("generated"),
+            Self::Unreadable => f.write_str("unreadable"),
+            Self::Content(reason) => reason.fmt(f),
+        }
+    }
+}
+
+/// What became of one file the walk found.
+enum Candidate {
+    Parsed(FileSymbols),
+    Skipped(Skipped),
+    /// Unreadable, or nothing left to map after filtering.
+    Empty,
+}
+
+/// A generated repository map, ready to be rendered in any format.
+#[derive(Clone)]
+pub struct MapOutput {
+    pub root: String,
+    pub stats: RepoStats,
+}
+
+impl MapOutput {
+    /// Maps an in-memory file tree of `(path, contents)` pairs. Languages are
+    /// inferred from each path; unsupported files are skipped. Markdown links
+    /// are left out, as [`RepoMapBuilder::doc_links`] leaves them by default.
+    pub fn from_sources<I, P, C>(root: impl Into<String>, sources: I) -> Self
+    where
+        I: IntoIterator<Item = (P, C)>,
+        P: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let mut stats = RepoStats::new();
+        for (path, content) in sources {
+            let path = path.as_ref();
+            if let Some(lang) = languages::infer_language(Path::new(path)) {
+                let mut file = formatter::process_source(path, content.as_ref(), lang);
+                if lang == Language::Markdown {
+                    file.imports.clear();
+                }
+                if !file.is_empty() {
+                    stats.add_file(file);
+                }
+            }
+        }
+
+        Self {
+            root: root.into(),
+            stats,
+        }
+    }
+
+    pub fn files(&self) -> &[FileSymbols] {
+        &self.stats.files
+    }
+
+    /// Symbols whose name contains `query` (case-insensitive), optionally
+    /// restricted to one kind, raw (`function_item`) or normalized
+    /// (`function`).
+    pub fn find_symbols(&self, query: &str, kind: Option<&str>) -> Vec<(&FileSymbols, &Symbol)> {
+        let query = query.to_lowercase();
+        self.stats
+            .files
+            .iter()
+            .flat_map(|file| file.symbols.iter().map(move |sym| (file, sym)))
+            .filter(|(_, sym)| sym.name.to_lowercase().contains(&query))
+            .filter(|(_, sym)| {
+                kind.is_none_or(|k| sym.kind == k || sym.normalized_kind().as_str() == k)
+            })
+            .collect()
+    }
+
+    /// Orders files by [`rank::rank_files`], most important first. Ties keep
+    /// their path order.
+    pub fn sort_by_rank(&mut self) {
+        let ranks = rank::rank_files(&self.root, &self.stats.files, &self.stats.weights);
+        let mut ranked: Vec<(f64, FileSymbols)> =
+            ranks.into_iter().zip(self.stats.files.drain(..)).collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        self.stats.files = ranked.into_iter().map(|(_, file)| file).collect();
+    }
+
+    /// Keeps only the `max` most important files by [`rank::rank_files`],
+    /// in their current order, and records the cutoff for the header.
+    /// Returns how many files were dropped.
+    pub fn keep_top_files(&mut self, max: usize) -> usize {
+        let total = self.stats.files.len();
+        if total <= max {
+            return 0;
+        }
+        let ranks = rank::rank_files(&self.root, &self.stats.files, &self.stats.weights);
+        let mut order: Vec<usize> = (0..total).collect();
+        order.sort_by(|&a, &b| ranks[b].total_cmp(&ranks[a]));
+        let mut keep = vec![false; total];
+        for &i in &order[..max] {
+            keep[i] = true;
+        }
+        let mut index = 0;
+        self.stats.files.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+
+        let dropped = total - max;
+        self.stats.files_before_cutoff = Some(self.stats.file_count);
+        self.stats.file_count -= dropped;
+        dropped
+    }
+
+    /// Folds directories with more than `threshold` files into summary lines,
+    /// except under the `expand` globs; see [`collapse::collapse_dirs`].
+    pub fn collapse_dirs(&mut self, threshold: usize, expand: &[String]) -> Result<usize> {
+        collapse::collapse_dirs(&self.root, &mut self.stats, threshold, expand)
+    }
+
+    /// Drops every symbol whose [`Symbol::normalized_kind`] is not one of
+    /// `kinds`. Files stay listed even when none of their symbols remain.
+    /// Returns how many symbols were dropped.
+    pub fn retain_kinds(&mut self, kinds: &[SymbolKind]) -> usize {
+        let mut dropped = 0;
+        for file in &mut self.stats.files {
+            let before = file.symbols.len();
+            file.symbols
+                .retain(|sym| kinds.contains(&sym.normalized_kind()));
+            dropped += before - file.symbols.len();
+        }
+        dropped
+    }
+
+    /// Keeps the `max` most important symbols of each file; see
+    /// [`budget::cap_symbols`].
+    pub fn cap_symbols(&mut self, max: usize) -> usize {
+        budget::cap_symbols(&mut self.stats, max)
+    }
+
+    /// Narrows detail to the files at or under `paths` and their import
+    /// neighbors; see [`focus::apply_focus`].
+    pub fn focus(&mut self, paths: &[PathBuf]) -> Vec<Detail> {
+        focus::apply_focus(&self.root, &mut self.stats, paths)
+    }
+
+    /// Trims the map until its Markdown rendering fits in `max_tokens`; see
+    /// [`budget::fit_to_budget`].
+    pub fn fit_to_budget(&mut self, max_tokens: usize, show_summary: bool) -> Omitted {
+        budget::fit_to_budget(&self.root, &mut self.stats, max_tokens, show_summary)
+    }
+
+    /// Splits the Markdown map into parts of at most `max_tokens` each; see
+    /// [`split::split_by_tokens`].
+    pub fn split(&self, max_tokens: usize) -> Vec<MapOutput> {
+        split::split_by_tokens(&self.root, &self.stats, max_tokens)
+            .into_iter()
+            .map(|stats| MapOutput {
+                root: self.root.clone(),
+                stats,
+            })
+            .collect()
+    }
+
+    /// Writes this map as part `index` (1-based) of `total` split parts.
+    pub fn write_part(&self, writer: &mut impl Write, index: usize, total: usize) -> Result<()> {
+        split::write_part(writer, &self.root, &self.stats, index, total)?;
+        Ok(())
+    }
+
+    pub fn to_markdown(&self, show_summary: bool) -> String {
+        formatter::assemble_final_map(&self.root, &self.stats, show_summary)
+    }
+
+    pub fn write_markdown(&self, writer: &mut impl Write, show_summary: bool) -> Result<()> {
+        formatter::write_final_map(writer, &self.root, &self.stats, show_summary)?;
+        Ok(())
+    }
+
+    /// Mermaid `graph TD` block of file import relationships.
+    pub fn to_mermaid(&self) -> String {
+        DependencyGraph::build(&self.root, &self.stats.files).to_mermaid(&self.stats.files)
+    }
+
+    pub fn render_template(&self, template_path: &Path) -> Result<String> {
+        template::render_template(template_path, &self.root, &self.stats)
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn write_sqlite(&self, path: &Path) -> Result<()> {
+        sqlite::write_database(path, &self.stats)
+    }
+
+    /// Renders a text format with its default options.
+    pub fn render(&self, format: OutputFormat) -> Result<String> {
+        let mut buffer = Vec::new();
+        self.write(format, &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+
+    /// Writes a text format to `writer` as it is produced. Markdown, JSON,
+    /// YAML, CSV, and NDJSON never build the full document in memory.
+    pub fn write(&self, format: OutputFormat, writer: &mut impl Write) -> Result<()> {
+        match format {
+            OutputFormat::Markdown => self.write_markdown(writer, false)?,
+            OutputFormat::Json => formatter::write_json(writer, &self.root, &self.stats)?,
+            OutputFormat::Yaml => formatter::write_yaml(writer, &self.root, &self.stats)?,
+            OutputFormat::Ndjson => {
+                for file in &self.stats.files {
+                    formatter::write_ndjson_line(writer, file)?;
+                }
+            }
+            OutputFormat::Csv => formatter::write_csv(writer, &self.stats)?,
+            OutputFormat::Aider => {
+                writer.write_all(aider::assemble_aider(&self.stats).as_bytes())?
+            }
+            OutputFormat::Ctags => {
+                writer.write_all(ctags::assemble_ctags(&self.stats).as_bytes())?
+            }
+            OutputFormat::Html => {
+                writer.write_all(html::assemble_html(&self.root, &self.stats).as_bytes())?
+            }
+            OutputFormat::Sqlite => bail!("sqlite output must be written with write_sqlite"),
+        }
+        Ok(())
+    }
+
+    pub fn estimate_tokens(&self, rendered: &str) -> usize {
+        self.stats.estimate_tokens(rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+    use std::fs;
+
+    #[test]
+    fn test_builder_maps_and_skips_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn visible() {}").unwrap();
+        fs::write(dir.path().join("skipped.rs"), "fn by_path() {}").unwrap();
+        fs::write(dir.path().join("CLAUDE.md"), "# By name").unwrap();
+        fs::write(dir.path().join("notes.txt"), "unsupported").unwrap();
+
+        let map = RepoMapBuilder::new(dir.path().to_str().unwrap())
+            .skip_file_name("CLAUDE.md")
+            .skip_path(dir.path().join("skipped.rs"))
+            .build()
+            .unwrap();
+
+        assert_eq!(map.stats.file_count, 1);
+        assert_eq!(map.files()[0].symbols[0].name, "visible");
+        assert!(
+            map.render(OutputFormat::Markdown)
+                .unwrap()
+                .contains("visible")
+        );
+        assert!(map.render(OutputFormat::Sqlite).is_err());
+    }
+
+    #[test]
+    fn test_test_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("tests")).unwrap();
+        fs::write(dir.path().join("tests/api.rs"), "fn api() {}").unwrap();
+        fs::write(
+            dir.path().join("lib.rs"),
+            "fn run() {}\n#[cfg(test)]\nmod tests {\n    fn check() {}\n}\n",
+        )
+        .unwrap();
+
+        let map = RepoMapBuilder::new(dir.path().to_str().unwrap())
+            .tests(TestFilter::Skip)
+            .build()
+            .unwrap();
+
+        assert_eq!(map.stats.file_count, 1);
+        let names: Vec<&str> = map.files()[0]
+            .symbols
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["run"]);
+
+        let inventory = RepoMapBuilder::new(dir.path().to_str().unwrap())
+            .tests(TestFilter::Only)
+            .build()
+            .unwrap();
+        let names: Vec<&str> = inventory
+            .files()
+            .iter()
+            .flat_map(|f| &f.symbols)
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["tests", "check", "api"]);
+    }
+
+    #[test]
+    fn test_skips_generated_files_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join(".gitattributes"),
+            "schema.rs linguist-generated\n",
+        )
+        .unwrap();
+        fs::write(root.join("schema.rs"), "fn table() {}").unwrap();
+        fs::write(root.join("api.pb.go"), "package api\nfunc Call() {}").unwrap();
+        fs::write(
+            root.join("client.py"),
+            "# @generated\ndef call():\n    pass\n",
+        )
+        .unwrap();
+        fs::write(root.join("lib.rs"), "fn handwritten() {}").unwrap();
+
+        let map = RepoMapBuilder::new(root.to_str().unwrap()).build().unwrap();
+        assert_eq!(map.stats.file_count, 1);
+        assert_eq!(map.files()[0].symbols[0].name, "handwritten");
+
+        let map = RepoMapBuilder::new(root.to_str().unwrap())
+            .include_generated(true)
+            .build()
+            .unwrap();
+        assert_eq!(map.stats.file_count, 4);
+    }
+
+    #[test]
+    fn test_doc_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("docs")).unwrap();
+        fs::write(
+            root.join("docs/guide.md"),
+            "# Guide\nSee [the API](./api.md).\n",
+        )
+        .unwrap();
+        fs::write(root.join("docs/api.md"), "# API\n").unwrap();
+
+        let map = RepoMapBuilder::new(root.to_str().unwrap()).build().unwrap();
+        assert!(map.files().iter().all(|f| f.imports.is_empty()));
+        assert!(!map.to_mermaid().contains("-->"));
+
+        let map = RepoMapBuilder::new(root.to_str().unwrap())
+            .doc_links(true)
+            .build()
+            .unwrap();
+        let guide = map
+            .files()
+            .iter()
+            .find(|f| f.path.ends_with("guide.md"))
+            .unwrap();
+        assert_eq!(guide.imports, vec!["./api.md"]);
+        assert!(map.to_mermaid().contains("-->"));
+    }
+
+    #[test]
+    fn test_notebook_with_image_output_is_mapped() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = "iVBORw0KGgo".repeat(2000);
+        let notebook = format!(
+            r#"{{
+ "cells": [
+  {{"cell_type": "code", "metadata": {{}}, "source": ["def plot(df):\n", "    return df.plot()\n"],
+   "outputs": [{{"output_type": "display_data", "metadata": {{}}, "data": {{"image/png": "{png}"}}}}]}}
+ ],
+ "metadata": {{"language_info": {{"name": "python"}}}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}}"#
+        );
+        fs::write(dir.path().join("analysis.ipynb"), notebook).unwrap();
+
+        let map = RepoMapBuilder::new(dir.path().to_str().unwrap())
+            .build()
+            .unwrap();
+        assert!(map.stats.skipped.is_empty());
+        assert_eq!(map.files()[0].symbols[0].name, "plot");
+    }
+
+    #[test]
+    fn test_minified_files_are_reported_not_mapped() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let bundle = format!("function a(){{{}}}", "b();".repeat(1000));
+        fs::write(root.join("bundle.js"), bundle).unwrap();
+        fs::write(root.join("app.js"), "function main() {}\n").unwrap();
+        fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G', 0]).unwrap();
+
+        let map = RepoMapBuilder::new(root.to_str().unwrap()).build().unwrap();
+        assert_eq!(map.stats.file_count, 1);
+        assert_eq!(map.stats.skipped.len(), 1);
+        assert_eq!(map.stats.skipped[0].reason, SkipReason::Minified);
+
+        assert!(
+            map.to_markdown(true)
+                .contains("_Skipped 1 file (1 minified)._")
+        );
+        assert!(
+            map.render(OutputFormat::Json)
+                .unwrap()
+                .contains("\"reason\": \"minified\"")
+        );
+
+        let rebuilt = RepoMapBuilder::new(root.to_str().unwrap())
+            .rebuild(map, &HashSet::new())
+            .unwrap();
+        assert_eq!(rebuilt.stats.skipped.len(), 1);
+
+        let map = RepoMapBuilder::new(root.to_str().unwrap())
+            .max_file_size(Some(100))
+            .build()
+            .unwrap();
+        assert_eq!(map.stats.skipped[0].reason, SkipReason::TooLarge);
+        let markdown = map.to_markdown(false);
+        assert!(markdown.ends_with(&format!(
+            "## Skipped files\n- {} (too large)\n",
+            root.join("bundle.js").display()
+        )));
+
+        fs::write(root.join("data.py"), [b'x', 0, 1, 2]).unwrap();
+        let map = RepoMapBuilder::new(root.to_str().unwrap()).build().unwrap();
+        let reasons: Vec<SkipReason> = map.stats.skipped.iter().map(|s| s.reason).collect();
+        assert_eq!(reasons, vec![SkipReason::Minified, SkipReason::Binary]);
+    }
+
+    #[test]
+    fn test_list_explains_each_file_without_parsing() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("app.py"), "def main():\n    pass\n").unwrap();
+        fs::write(root.join("notes.txt"), "todo\n").unwrap();
+        fs::write(root.join("api.pb.go"), "package api\n").unwrap();
+        fs::write(root.join("data.rs"), [b'x', 0]).unwrap();
+        fs::write(root.join("repomap.md"), "# map\n").unwrap();
+
+        let files = RepoMapBuilder::new(root.to_str().unwrap())
+            .skip_file_name("repomap.md")
+            .list()
+            .unwrap();
+        let listed: Vec<(String, Option<Language>, Option<Exclusion>)> = files
+            .into_iter()
+            .map(|f| {
+                let name = f.path.file_name().unwrap().to_string_lossy().into_owned();
+                (name, f.language, f.exclusion)
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (
+                    "api.pb.go".into(),
+                    Some(Language::Go),
+                    Some(Exclusion::Generated)
+                ),
+                ("app.py".into(), Some(Language::Python), None),
+                (
+                    "data.rs".into(),
+                    Some(Language::Rust),
+                    Some(Exclusion::Content(SkipReason::Binary))
+                ),
+                (
+                    "notes.txt".into(),
+                    None,
+                    Some(Exclusion::UnsupportedLanguage)
+                ),
+                (
+                    "repomap.md".into(),
+                    Some(Language::Markdown),
+                    Some(Exclusion::Output)
+                ),
+            ]
+        );
+
+        let builder = RepoMapBuilder::new(root.to_str().unwrap()).exclude("*.py");
+        let explained = builder.explain(&root.join("app.py")).unwrap();
+        assert_eq!(
+            explained.exclusion,
+            Some(Exclusion::Walk(WalkRule::Excluded("*.py".into())))
+        );
+        let explained = builder.explain(&root.join("data.rs")).unwrap();
+        assert_eq!(
+            explained.exclusion,
+            Some(Exclusion::Content(SkipReason::Binary))
+        );
+        assert!(builder.explain(root).is_err());
+    }
+
+    #[test]
+    fn test_write_streams_each_format() {
+        let map = MapOutput::from_sources(".", [("lib.rs", "use std::io;\nfn run() {}")]);
+
+        for &format in OutputFormat::value_variants() {
+            if format == OutputFormat::Sqlite {
+                continue;
+            }
+            let mut streamed = Vec::new();
+            map.write(format, &mut streamed).unwrap();
+            assert!(String::from_utf8(streamed).unwrap().contains("run"));
+        }
+    }
+
+    #[test]
+    fn test_rebuild_reparses_only_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept.rs");
+        let edited = dir.path().join("edited.rs");
+        let removed = dir.path().join("removed.rs");
+        fs::write(&kept, "fn kept() {}").unwrap();
+        fs::write(&edited, "fn before() {}").unwrap();
+        fs::write(&removed, "fn removed() {}").unwrap();
+
+        let builder = RepoMapBuilder::new(dir.path().to_str().unwrap());
+        let map = builder.build().unwrap();
+        assert_eq!(map.stats.file_count, 3);
+
+        // Not listed as changed, so the stale symbols must survive
+        fs::write(&kept, "fn not_reparsed() {}").unwrap();
+        fs::write(&edited, "fn after() {}").unwrap();
+        fs::remove_file(&removed).unwrap();
+        fs::write(dir.path().join("added.rs"), "fn added() {}").unwrap();
+
+        let changed = HashSet::from([edited.canonicalize().unwrap()]);
+        let map = builder.rebuild(map, &changed).unwrap();
+
+        let mut names: Vec<&str> = map
+            .files()
+            .iter()
+            .map(|f| f.symbols[0].name.as_str())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["added", "after", "kept"]);
+        assert_eq!(map.stats.file_count, 3);
+    }
+
+    #[test]
+    fn test_cache_reuses_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join(".repomap/cache");
+        fs::write(dir.path().join("lib.rs"), "fn original() {}").unwrap();
+
+        let builder =
+            RepoMapBuilder::new(dir.path().to_str().unwrap()).cache_dir(Some(cache_dir.clone()));
+        builder.build().unwrap();
+
+        // A hit returns whatever the cache holds, so tamper with it to prove
+        // the file was not parsed again
+        let cache_file = cache_dir.join("symbols.json");
+        let json = fs::read_to_string(&cache_file).unwrap();
+        fs::write(&cache_file, json.replace("\"original\"", "\"from_cache\"")).unwrap();
+        assert_eq!(
+            builder.build().unwrap().files()[0].symbols[0].name,
+            "from_cache"
+        );
+
+        fs::write(dir.path().join("lib.rs"), "fn edited() {}").unwrap();
+        assert_eq!(
+            builder.build().unwrap().files()[0].symbols[0].name,
+            "edited"
+        );
+    }
+
+    #[test]
+    fn test_read_only_root_is_still_mapped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn mapped() {}").unwrap();
+        // A file where the cache directory would go, so creating it fails even
+        // for a user the permissions don't stop
+        fs::write(dir.path().join(".repomap"), "").unwrap();
+        let mut readonly = fs::metadata(dir.path()).unwrap().permissions();
+        readonly.set_readonly(true);
+        fs::set_permissions(dir.path(), readonly.clone()).unwrap();
+
+        let map = RepoMapBuilder::new(dir.path().to_str().unwrap())
+            .cache_dir(Some(dir.path().join(".repomap/cache")))
+            .build();
+        #[allow(clippy::permissions_set_readonly_false)]
+        readonly.set_readonly(false);
+        fs::set_permissions(dir.path(), readonly).unwrap();
+        assert_eq!(map.unwrap().files()[0].symbols[0].name, "mapped");
+    }
+
+    #[test]
+    fn test_find_symbols() {
+        let map = MapOutput::from_sources(
+            ".",
+            [(
+                "lib.rs",
+                "struct Parser {}\nimpl Parser { fn parse(&self) {} }\nfn reparse() {}",
+            )],
+        );
+
+        let names: Vec<&str> = map
+            .find_symbols("PARS", None)
+            .iter()
+            .map(|(_, sym)| sym.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Parser", "parse", "reparse"]);
+        assert_eq!(map.find_symbols("pars", Some("struct_item")).len(), 1);
+        assert_eq!(map.find_symbols("pars", Some("method")).len(), 1);
+    }
+
+    #[test]
+    fn test_retain_kinds_uses_normalized_kinds() {
+        let mut map = MapOutput::from_sources(
+            ".",
+            [
+                ("lib.rs", "struct Config {}\nfn load() {}\n"),
+                (
+                    "app.py",
+                    "class App:\n    def run(self):\n        pass\ndef main():\n    pass\n",
+                ),
+            ],
+        );
+
+        assert_eq!(
+            map.retain_kinds(&[SymbolKind::Function, SymbolKind::Class]),
+            2
+        );
+        let names: Vec<&str> = map
+            .files()
+            .iter()
+            .flat_map(|f| &f.symbols)
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["load", "App", "main"]);
+        assert_eq!(
+            SymbolKind::from_str("functions", true),
+            Ok(SymbolKind::Function)
+        );
+    }
+
+    #[test]
+    fn test_from_sources_maps_virtual_tree() {
+        let map = MapOutput::from_sources(
+            "app",
+            [
+                ("app/main.py", "import os\ndef main():\n    pass\n"),
+                ("app/README.md", "# App\n"),
+                ("app/data.bin", "ignored"),
+            ],
+        );
+
+        assert_eq!(map.stats.file_count, 2);
+        assert_eq!(map.files()[0].symbols[0].name, "main");
+        assert_eq!(map.files()[0].imports, vec!["os"]);
+    }
+}
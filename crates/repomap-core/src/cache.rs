@@ -0,0 +1,168 @@
+use crate::formatter::{self, FileSymbols};
+use crate::languages::{self, Language};
+use crate::queries::QueryRegistry;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+const CACHE_FILE: &str = "symbols.json";
+
+/// Bumped when the cache layout changes without a release.
+const CACHE_FORMAT: &str = "3";
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    fingerprint: String,
+    files: HashMap<String, CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    hash: String,
+    file: FileSymbols,
+    // Rendered output skips these, so they are stored alongside the file
+    references: Vec<SmolStr>,
+}
+
+/// Per-file extraction results keyed by path and a BLAKE3 hash of the
+/// contents. Only files looked up since [`SymbolCache::load`] are saved, so
+/// deleted files fall out on the next run.
+pub struct SymbolCache {
+    dir: PathBuf,
+    previous: HashMap<String, CacheEntry>,
+    current: HashMap<String, CacheEntry>,
+    queries: QueryRegistry,
+}
+
+impl SymbolCache {
+    /// Loads the cache in `dir`. A missing or unreadable cache, or one
+    /// written with different queries or grammars, starts out empty. Files
+    /// are parsed with `queries`.
+    pub fn load(dir: impl Into<PathBuf>, queries: QueryRegistry) -> Self {
+        let dir = dir.into();
+        let previous = fs::read_to_string(dir.join(CACHE_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str::<CacheFile>(&json).ok())
+            .filter(|cache| cache.fingerprint == fingerprint(&queries))
+            .map(|cache| cache.files)
+            .unwrap_or_default();
+
+        Self {
+            dir,
+            previous,
+            current: HashMap::new(),
+            queries,
+        }
+    }
+
+    /// Returns the cached result for `path` if `content` is unchanged,
+    /// otherwise parses it and records the new result.
+    pub fn get_or_parse(&mut self, path: &str, content: &str, lang: Language) -> FileSymbols {
+        let hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+
+        let entry = match self.previous.remove(path) {
+            Some(entry) if entry.hash == hash && entry.file.language == lang => entry,
+            _ => {
+                let file = formatter::process_source_with(path, content, lang, &self.queries);
+                CacheEntry {
+                    hash,
+                    references: file.references.clone(),
+                    file,
+                }
+            }
+        };
+        let mut file = entry.file.clone();
+        file.references = entry.references.clone();
+        self.current.insert(path.to_string(), entry);
+        file
+    }
+
+    pub fn save(self) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create cache directory {}", self.dir.display()))?;
+        // Keep the cache out of version control without touching the repo's .gitignore
+        let gitignore = self.dir.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(&gitignore, "*\n")?;
+        }
+
+        let cache = CacheFile {
+            fingerprint: fingerprint(&self.queries),
+            files: self.current,
+        };
+        let path = self.dir.join(CACHE_FILE);
+        fs::write(&path, serde_json::to_string(&cache)?)
+            .with_context(|| format!("failed to write symbol cache {}", path.display()))
+    }
+}
+
+/// Changes whenever repomap, its queries, or its grammars change, which
+/// invalidates every cached result.
+fn fingerprint(queries: &QueryRegistry) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(CACHE_FORMAT.as_bytes());
+    for &lang in Language::value_variants() {
+        hasher.update(lang.to_string().as_bytes());
+        if let Some(ts_lang) = languages::get_ts_language(lang) {
+            hasher.update(
+                format!(":{}:{}", ts_lang.abi_version(), ts_lang.node_kind_count()).as_bytes(),
+            );
+        }
+        hasher.update(queries.symbol_query(lang).unwrap_or_default().as_bytes());
+        hasher.update(queries.import_query(lang).unwrap_or_default().as_bytes());
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queries::QueryMode;
+
+    #[test]
+    fn test_cache_round_trip_and_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut cache = SymbolCache::load(dir.path(), QueryRegistry::default());
+        let file = cache.get_or_parse("lib.rs", "fn cached() {}", Language::Rust);
+        assert_eq!(file.symbols[0].name, "cached");
+        cache.save().unwrap();
+        assert!(dir.path().join(".gitignore").exists());
+
+        let mut cache = SymbolCache::load(dir.path(), QueryRegistry::default());
+        assert!(cache.previous.contains_key("lib.rs"));
+        let file = cache.get_or_parse("lib.rs", "fn cached() {}", Language::Rust);
+        assert_eq!(file.references, vec!["cached"]);
+        let file = cache.get_or_parse("lib.rs", "fn edited() {}", Language::Rust);
+        assert_eq!(file.symbols[0].name, "edited");
+
+        cache.save().unwrap();
+
+        // A user query changes what gets extracted
+        let mut queries = QueryRegistry::default();
+        queries
+            .add(
+                Language::Rust,
+                "(trait_item name: (type_identifier) @name) @item",
+                QueryMode::Extend,
+            )
+            .unwrap();
+        assert!(SymbolCache::load(dir.path(), queries).previous.is_empty());
+
+        let cache_path = dir.path().join(CACHE_FILE);
+        let mut json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&cache_path).unwrap()).unwrap();
+        json["fingerprint"] = "older-repomap".into();
+        fs::write(&cache_path, json.to_string()).unwrap();
+        assert!(
+            SymbolCache::load(dir.path(), QueryRegistry::default())
+                .previous
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::formatter::RepoStats;
+use crate::graph;
+use crate::languages::Language;
+use crate::parser::Symbol;
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+use smol_str::SmolStr;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const TOP_SYMBOLS: usize = 5;
+
+/// A directory whose files were folded into one line by [`collapse_dirs`].
+#[derive(Serialize, Clone, Debug)]
+pub struct DirSummary {
+    /// Relative to the mapped root.
+    pub path: String,
+    pub files: usize,
+    pub lines: usize,
+    /// Files per language, most common first.
+    pub languages: Vec<(Language, usize)>,
+    /// The largest top-level symbols, by line count.
+    pub top_symbols: Vec<SmolStr>,
+}
+
+impl DirSummary {
+    pub fn write_markdown(&self, writer: &mut impl Write) -> io::Result<()> {
+        let languages: Vec<String> = self
+            .languages
+            .iter()
+            .map(|(lang, count)| format!("{} {}%", lang, count * 100 / self.files))
+            .collect();
+        write!(
+            writer,
+            "\n## {}/ ({} files collapsed: {}",
+            self.path,
+            self.files,
+            languages.join(", ")
+        )?;
+        if !self.top_symbols.is_empty() {
+            write!(writer, "; top: {}", self.top_symbols.join(", "))?;
+        }
+        writeln!(writer, ")")
+    }
+}
+
+/// Folds every directory holding more than `threshold` mapped files into a
+/// [`DirSummary`], topmost directory first, so a huge subtree costs one line.
+/// Files at or under a path matching one of the `expand` globs keep their
+/// detail and do not count towards the threshold. Returns how many files
+/// were folded.
+pub fn collapse_dirs(
+    root: &str,
+    stats: &mut RepoStats,
+    threshold: usize,
+    expand: &[String],
+) -> Result<usize> {
+    let expand = build_globset(expand)?;
+    let root = graph::normalize(Path::new(root));
+    let relative: Vec<PathBuf> = stats
+        .files
+        .iter()
+        .map(|f| {
+            let path = graph::normalize(Path::new(&f.path));
+            match path.strip_prefix(&root) {
+                Ok(rest) => rest.to_path_buf(),
+                Err(_) => path,
+            }
+        })
+        .collect();
+    let expanded: Vec<bool> = relative
+        .iter()
+        .map(|path| path.ancestors().any(|a| expand.is_match(a)))
+        .collect();
+
+    let mut counts: HashMap<&Path, usize> = HashMap::new();
+    for (path, _) in relative.iter().zip(&expanded).filter(|(_, e)| !**e) {
+        for dir in parent_dirs(path) {
+            *counts.entry(dir).or_default() += 1;
+        }
+    }
+
+    let mut folded: BTreeMap<PathBuf, Vec<usize>> = BTreeMap::new();
+    for (i, path) in relative.iter().enumerate() {
+        if expanded[i] {
+            continue;
+        }
+        let mut dirs = parent_dirs(path);
+        dirs.reverse();
+        if let Some(dir) = dirs.into_iter().find(|dir| counts[dir] > threshold) {
+            folded.entry(dir.to_path_buf()).or_default().push(i);
+        }
+    }
+    if folded.is_empty() {
+        return Ok(0);
+    }
+
+    let mut remove = vec![false; stats.files.len()];
+    for (dir, indices) in &folded {
+        stats.collapsed_dirs.push(summarize(dir, stats, indices));
+        for &i in indices {
+            remove[i] = true;
+        }
+    }
+    let mut index = 0;
+    stats.files.retain(|_| {
+        index += 1;
+        !remove[index - 1]
+    });
+
+    Ok(remove.iter().filter(|&&r| r).count())
+}
+
+/// Every directory above `path`, nearest first, stopping below the root.
+fn parent_dirs(path: &Path) -> Vec<&Path> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| !dir.as_os_str().is_empty())
+        .collect()
+}
+
+fn summarize(dir: &Path, stats: &RepoStats, indices: &[usize]) -> DirSummary {
+    let files: Vec<_> = indices.iter().map(|&i| &stats.files[i]).collect();
+
+    let mut languages: Vec<(Language, usize)> = Vec::new();
+    for file in &files {
+        match languages
+            .iter_mut()
+            .find(|(lang, _)| *lang == file.language)
+        {
+            Some((_, count)) => *count += 1,
+            None => languages.push((file.language, 1)),
+        }
+    }
+    languages.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+
+    let mut top: Vec<&Symbol> = files
+        .iter()
+        .flat_map(|f| &f.symbols)
+        .filter(|sym| sym.parent.is_none())
+        .collect();
+    top.sort_by_key(|sym| std::cmp::Reverse(sym.end_line - sym.line));
+    let mut top_symbols: Vec<SmolStr> = Vec::new();
+    for sym in top {
+        if top_symbols.len() == TOP_SYMBOLS {
+            break;
+        }
+        if !top_symbols.contains(&sym.name) {
+            top_symbols.push(sym.name.clone());
+        }
+    }
+
+    DirSummary {
+        path: dir.display().to_string(),
+        files: files.len(),
+        lines: files.iter().map(|f| f.lines).sum(),
+        languages,
+        top_symbols,
+    }
+}
+
+fn build_globset(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).with_context(|| format!("invalid glob '{}'", pattern))?);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapOutput;
+
+    fn sample() -> MapOutput {
+        let mut sources = vec![("src/main.rs".to_string(), "fn main() {}\n".to_string())];
+        for i in 0..4 {
+            sources.push((
+                format!("vendor/gen/file_{i}.rs"),
+                format!("struct Big{i} {{\n}}\nfn small_{i}() {{}}\n"),
+            ));
+        }
+        sources.push((
+            "vendor/keep/util.py".to_string(),
+            "def helper():\n    pass\n".to_string(),
+        ));
+        MapOutput::from_sources(".", sources)
+    }
+
+    #[test]
+    fn test_collapses_topmost_large_directory() {
+        let mut map = sample();
+        assert_eq!(map.collapse_dirs(3, &[]).unwrap(), 5);
+
+        assert_eq!(map.files().len(), 1);
+        assert_eq!(map.stats.file_count, 6);
+        let summary = &map.stats.collapsed_dirs[0];
+        assert_eq!((summary.path.as_str(), summary.files), ("vendor", 5));
+        assert_eq!(
+            summary.languages,
+            vec![(Language::Rust, 4), (Language::Python, 1)]
+        );
+        assert_eq!(summary.top_symbols[0], "Big0");
+        assert!(
+            map.to_markdown(false)
+                .contains("## vendor/ (5 files collapsed: rust 80%, python 20%; top: Big0,")
+        );
+    }
+
+    #[test]
+    fn test_expand_keeps_detail() {
+        let mut map = sample();
+        let expand = ["vendor/keep".to_string()];
+        assert_eq!(map.collapse_dirs(3, &expand).unwrap(), 4);
+        assert!(map.files().iter().any(|f| f.path == "vendor/keep/util.py"));
+
+        assert!(sample().collapse_dirs(3, &["[".to_string()]).is_err());
+        assert_eq!(sample().collapse_dirs(10, &[]).unwrap(), 0);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::formatter::RepoStats;
+use crate::parser::{Symbol, SymbolKind};
+
+/// The universal-ctags kind name: the normalized kind, or the raw node kind
+/// when there is no better name.
+fn ctags_kind(sym: &Symbol) -> &str {
+    match sym.normalized_kind() {
+        SymbolKind::Other => &sym.kind,
+        kind => kind.as_str(),
+    }
+}
+
+/// Builds a sorted universal-ctags file (extended format) from the map.
+pub fn assemble_ctags(stats: &RepoStats) -> String {
+    let mut tags: Vec<String> = Vec::new();
+
+    for file in &stats.files {
+        let path = file.path.strip_prefix("./").unwrap_or(&file.path);
+        for sym in &file.symbols {
+            let mut tag = format!(
+                "{}\t{}\t{};\"\tkind:{}\tline:{}",
+                sym.name,
+                path,
+                sym.line,
+                ctags_kind(sym),
+                sym.line
+            );
+            if let Some(parent) = &sym.parent {
+                tag.push_str(&format!("\tclass:{}", parent.replace('\t', " ")));
+            }
+            tag.push_str(&format!("\tend:{}", sym.end_line));
+            tags.push(tag);
+        }
+    }
+
+    tags.sort();
+
+    let mut output = String::from(
+        "!_TAG_FILE_FORMAT\t2\t/extended format; --format=1 will not append ;\" to lines/\n\
+         !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n\
+         !_TAG_PROGRAM_NAME\trepomap\t//\n",
+    );
+    output.push_str(&format!(
+        "!_TAG_PROGRAM_VERSION\t{}\t//\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    for tag in tags {
+        output.push_str(&tag);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::FileSymbols;
+    use crate::languages::Language;
+
+    #[test]
+    fn test_ctags_lines_are_sorted_and_scoped() {
+        let mut stats = RepoStats::new();
+        stats.add_file(FileSymbols {
+            path: "./src/config.rs".to_string(),
+            language: Language::Rust,
+            lines: 20,
+            imports: vec![],
+            symbols: vec![
+                Symbol {
+                    name: "load".into(),
+                    parent: Some("Config".into()),
+                    line: 10,
+                    kind: "function_item".into(),
+                    end_line: 14,
+                    test: false,
+                },
+                Symbol {
+                    name: "Config".into(),
+                    parent: None,
+                    line: 1,
+                    kind: "struct_item".into(),
+                    end_line: 4,
+                    test: false,
+                },
+            ],
+            omitted: 0,
+            references: vec![],
+        });
+
+        let tags = assemble_ctags(&stats);
+        let lines: Vec<&str> = tags.lines().filter(|l| !l.starts_with("!_")).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Config\tsrc/config.rs\t1;\"\tkind:struct\tline:1\tend:4",
+                "load\tsrc/config.rs\t10;\"\tkind:method\tline:10\tclass:Config\tend:14",
+            ]
+        );
+        assert!(tags.starts_with("!_TAG_FILE_FORMAT\t2\t"));
+    }
+}
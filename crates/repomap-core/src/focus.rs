@@ -0,0 +1,112 @@
+use crate::formatter::{FileSymbols, RepoStats};
+use crate::graph::{self, DependencyGraph};
+use std::path::{Path, PathBuf};
+
+/// How much of a file [`apply_focus`] keeps.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Detail {
+    /// Every symbol and import.
+    Full,
+    /// Imports and top-level symbols; methods and other nested symbols go.
+    TopLevel,
+    /// Only the `## path` line.
+    Headline,
+}
+
+/// Keeps full detail for the files at or under `paths`, top-level symbols for
+/// files that import or are imported by them, and headlines for the rest.
+/// Returns the detail each file got, in file order.
+pub fn apply_focus(root: &str, stats: &mut RepoStats, paths: &[PathBuf]) -> Vec<Detail> {
+    let paths: Vec<PathBuf> = paths.iter().map(|p| graph::normalize(p)).collect();
+    let focused: Vec<bool> = stats
+        .files
+        .iter()
+        .map(|f| {
+            let path = graph::normalize(Path::new(&f.path));
+            paths.iter().any(|p| path.starts_with(p))
+        })
+        .collect();
+
+    let mut detail: Vec<Detail> = focused
+        .iter()
+        .map(|&f| if f { Detail::Full } else { Detail::Headline })
+        .collect();
+    let imports = DependencyGraph::build(root, &stats.files);
+    for edge in imports.graph.raw_edges() {
+        let from = imports.graph[edge.source()];
+        let to = imports.graph[edge.target()];
+        for (a, b) in [(from, to), (to, from)] {
+            if focused[a] && detail[b] == Detail::Headline {
+                detail[b] = Detail::TopLevel;
+            }
+        }
+    }
+
+    for (file, &detail) in stats.files.iter_mut().zip(&detail) {
+        match detail {
+            Detail::Full => {}
+            Detail::TopLevel => keep_top_level(file),
+            Detail::Headline => {
+                file.omitted += file.symbols.len();
+                file.symbols.clear();
+                file.imports.clear();
+            }
+        }
+    }
+    detail
+}
+
+fn keep_top_level(file: &mut FileSymbols) {
+    let before = file.symbols.len();
+    file.symbols.retain(|sym| sym.parent.is_none());
+    file.omitted += before - file.symbols.len();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapOutput;
+
+    #[test]
+    fn test_focus_detail_levels() {
+        let mut map = MapOutput::from_sources(
+            ".",
+            [
+                (
+                    "src/parser.py",
+                    "from .lexer import Lexer\nclass Parser:\n    def parse(self):\n        pass\n",
+                ),
+                (
+                    "src/lexer.py",
+                    "class Lexer:\n    def next(self):\n        pass\n",
+                ),
+                (
+                    "src/cli.py",
+                    "from .parser import Parser\ndef main():\n    pass\n",
+                ),
+                ("src/other.py", "def unrelated():\n    pass\n"),
+            ],
+        );
+
+        let detail = map.focus(&[PathBuf::from("src/parser.py")]);
+        assert_eq!(
+            detail,
+            vec![
+                Detail::Full,
+                Detail::TopLevel,
+                Detail::TopLevel,
+                Detail::Headline
+            ]
+        );
+
+        let files = map.files();
+        assert_eq!(files[0].symbols.len(), 2);
+        let lexer: Vec<&str> = files[1].symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!((lexer, files[1].omitted), (vec!["Lexer"], 1));
+        assert!(files[3].symbols.is_empty());
+        assert!(
+            map.to_markdown(false)
+                .contains("## src/other.py (1 symbols collapsed)")
+        );
+    }
+}
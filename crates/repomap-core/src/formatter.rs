@@ -0,0 +1,605 @@
+use crate::collapse::DirSummary;
+use crate::languages::Language;
+use crate::markup;
+use crate::parser::{self, Symbol};
+use crate::queries::QueryRegistry;
+use crate::rank::Weights;
+use crate::testing;
+use crate::tokens::Tokenizer;
+use crate::walk::{self, Skipped};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug, Default)]
+pub enum OutputFormat {
+    #[default]
+    Markdown,
+    Json,
+    Yaml,
+    Ndjson,
+    Html,
+    Ctags,
+    Sqlite,
+    Csv,
+    Aider,
+}
+
+/// Everything extracted from a single file, independent of output format.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileSymbols {
+    pub path: String,
+    pub language: Language,
+    pub lines: usize,
+    pub imports: Vec<String>,
+    pub symbols: Vec<Symbol>,
+    /// Symbols left out to fit a token budget; see [`crate::budget`].
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub omitted: usize,
+    /// Identifiers this file mentions. Only used for ranking, so it is
+    /// cached but left out of rendered output.
+    #[serde(skip)]
+    pub references: Vec<SmolStr>,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+impl FileSymbols {
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty() && self.imports.is_empty()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut buffer = Vec::new();
+        self.write_markdown(&mut buffer)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(buffer).expect("markdown is built from UTF-8 strings")
+    }
+
+    pub fn write_markdown(&self, writer: &mut impl Write) -> io::Result<()> {
+        if self.is_empty() {
+            if self.omitted > 0 {
+                write!(
+                    writer,
+                    "\n## {} ({} symbols collapsed)\n",
+                    self.path, self.omitted
+                )?;
+            }
+            return Ok(());
+        }
+
+        write!(writer, "\n## {}\n", self.path)?;
+
+        // Show imports first if present
+        if !self.imports.is_empty() {
+            writeln!(writer, "imports: {}", self.imports.join(", "))?;
+        }
+
+        if !self.symbols.is_empty() {
+            writeln!(writer, "```{}", get_fence_tag(self.language))?;
+            for sym in &self.symbols {
+                write_symbol_line(writer, sym)?;
+            }
+            writeln!(writer, "```")?;
+        }
+        if self.omitted > 0 {
+            writeln!(writer, "_… and {} more symbols_", self.omitted)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// One `L12 | kind | name | (n lines)` row of a Markdown symbol block, with
+/// a trailing `| test` column for test code.
+pub(crate) fn write_symbol_line(writer: &mut impl Write, sym: &Symbol) -> io::Result<()> {
+    let size = sym.end_line - sym.line + 1;
+    write!(
+        writer,
+        "L{: <3} | {: <10} | {: <30} | ({} lines)",
+        sym.line,
+        sym.kind,
+        display_name(sym),
+        size
+    )?;
+    if sym.test {
+        write!(writer, " | test")?;
+    }
+    writeln!(writer)
+}
+
+pub fn display_name(sym: &Symbol) -> String {
+    match &sym.parent {
+        Some(p) => format!("{} > {}", p, sym.name),
+        None => {
+            if sym.kind.starts_with('h') && sym.kind.len() > 1 {
+                let level = sym.kind[1..].parse::<usize>().unwrap_or(1);
+                format!("{}{}", "  ".repeat(level.saturating_sub(1)), sym.name)
+            } else {
+                sym.name.to_string()
+            }
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct RepoStats {
+    pub files: Vec<FileSymbols>,
+    pub file_count: usize,
+    /// Used for the per-file token column of the summary table.
+    pub tokenizer: Tokenizer,
+    /// Bias for [`crate::rank::rank_files`] when ordering or trimming.
+    pub weights: Weights,
+    /// Directories folded by [`crate::collapse::collapse_dirs`]; their files
+    /// are no longer in `files` but still count towards `file_count`.
+    pub collapsed_dirs: Vec<DirSummary>,
+    /// Files the walk found but left out for their content.
+    pub skipped: Vec<Skipped>,
+    /// How many files there were before [`crate::MapOutput::keep_top_files`]
+    /// cut the map down to the most important ones.
+    pub files_before_cutoff: Option<usize>,
+}
+
+impl RepoStats {
+    pub fn new() -> Self {
+        Self::with_tokenizer(Tokenizer::default())
+    }
+
+    pub fn with_tokenizer(tokenizer: Tokenizer) -> Self {
+        Self {
+            files: Vec::new(),
+            file_count: 0,
+            tokenizer,
+            weights: Weights::default(),
+            collapsed_dirs: Vec::new(),
+            skipped: Vec::new(),
+            files_before_cutoff: None,
+        }
+    }
+
+    pub fn add_file(&mut self, file: FileSymbols) {
+        self.files.push(file);
+        self.file_count += 1;
+    }
+
+    /// Tokens `file` contributes to the Markdown map.
+    pub fn file_tokens(&self, file: &FileSymbols) -> usize {
+        self.tokenizer.count(&file.to_markdown())
+    }
+
+    /// Quick bytes / 4 estimate; see [`Tokenizer`] for real counts.
+    pub fn estimate_tokens(&self, final_output: &str) -> usize {
+        Tokenizer::Heuristic.count(final_output)
+    }
+}
+
+pub(crate) fn get_fence_tag(lang: Language) -> &'static str {
+    match lang {
+        Language::Rust => "rust",
+        Language::Python => "python",
+        Language::Go => "go",
+        Language::Javascript => "javascript",
+        Language::Typescript | Language::Tsx => "typescript",
+        Language::Markdown => "markdown",
+        Language::Java => "java",
+        Language::Cpp => "cpp",
+        Language::Csharp => "csharp",
+        Language::Ruby => "ruby",
+        Language::Php => "php",
+        Language::Kotlin => "kotlin",
+        Language::Swift => "swift",
+        Language::Elixir => "elixir",
+        Language::Haskell => "haskell",
+        Language::Zig => "zig",
+        Language::Dart => "dart",
+        Language::Julia => "julia",
+        Language::R => "r",
+        Language::Shell => "bash",
+        Language::Powershell => "powershell",
+        Language::Hcl => "hcl",
+        Language::Graphql => "graphql",
+        Language::Protobuf => "protobuf",
+        Language::Dockerfile => "dockerfile",
+        Language::Yaml => "yaml",
+        Language::Toml => "toml",
+        Language::Json => "json",
+        Language::Html => "html",
+        Language::Vue => "vue",
+        Language::Svelte => "svelte",
+        Language::Jupyter => "python",
+        Language::Clojure => "clojure",
+        Language::Erlang => "erlang",
+        Language::Nix => "nix",
+        Language::Solidity => "solidity",
+        Language::Perl => "perl",
+        Language::Groovy => "groovy",
+        Language::Fsharp => "fsharp",
+        Language::Nim => "nim",
+        Language::Gleam => "gleam",
+        Language::Rst => "rst",
+        Language::Latex => "latex",
+    }
+}
+
+pub fn process_file(path: &Path, lang: Language) -> Result<FileSymbols> {
+    let content = fs::read_to_string(path)?;
+    Ok(process_source(&path.display().to_string(), &content, lang))
+}
+
+/// Extracts symbols and imports from in-memory source, for callers that have
+/// no filesystem (e.g. the WASM build).
+pub fn process_source(path: &str, content: &str, lang: Language) -> FileSymbols {
+    process_source_with(path, content, lang, &QueryRegistry::default())
+}
+
+/// [`process_source`] with the queries in `registry`.
+pub fn process_source_with(
+    path: &str,
+    content: &str,
+    lang: Language,
+    registry: &QueryRegistry,
+) -> FileSymbols {
+    let mut extracted = parser::extract_with(content, lang, registry);
+    let lines = content.lines().count();
+    markup::name_component(path, lang, lines, &mut extracted.symbols);
+    testing::mark_tests(path, lang, &mut extracted.symbols);
+
+    FileSymbols {
+        path: path.to_string(),
+        language: lang,
+        lines,
+        imports: extracted.imports,
+        symbols: extracted.symbols,
+        omitted: 0,
+        references: extracted.references,
+    }
+}
+
+pub fn assemble_final_map(root: &str, stats: &RepoStats, show_summary: bool) -> String {
+    let mut buffer = Vec::new();
+    write_final_map(&mut buffer, root, stats, show_summary).expect("writing to a Vec cannot fail");
+    String::from_utf8(buffer).expect("markdown is built from UTF-8 strings")
+}
+
+pub(crate) fn write_header(
+    writer: &mut impl Write,
+    title: &str,
+    root: &str,
+    file_count: usize,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "# {}\n**Root:** `{}`\n**Files:** {}\n\n",
+        title, root, file_count
+    )
+}
+
+/// Writes the Markdown map file by file, so the whole document never has to
+/// be held in memory.
+pub fn write_final_map(
+    writer: &mut impl Write,
+    root: &str,
+    stats: &RepoStats,
+    show_summary: bool,
+) -> io::Result<()> {
+    write_header(writer, "Repository Map", root, stats.file_count)?;
+    if let Some(total) = stats.files_before_cutoff {
+        writeln!(
+            writer,
+            "> Only the {} most important of {} files are mapped (--max-files).\n",
+            stats.file_count, total
+        )?;
+    }
+    if show_summary {
+        writer.write_all(
+            b"## Summary\n| File | Symbols | Lines | Tokens |\n| :--- | :--- | :--- | :--- |\n",
+        )?;
+        for file in &stats.files {
+            writeln!(
+                writer,
+                "| `{}` | {} | {} | {} |",
+                file.path,
+                file.symbols.len(),
+                file.lines,
+                stats.file_tokens(file)
+            )?;
+        }
+        if let Some(note) = walk::skipped_note(&stats.skipped) {
+            write!(writer, "\n_{}_\n", note)?;
+        }
+        writer.write_all(b"\n---\n")?;
+    } else {
+        writer.write_all(b"---\n")?;
+    }
+    for file in &stats.files {
+        file.write_markdown(writer)?;
+    }
+    write_appendix(writer, stats)
+}
+
+/// Writes what follows the files: collapsed directories, then the files the
+/// walk skipped and why.
+pub(crate) fn write_appendix(writer: &mut impl Write, stats: &RepoStats) -> io::Result<()> {
+    for dir in &stats.collapsed_dirs {
+        dir.write_markdown(writer)?;
+    }
+    if !stats.skipped.is_empty() {
+        writer.write_all(b"\n## Skipped files\n")?;
+        for file in &stats.skipped {
+            writeln!(writer, "- {} ({})", file.path, file.reason)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct StructuredFile<'a> {
+    #[serde(flatten)]
+    file: &'a FileSymbols,
+    symbol_count: usize,
+}
+
+impl<'a> From<&'a FileSymbols> for StructuredFile<'a> {
+    fn from(file: &'a FileSymbols) -> Self {
+        Self {
+            file,
+            symbol_count: file.symbols.len(),
+        }
+    }
+}
+
+/// Shared document shape for the JSON and YAML formats and user templates.
+#[derive(Serialize)]
+pub struct StructuredMap<'a> {
+    root: &'a str,
+    file_count: usize,
+    files: Vec<StructuredFile<'a>>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    collapsed_dirs: &'a [DirSummary],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    skipped: &'a [Skipped],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files_before_cutoff: Option<usize>,
+}
+
+impl<'a> StructuredMap<'a> {
+    pub fn new(root: &'a str, stats: &'a RepoStats) -> Self {
+        Self {
+            root,
+            file_count: stats.file_count,
+            files: stats.files.iter().map(StructuredFile::from).collect(),
+            collapsed_dirs: &stats.collapsed_dirs,
+            skipped: &stats.skipped,
+            files_before_cutoff: stats.files_before_cutoff,
+        }
+    }
+}
+
+pub fn assemble_json(root: &str, stats: &RepoStats) -> Result<String> {
+    let mut buffer = Vec::new();
+    write_json(&mut buffer, root, stats)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+pub fn write_json(writer: &mut impl Write, root: &str, stats: &RepoStats) -> Result<()> {
+    serde_json::to_writer_pretty(writer, &StructuredMap::new(root, stats))?;
+    Ok(())
+}
+
+pub fn assemble_yaml(root: &str, stats: &RepoStats) -> Result<String> {
+    let mut buffer = Vec::new();
+    write_yaml(&mut buffer, root, stats)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+pub fn write_yaml(writer: &mut impl Write, root: &str, stats: &RepoStats) -> Result<()> {
+    serde_yaml_ng::to_writer(writer, &StructuredMap::new(root, stats))?;
+    Ok(())
+}
+
+pub fn assemble_csv(stats: &RepoStats) -> Result<String> {
+    let mut buffer = Vec::new();
+    write_csv(&mut buffer, stats)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+/// One row per symbol, for spreadsheets and BI tools.
+pub fn write_csv(writer: &mut impl Write, stats: &RepoStats) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record([
+        "path",
+        "kind",
+        "name",
+        "parent",
+        "start_line",
+        "end_line",
+        "size",
+    ])?;
+
+    for file in &stats.files {
+        for sym in &file.symbols {
+            writer.write_record([
+                file.path.as_str(),
+                sym.kind.as_str(),
+                sym.name.as_str(),
+                sym.parent.as_deref().unwrap_or(""),
+                &sym.line.to_string(),
+                &sym.end_line.to_string(),
+                &(sym.end_line - sym.line + 1).to_string(),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes one file as a single NDJSON record, shaped like a `files` entry of
+/// the JSON format, and flushes so consumers see it immediately.
+pub fn write_ndjson_line(writer: &mut impl Write, file: &FileSymbols) -> Result<()> {
+    serde_json::to_writer(&mut *writer, &StructuredFile::from(file))?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+const REPOMAP_START: &str = "<!-- REPOMAP START -->";
+const REPOMAP_END: &str = "<!-- REPOMAP END -->";
+
+pub fn wrap_for_claude_md(content: &str, file_count: usize, token_estimate: usize) -> String {
+    let token_display = if token_estimate >= 1000 {
+        format!("~{:.1}k tokens", token_estimate as f64 / 1000.0)
+    } else {
+        format!("~{} tokens", token_estimate)
+    };
+
+    format!(
+        "{}\n## Repository Structure\n\n<details>\n<summary>Repository map ({} files, {})</summary>\n\n{}\n</details>\n{}\n",
+        REPOMAP_START, file_count, token_display, content, REPOMAP_END
+    )
+}
+
+pub fn update_or_append_repomap(existing_content: &str, new_repomap: &str) -> String {
+    if let (Some(start), Some(end)) = (
+        existing_content.find(REPOMAP_START),
+        existing_content.find(REPOMAP_END),
+    ) {
+        // Replace existing repomap section
+        let before = &existing_content[..start];
+        let after = &existing_content[end + REPOMAP_END.len()..];
+        format!("{}{}{}", before.trim_end(), new_repomap, after)
+    } else {
+        // Append to existing content
+        let trimmed = existing_content.trim_end();
+        if trimmed.is_empty() {
+            new_repomap.to_string()
+        } else {
+            format!("{}\n\n{}", trimmed, new_repomap)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_markdown_formatting_logic() {
+        let mut file = NamedTempFile::new().expect("Failed to create temp file");
+        writeln!(file, "# Header 1\n## Header 2").expect("Failed to write to temp file");
+
+        let file_map = process_file(file.path(), Language::Markdown).expect("Processing failed");
+        let output = file_map.to_markdown();
+
+        assert_eq!(file_map.symbols.len(), 2);
+        assert_eq!(file_map.lines, 2);
+        assert!(output.contains("h1         | Header 1"));
+        assert!(output.contains("h2         |   Header 2"));
+    }
+
+    fn dummy_file(path: &str, sym_count: usize, lines: usize) -> FileSymbols {
+        FileSymbols {
+            path: path.to_string(),
+            language: Language::Rust,
+            lines,
+            imports: vec![],
+            symbols: (0..sym_count)
+                .map(|i| Symbol {
+                    name: format!("sym_{}", i).into(),
+                    parent: None,
+                    line: i + 1,
+                    kind: "function_item".into(),
+                    end_line: i + 1,
+                    test: false,
+                })
+                .collect(),
+            omitted: 0,
+            references: vec![],
+        }
+    }
+
+    #[test]
+    fn test_repostats_aggregation() {
+        let mut stats = RepoStats::with_tokenizer(Tokenizer::Heuristic);
+
+        stats.add_file(dummy_file("src/main.rs", 5, 100));
+
+        assert_eq!(stats.file_count, 1);
+        let tokens = stats.files[0].to_markdown().len() / 4;
+        let output = assemble_final_map(".", &stats, true);
+        assert!(output.contains(&format!("| `src/main.rs` | 5 | 100 | {} |", tokens)));
+    }
+
+    #[test]
+    fn test_json_output() {
+        let mut stats = RepoStats::new();
+        stats.add_file(dummy_file("src/lib.rs", 1, 10));
+
+        let json = assemble_json(".", &stats).expect("Serialization failed");
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["file_count"], 1);
+        assert_eq!(value["files"][0]["path"], "src/lib.rs");
+        assert_eq!(value["files"][0]["language"], "rust");
+        assert_eq!(value["files"][0]["symbol_count"], 1);
+        assert_eq!(value["files"][0]["symbols"][0]["name"], "sym_0");
+        assert!(value["files"][0]["symbols"][0]["parent"].is_null());
+    }
+
+    #[test]
+    fn test_yaml_matches_json_structure() {
+        let mut stats = RepoStats::new();
+        stats.add_file(dummy_file("src/lib.rs", 2, 10));
+
+        let yaml = assemble_yaml(".", &stats).expect("Serialization failed");
+        let from_yaml: serde_json::Value = serde_yaml_ng::from_str(&yaml).unwrap();
+        let from_json: serde_json::Value =
+            serde_json::from_str(&assemble_json(".", &stats).unwrap()).unwrap();
+
+        assert_eq!(from_yaml, from_json);
+    }
+
+    #[test]
+    fn test_csv_one_row_per_symbol() {
+        let mut stats = RepoStats::new();
+        let mut file = dummy_file("src/a, b.rs", 2, 10);
+        file.symbols[1].parent = Some("Config".into());
+        stats.add_file(file);
+
+        let csv = assemble_csv(&stats).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "path,kind,name,parent,start_line,end_line,size");
+        assert_eq!(lines[1], "\"src/a, b.rs\",function_item,sym_0,,1,1,1");
+        assert_eq!(lines[2], "\"src/a, b.rs\",function_item,sym_1,Config,2,2,1");
+    }
+
+    #[test]
+    fn test_ndjson_one_record_per_line() {
+        let mut buffer = Vec::new();
+        write_ndjson_line(&mut buffer, &dummy_file("a.rs", 1, 5)).unwrap();
+        write_ndjson_line(&mut buffer, &dummy_file("b.rs", 2, 8)).unwrap();
+
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["path"], "b.rs");
+        assert_eq!(second["symbol_count"], 2);
+    }
+
+    #[test]
+    fn test_token_estimation() {
+        let stats = RepoStats::new();
+        let dummy_output = "a".repeat(400);
+        assert_eq!(stats.estimate_tokens(&dummy_output), 100);
+    }
+}
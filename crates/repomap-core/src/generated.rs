@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::fs;
+use std::path::Path;
+
+/// Markers code generators put near the top of their output.
+const HEADER_MARKERS: &[&str] = &[
+    "@generated",
+    "DO NOT EDIT",
+    "Generated by the protocol buffer compiler",
+    "Code generated by",
+    "Autogenerated by",
+    "This file was automatically generated",
+    "This file is automatically generated",
+    "Generated by: https://openapi-generator.tech",
+    "generated by swagger-codegen",
+];
+
+const COMMENT_STARTS: &[&str] = &["//", "#", "/*", "*", "--", "<!--", ";", "%"];
+
+/// File name endings that only code generators produce.
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".pb.go",
+    ".pb.cc",
+    ".pb.h",
+    ".pb.ts",
+    "_pb2.py",
+    "_pb2_grpc.py",
+    "_pb2.pyi",
+    ".gen.go",
+    ".gen.ts",
+    ".g.dart",
+    ".freezed.dart",
+    ".designer.cs",
+];
+
+/// How much of a file [`has_generated_header`] looks at.
+pub const HEADER_BYTES: usize = 1024;
+
+/// Files the repository marks `linguist-generated` in its root
+/// `.gitattributes`. Later lines win, as in git, so a pattern can be unset
+/// again with `-linguist-generated` or `linguist-generated=false`.
+#[derive(Clone, Debug, Default)]
+pub struct GeneratedFiles {
+    patterns: GlobSet,
+    generated: Vec<bool>,
+}
+
+impl GeneratedFiles {
+    /// Reads `root/.gitattributes`; a missing file marks nothing.
+    pub fn load(root: &Path) -> Result<Self> {
+        match fs::read_to_string(root.join(".gitattributes")) {
+            Ok(text) => Self::parse(&text),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        let mut generated = Vec::new();
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next().filter(|p| !p.starts_with('#')) else {
+                continue;
+            };
+            let Some(value) = fields.filter_map(linguist_generated).next_back() else {
+                continue;
+            };
+            // Like .gitignore: a pattern with no inner slash matches at any depth
+            let glob = match pattern.trim_end_matches('/').strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if pattern.contains('/') => pattern.to_string(),
+                None => format!("**/{}", pattern),
+            };
+            builder.add(
+                GlobBuilder::new(&glob)
+                    .literal_separator(true)
+                    .build()
+                    .with_context(|| format!("invalid .gitattributes pattern '{}'", pattern))?,
+            );
+            generated.push(value);
+        }
+        Ok(Self {
+            patterns: builder.build()?,
+            generated,
+        })
+    }
+
+    /// Whether `path`, relative to the root, is marked generated.
+    pub fn is_marked(&self, path: &Path) -> bool {
+        self.patterns
+            .matches(path)
+            .into_iter()
+            .max()
+            .is_some_and(|last| self.generated[last])
+    }
+}
+
+/// The value a `linguist-generated` attribute sets, if `attr` is one.
+fn linguist_generated(attr: &str) -> Option<bool> {
+    match attr {
+        "linguist-generated" | "linguist-generated=true" => Some(true),
+        "-linguist-generated" | "!linguist-generated" | "linguist-generated=false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the file name alone says generated, like `api.pb.go`.
+pub fn has_generated_name(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    GENERATED_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+/// Whether a comment in the first [`HEADER_BYTES`] of a file carries a code
+/// generator's marker, like `// Code generated by protoc-gen-go. DO NOT EDIT.`
+pub fn has_generated_header(head: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&head[..head.len().min(HEADER_BYTES)]);
+    head.lines()
+        .map(str::trim_start)
+        .filter(|line| COMMENT_STARTS.iter().any(|start| line.starts_with(start)))
+        .any(|line| HEADER_MARKERS.iter().any(|marker| line.contains(marker)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitattributes_patterns() {
+        let attributes = GeneratedFiles::parse(
+            "# codegen\n\
+             *.ts text eol=lf\n\
+             gen/** linguist-generated=true\n\
+             gen/keep.rs -linguist-generated\n\
+             schema.rs linguist-generated\n\
+             /api/*.go linguist-generated\n",
+        )
+        .unwrap();
+
+        assert!(attributes.is_marked(Path::new("gen/a/b.rs")));
+        assert!(!attributes.is_marked(Path::new("gen/keep.rs")));
+        assert!(attributes.is_marked(Path::new("src/db/schema.rs")));
+        assert!(attributes.is_marked(Path::new("api/client.go")));
+        assert!(!attributes.is_marked(Path::new("api/v1/client.go")));
+        assert!(!attributes.is_marked(Path::new("src/app.ts")));
+        assert!(!GeneratedFiles::default().is_marked(Path::new("gen/a.rs")));
+    }
+
+    #[test]
+    fn test_heuristics() {
+        assert!(has_generated_name(Path::new("api/service.pb.go")));
+        assert!(has_generated_name(Path::new("proto/user_pb2.py")));
+        assert!(!has_generated_name(Path::new("src/pb.go")));
+
+        assert!(has_generated_header(
+            b"// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"
+        ));
+        assert!(has_generated_header(b"/**\n * @generated\n */\n"));
+        assert!(!has_generated_header(b"fn main() {}\n"));
+        assert!(!has_generated_header(
+            b"const MARKER: &str = \"@generated\";\n"
+        ));
+    }
+}
@@ -0,0 +1,1374 @@
+use crate::formatter::FileSymbols;
+use crate::languages::Language;
+use petgraph::graph::{DiGraph, NodeIndex};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
+
+/// File-level import graph. Node weights are indices into the mapped files.
+pub struct DependencyGraph {
+    pub graph: DiGraph<usize, ()>,
+}
+
+impl DependencyGraph {
+    /// Links each of `files`, mapped from `root`, to the files it imports.
+    pub fn build(root: &str, files: &[FileSymbols]) -> Self {
+        let root = normalize(Path::new(root));
+        let index: HashMap<PathBuf, usize> = files
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (normalize(Path::new(&f.path)), i))
+            .collect();
+
+        let mut graph = DiGraph::new();
+        let nodes: Vec<NodeIndex> = (0..files.len()).map(|i| graph.add_node(i)).collect();
+
+        for (i, file) in files.iter().enumerate() {
+            let mut targets: Vec<usize> = file
+                .imports
+                .iter()
+                .flat_map(|import| resolve_import(&root, file, import, &index, files))
+                .filter(|&t| t != i)
+                .collect();
+            targets.sort_unstable();
+            targets.dedup();
+
+            for t in targets {
+                graph.add_edge(nodes[i], nodes[t], ());
+            }
+        }
+
+        Self { graph }
+    }
+
+    /// Renders the graph as a Mermaid `graph TD` block, listing only files
+    /// that take part in at least one edge.
+    pub fn to_mermaid(&self, files: &[FileSymbols]) -> String {
+        let mut output = String::from("```mermaid\ngraph TD\n");
+
+        for node in self.graph.node_indices() {
+            if self.graph.neighbors_undirected(node).next().is_some() {
+                let file = &files[self.graph[node]];
+                output.push_str(&format!(
+                    "    n{}[\"{}\"]\n",
+                    node.index(),
+                    file.path.replace('"', "#quot;")
+                ));
+            }
+        }
+        for edge in self.graph.raw_edges() {
+            output.push_str(&format!(
+                "    n{} --> n{}\n",
+                edge.source().index(),
+                edge.target().index()
+            ));
+        }
+
+        output.push_str("```\n");
+        output
+    }
+}
+
+/// Lexically resolves `.` and `..` so paths from the walker and paths built
+/// from import strings compare equal.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn resolve_import(
+    root: &Path,
+    file: &FileSymbols,
+    import: &str,
+    index: &HashMap<PathBuf, usize>,
+    files: &[FileSymbols],
+) -> Vec<usize> {
+    let path = normalize(Path::new(&file.path));
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    let lookup = |candidates: Vec<PathBuf>| -> Vec<usize> {
+        candidates
+            .iter()
+            .find_map(|c| index.get(&normalize(c)).copied())
+            .into_iter()
+            .collect()
+    };
+
+    match file.language {
+        Language::Javascript
+        | Language::Typescript
+        | Language::Tsx
+        | Language::Vue
+        | Language::Svelte => {
+            if !import.starts_with('.') {
+                return vec![];
+            }
+            let base = dir.join(import);
+            let mut candidates = vec![base.clone()];
+            for ext in JS_EXTENSIONS {
+                candidates.push(PathBuf::from(format!("{}.{}", base.display(), ext)));
+            }
+            for ext in JS_EXTENSIONS {
+                candidates.push(base.join(format!("index.{}", ext)));
+            }
+            lookup(candidates)
+        }
+        Language::Python | Language::Jupyter => {
+            let dots = import.chars().take_while(|&c| c == '.').count();
+            let module: PathBuf = import[dots..]
+                .split('.')
+                .filter(|s| !s.is_empty())
+                .collect();
+            if dots > 0 {
+                let mut base = dir.to_path_buf();
+                for _ in 1..dots {
+                    base.pop();
+                }
+                let base = base.join(&module);
+                lookup(vec![base.with_extension("py"), base.join("__init__.py")])
+            } else if module.as_os_str().is_empty() {
+                vec![]
+            } else {
+                let module_file = module.with_extension("py");
+                let package_file = module.join("__init__.py");
+                files
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, f)| {
+                        let p = normalize(Path::new(&f.path));
+                        p.ends_with(&module_file) || p.ends_with(&package_file)
+                    })
+                    .map(|(i, _)| i)
+                    .take(1)
+                    .collect()
+            }
+        }
+        Language::Rust => resolve_rust_import(&path, import)
+            .map(lookup)
+            .unwrap_or_default(),
+        Language::Go => {
+            let import_path = Path::new(import);
+            files
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.language == Language::Go)
+                .filter(|(_, f)| {
+                    // An import path ends with the package's directory in the
+                    // repo, whatever the repo's own path is
+                    let p = normalize(Path::new(&f.path));
+                    let p = p.strip_prefix(root).unwrap_or(&p);
+                    let parent = p.parent().unwrap_or(Path::new(""));
+                    !parent.as_os_str().is_empty() && import_path.ends_with(parent)
+                })
+                .map(|(i, _)| i)
+                .collect()
+        }
+        // Gradle's `apply from:` names a script next to this one
+        Language::Groovy if import.ends_with(".gradle") => lookup(vec![dir.join(import)]),
+        Language::Java | Language::Kotlin | Language::Groovy => resolve_jvm_import(import, files),
+        // By convention `Acme.Store` lives in `Acme/Store/` or an `Acme.Store/` project
+        Language::Csharp => {
+            let nested: PathBuf = import.split('.').collect();
+            files
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.language == Language::Csharp)
+                .filter(|(_, f)| {
+                    let p = normalize(Path::new(&f.path));
+                    p.parent().is_some_and(|dir| {
+                        dir.ends_with(&nested) || dir.file_name().is_some_and(|n| n == import)
+                    })
+                })
+                .map(|(i, _)| i)
+                .collect()
+        }
+        Language::Cpp => {
+            let local = lookup(vec![dir.join(import)]);
+            if local.is_empty() {
+                on_load_path(Path::new(import), Language::Cpp, files)
+            } else {
+                local
+            }
+        }
+        // PSR-4: `App\Models\User` in `app/Models/User.php`, whatever the
+        // vendor prefix maps to
+        Language::Php => {
+            let segments: Vec<&str> = import.split('\\').filter(|s| !s.is_empty()).collect();
+            (2..=segments.len())
+                .rev()
+                .map(|len| {
+                    let file: PathBuf = segments[segments.len() - len..].iter().collect();
+                    on_load_path(&file.with_extension("php"), Language::Php, files)
+                })
+                .find(|found| !found.is_empty())
+                .unwrap_or_default()
+        }
+        Language::Ruby => {
+            let file = Path::new(import).with_extension("rb");
+            let local = lookup(vec![dir.join(&file)]);
+            if local.is_empty() {
+                on_load_path(&file, Language::Ruby, files)
+            } else {
+                local
+            }
+        }
+        // A module is a target directory, like SwiftPM's `Sources/Core/`
+        Language::Swift => {
+            let module = import.split('.').next().unwrap_or(import);
+            files
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.language == Language::Swift)
+                .filter(|(_, f)| {
+                    let p = normalize(Path::new(&f.path));
+                    p.parent()
+                        .is_some_and(|dir| dir.components().any(|c| c.as_os_str() == module))
+                })
+                .map(|(i, _)| i)
+                .collect()
+        }
+        // `Acme.ShoppingCart` in `lib/acme/shopping_cart.ex`
+        Language::Elixir => {
+            let file: PathBuf = import.split('.').map(snake_case).collect();
+            on_load_path(&file.with_extension("ex"), Language::Elixir, files)
+        }
+        Language::Zig => lookup(vec![dir.join(import)]),
+        // `package:app/src/cart.dart` is the package's `lib/src/cart.dart`
+        Language::Dart => match import.strip_prefix("package:") {
+            Some(package) => {
+                let file = package.split_once('/').map_or(package, |(_, file)| file);
+                on_load_path(&Path::new("lib").join(file), Language::Dart, files)
+            }
+            None => lookup(vec![dir.join(import)]),
+        },
+        // `include("geometry.jl")`, or the `src/Geometry.jl` of `using Geometry`
+        Language::Julia if import.ends_with(".jl") => lookup(vec![dir.join(import)]),
+        Language::Julia => {
+            let module = import.rsplit('.').next().unwrap_or(import);
+            on_load_path(
+                &Path::new(module).with_extension("jl"),
+                Language::Julia,
+                files,
+            )
+        }
+        // `source("R/utils.R")` runs from the file's directory or the project root
+        Language::R => {
+            let local = lookup(vec![dir.join(import)]);
+            if local.is_empty() {
+                on_load_path(Path::new(import), Language::R, files)
+            } else {
+                local
+            }
+        }
+        Language::Shell => {
+            let file = script_path(import);
+            let local = lookup(vec![dir.join(&file)]);
+            if local.is_empty() {
+                on_load_path(&file, Language::Shell, files)
+            } else {
+                local
+            }
+        }
+        // A module imported by name is `Name.psm1`, often in a `Name/` folder
+        Language::Powershell => {
+            if import.ends_with(".ps1") || import.ends_with(".psm1") {
+                lookup(vec![dir.join(script_path(import))])
+            } else {
+                let dir = script_path(import);
+                let name = dir.file_name().unwrap_or_default().to_string_lossy();
+                let module = Path::new(name.as_ref()).join(format!("{name}.psm1"));
+                on_load_path(&module, Language::Powershell, files)
+            }
+        }
+        // A local module `source` is a directory of `.tf` files; registry and
+        // git sources match nothing
+        Language::Hcl => {
+            let module = normalize(&dir.join(import));
+            files
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.language == Language::Hcl)
+                .filter(|(_, f)| normalize(Path::new(&f.path)).parent() == Some(module.as_path()))
+                .map(|(i, _)| i)
+                .collect()
+        }
+        // `<script src>`s and module imports, relative to the page or, with a
+        // leading `/`, to the site root
+        Language::Html => {
+            if import.contains("://") || import.starts_with("//") {
+                vec![]
+            } else if let Some(rooted) = import.strip_prefix('/') {
+                on_load_path(Path::new(rooted), Language::Javascript, files)
+            } else {
+                lookup(vec![dir.join(import)])
+            }
+        }
+        // Relative to an include root, like `protoc -I proto`
+        Language::Protobuf => on_load_path(Path::new(import), Language::Protobuf, files),
+        Language::Haskell => {
+            let file: PathBuf = import.split('.').collect();
+            on_load_path(&file.with_extension("hs"), Language::Haskell, files)
+        }
+        // `acme.shopping-cart` in `src/acme/shopping_cart.clj`, or `.cljc` or
+        // `.cljs` in ClojureScript
+        Language::Clojure => {
+            let file: PathBuf = import.replace('-', "_").split('.').collect();
+            ["clj", "cljc", "cljs"]
+                .iter()
+                .map(|ext| on_load_path(&file.with_extension(ext), Language::Clojure, files))
+                .find(|found| !found.is_empty())
+                .unwrap_or_default()
+        }
+        // `-include("cart.hrl")` next to the file or on the include path, and
+        // `-include_lib("app/include/cart.hrl")`; a `-behaviour` is a module
+        Language::Erlang if import.ends_with(".hrl") => {
+            let local = lookup(vec![dir.join(import)]);
+            if local.is_empty() {
+                on_load_path(Path::new(import), Language::Erlang, files)
+            } else {
+                local
+            }
+        }
+        Language::Erlang => on_load_path(
+            &Path::new(import).with_extension("erl"),
+            Language::Erlang,
+            files,
+        ),
+        // `import ./modules/acme.nix`, or a directory's `default.nix`
+        Language::Nix => {
+            let path = dir.join(import);
+            lookup(vec![path.clone(), path.join("default.nix")])
+        }
+        // `./IVault.sol`, or a package path like `@openzeppelin/contracts/...`
+        // found under `node_modules/` or `lib/`
+        Language::Solidity => {
+            let local = lookup(vec![dir.join(import)]);
+            if local.is_empty() {
+                on_load_path(Path::new(import), Language::Solidity, files)
+            } else {
+                local
+            }
+        }
+        // `require "lib/util.pl"` names a file, next to this one or under the
+        // project root; `Acme::Cart` is `lib/Acme/Cart.pm`
+        Language::Perl if import.contains(['/', '.']) => {
+            let local = lookup(vec![dir.join(import)]);
+            if local.is_empty() {
+                on_load_path(Path::new(import), Language::Perl, files)
+            } else {
+                local
+            }
+        }
+        Language::Perl => {
+            let file: PathBuf = import.split("::").collect();
+            on_load_path(&file.with_extension("pm"), Language::Perl, files)
+        }
+        // `#load "Helpers.fsx"`, or the file named for the module opened, like
+        // `Cart.fs` for `Acme.Store.Cart`
+        Language::Fsharp if import.ends_with(".fsx") || import.ends_with(".fs") => {
+            lookup(vec![dir.join(import)])
+        }
+        Language::Fsharp => {
+            let module = import.rsplit('.').next().unwrap_or(import);
+            on_load_path(
+                &Path::new(module).with_extension("fs"),
+                Language::Fsharp,
+                files,
+            )
+        }
+        // `import cart` or `./cart` is `cart.nim` next to this file, or under a
+        // search path like `src/`; `std/` modules match nothing
+        Language::Nim => {
+            let file = Path::new(import).with_extension("nim");
+            let local = lookup(vec![dir.join(&file)]);
+            if local.is_empty() {
+                on_load_path(&file, Language::Nim, files)
+            } else {
+                local
+            }
+        }
+        // `import app/cart` is `src/app/cart.gleam`, or under `test/`
+        Language::Gleam => on_load_path(
+            &Path::new(import).with_extension("gleam"),
+            Language::Gleam,
+            files,
+        ),
+        // `\input{chapters/intro}` is `chapters/intro.tex`, relative to the
+        // main document, which is usually the file including it
+        Language::Latex => {
+            let file = match Path::new(import).extension() {
+                Some(_) => PathBuf::from(import),
+                None => PathBuf::from(format!("{import}.tex")),
+            };
+            let local = lookup(vec![dir.join(&file)]);
+            if local.is_empty() {
+                on_load_path(&file, Language::Latex, files)
+            } else {
+                local
+            }
+        }
+        // Links, relative to the document or, with a leading `/`, to the root;
+        // a directory stands for its README or index
+        Language::Markdown => {
+            let target = match import.strip_prefix('/') {
+                Some(rooted) => PathBuf::from(rooted),
+                None => dir.join(import),
+            };
+            lookup(vec![
+                target.clone(),
+                target.join("README.md"),
+                target.join("index.md"),
+            ])
+        }
+        Language::Rst
+        | Language::Graphql
+        | Language::Dockerfile
+        | Language::Yaml
+        | Language::Toml
+        | Language::Json => vec![],
+    }
+}
+
+/// A sourced script's path without the leading segments built from
+/// variables, like `$(dirname "$0")/` or `$PSScriptRoot\`, which are taken
+/// to be the script's own directory.
+fn script_path(import: &str) -> PathBuf {
+    import
+        .split(['/', '\\'])
+        .skip_while(|segment| segment.contains('$') || segment.is_empty())
+        .map(|segment| segment.trim_matches(['"', '\'']))
+        .collect()
+}
+
+/// `ShoppingCart` as `shopping_cart`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// The first `lang` file whose path ends in `path`, for includes and
+/// requires that aren't next to the file.
+fn on_load_path(path: &Path, lang: Language, files: &[FileSymbols]) -> Vec<usize> {
+    files
+        .iter()
+        .position(|f| f.language == lang && normalize(Path::new(&f.path)).ends_with(path))
+        .into_iter()
+        .collect()
+}
+
+/// `a.b.C` and the `a.b.C.member` of a static import both resolve to the
+/// file ending in `a/b/C.java`, or `a/b/C.kt` or `a/b/C.groovy` since Java,
+/// Kotlin, and Groovy import each other's classes; `a.b.*` to every such
+/// file in `a/b`.
+fn resolve_jvm_import(import: &str, files: &[FileSymbols]) -> Vec<usize> {
+    let segments: Vec<&str> = import.split('.').collect();
+    let jvm_files = || {
+        files
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| {
+                matches!(
+                    f.language,
+                    Language::Java | Language::Kotlin | Language::Groovy
+                )
+            })
+            .map(|(i, f)| (i, normalize(Path::new(&f.path))))
+    };
+
+    if let Some((_, package)) = segments.split_last().filter(|(last, _)| **last == "*") {
+        let package: PathBuf = package.iter().collect();
+        return jvm_files()
+            .filter(|(_, p)| p.parent().is_some_and(|dir| dir.ends_with(&package)))
+            .map(|(i, _)| i)
+            .collect();
+    }
+    (1..=segments.len())
+        .rev()
+        .take(2)
+        .find_map(|len| {
+            let class: PathBuf = segments[..len].iter().collect();
+            jvm_files().find(|(_, p)| p.with_extension("").ends_with(&class))
+        })
+        .map(|(i, _)| i)
+        .into_iter()
+        .collect()
+}
+
+/// Maps `crate::`, `super::`, and `self::` paths to candidate module files,
+/// longest module path first.
+fn resolve_rust_import(path: &Path, import: &str) -> Option<Vec<PathBuf>> {
+    let segments: Vec<&str> = import
+        .split("::")
+        .map(str::trim)
+        .take_while(|s| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        .collect();
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let stem = path.file_stem()?.to_str()?;
+    let is_module_root = matches!(stem, "mod" | "lib" | "main");
+    // Directory holding this file's child modules
+    let module_dir = if is_module_root {
+        dir.to_path_buf()
+    } else {
+        dir.join(stem)
+    };
+
+    let (base, rest) = match *segments.first()? {
+        "crate" => {
+            let src = path
+                .ancestors()
+                .find(|a| a.file_name().is_some_and(|n| n == "src"))
+                .unwrap_or(dir);
+            (src.to_path_buf(), &segments[1..])
+        }
+        "self" => (module_dir, &segments[1..]),
+        "super" => {
+            let supers = segments.iter().take_while(|s| **s == "super").count();
+            let mut base = module_dir;
+            for _ in 0..supers {
+                base.pop();
+            }
+            (base, &segments[supers..])
+        }
+        _ => return None,
+    };
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+    for len in (1..=rest.len()).rev() {
+        let module = base.join(rest[..len].iter().collect::<PathBuf>());
+        candidates.push(module.with_extension("rs"));
+        candidates.push(module.join("mod.rs"));
+    }
+    // An item defined in the crate root itself, e.g. `crate::Config`
+    if segments[0] == "crate" {
+        candidates.push(base.join("lib.rs"));
+        candidates.push(base.join("main.rs"));
+    }
+    Some(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, language: Language, imports: &[&str]) -> FileSymbols {
+        FileSymbols {
+            path: path.to_string(),
+            language,
+            lines: 1,
+            imports: imports.iter().map(|s| s.to_string()).collect(),
+            symbols: vec![],
+            omitted: 0,
+            references: vec![],
+        }
+    }
+
+    fn edges(files: &[FileSymbols]) -> Vec<(String, String)> {
+        let graph = DependencyGraph::build(".", files);
+        graph
+            .graph
+            .raw_edges()
+            .iter()
+            .map(|e| {
+                (
+                    files[graph.graph[e.source()]].path.clone(),
+                    files[graph.graph[e.target()]].path.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_crate_and_super_imports() {
+        let files = vec![
+            file(
+                "./src/main.rs",
+                Language::Rust,
+                &["crate::parser::{self, Symbol}", "std::fs"],
+            ),
+            file(
+                "./src/parser.rs",
+                Language::Rust,
+                &["super::lang::Kind", "crate::Config"],
+            ),
+            file("./src/lang/mod.rs", Language::Rust, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("./src/main.rs".into(), "./src/parser.rs".into()),
+                ("./src/parser.rs".into(), "./src/main.rs".into()),
+                ("./src/parser.rs".into(), "./src/lang/mod.rs".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_js_and_python_relative_imports() {
+        let files = vec![
+            file("web/app.ts", Language::Typescript, &["./util", "react"]),
+            file("web/util/index.ts", Language::Typescript, &[]),
+            file("pkg/a.py", Language::Python, &[".b", "pkg.c"]),
+            file("pkg/b.py", Language::Python, &[]),
+            file("pkg/c.py", Language::Python, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("web/app.ts".into(), "web/util/index.ts".into()),
+                ("pkg/a.py".into(), "pkg/b.py".into()),
+                ("pkg/a.py".into(), "pkg/c.py".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_java_imports() {
+        let files = vec![
+            file(
+                "src/com/acme/App.java",
+                Language::Java,
+                &[
+                    "com.acme.store.Store",
+                    "com.acme.util.Strings.join",
+                    "com.acme.model.*",
+                    "java.util.List",
+                ],
+            ),
+            file("src/com/acme/store/Store.java", Language::Java, &[]),
+            file("src/com/acme/util/Strings.java", Language::Java, &[]),
+            file("src/com/acme/model/User.java", Language::Java, &[]),
+            file("src/com/acme/model/Order.java", Language::Java, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec![
+                "src/com/acme/store/Store.java",
+                "src/com/acme/util/Strings.java",
+                "src/com/acme/model/User.java",
+                "src/com/acme/model/Order.java",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kotlin_imports() {
+        let files = vec![
+            file(
+                "app/src/main/kotlin/com/acme/App.kt",
+                Language::Kotlin,
+                &[
+                    "com.acme.store.Store",
+                    "com.acme.util.Strings",
+                    "kotlinx.coroutines.launch",
+                ],
+            ),
+            file(
+                "app/src/main/kotlin/com/acme/store/Store.kt",
+                Language::Kotlin,
+                &[],
+            ),
+            file(
+                "app/src/main/java/com/acme/util/Strings.java",
+                Language::Java,
+                &[],
+            ),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec![
+                "app/src/main/kotlin/com/acme/store/Store.kt",
+                "app/src/main/java/com/acme/util/Strings.java",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_groovy_imports() {
+        let files = vec![
+            file(
+                "src/main/groovy/com/acme/store/Cart.groovy",
+                Language::Groovy,
+                &["com.acme.model.Item", "groovy.transform.CompileStatic"],
+            ),
+            file(
+                "src/main/java/com/acme/model/Item.java",
+                Language::Java,
+                &[],
+            ),
+            file(
+                "build.gradle",
+                Language::Groovy,
+                &["gradle/publishing.gradle"],
+            ),
+            file("gradle/publishing.gradle", Language::Groovy, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                (
+                    "src/main/groovy/com/acme/store/Cart.groovy".into(),
+                    "src/main/java/com/acme/model/Item.java".into()
+                ),
+                ("build.gradle".into(), "gradle/publishing.gradle".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swift_imports() {
+        let files = vec![
+            file(
+                "Sources/App/main.swift",
+                Language::Swift,
+                &["Core", "Foundation"],
+            ),
+            file("Sources/Core/Store.swift", Language::Swift, &[]),
+            file("Sources/Core/Models/Item.swift", Language::Swift, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec!["Sources/Core/Store.swift", "Sources/Core/Models/Item.swift"]
+        );
+    }
+
+    #[test]
+    fn test_elixir_aliases() {
+        let files = vec![
+            file(
+                "lib/acme_web/controllers/cart_controller.ex",
+                Language::Elixir,
+                &["Acme.ShoppingCart", "Phoenix.Controller"],
+            ),
+            file("lib/acme/shopping_cart.ex", Language::Elixir, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![(
+                "lib/acme_web/controllers/cart_controller.ex".into(),
+                "lib/acme/shopping_cart.ex".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_haskell_imports() {
+        let files = vec![
+            file(
+                "app/Main.hs",
+                Language::Haskell,
+                &["Acme.Store", "Data.Map.Strict"],
+            ),
+            file("src/Acme/Store.hs", Language::Haskell, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![("app/Main.hs".into(), "src/Acme/Store.hs".into())]
+        );
+    }
+
+    #[test]
+    fn test_clojure_requires() {
+        let files = vec![
+            file(
+                "src/acme/web.clj",
+                Language::Clojure,
+                &["acme.shopping-cart", "clojure.string"],
+            ),
+            file("src/acme/shopping_cart.cljc", Language::Clojure, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![(
+                "src/acme/web.clj".into(),
+                "src/acme/shopping_cart.cljc".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_erlang_includes() {
+        let files = vec![
+            file(
+                "src/cart.erl",
+                Language::Erlang,
+                &["store_worker", "cart.hrl", "kernel/include/logger.hrl"],
+            ),
+            file("src/store_worker.erl", Language::Erlang, &[]),
+            file("include/cart.hrl", Language::Erlang, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("src/cart.erl".into(), "src/store_worker.erl".into()),
+                ("src/cart.erl".into(), "include/cart.hrl".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nix_imports() {
+        let files = vec![
+            file(
+                "hosts/web/configuration.nix",
+                Language::Nix,
+                &["./hardware.nix", "../common", "./missing.nix"],
+            ),
+            file("hosts/web/hardware.nix", Language::Nix, &[]),
+            file("hosts/common/default.nix", Language::Nix, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                (
+                    "hosts/web/configuration.nix".into(),
+                    "hosts/web/hardware.nix".into()
+                ),
+                (
+                    "hosts/web/configuration.nix".into(),
+                    "hosts/common/default.nix".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solidity_imports() {
+        let files = vec![
+            file(
+                "contracts/Vault.sol",
+                Language::Solidity,
+                &[
+                    "./interfaces/IVault.sol",
+                    "@openzeppelin/contracts/access/Ownable.sol",
+                ],
+            ),
+            file("contracts/interfaces/IVault.sol", Language::Solidity, &[]),
+            file(
+                "node_modules/@openzeppelin/contracts/access/Ownable.sol",
+                Language::Solidity,
+                &[],
+            ),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                (
+                    "contracts/Vault.sol".into(),
+                    "contracts/interfaces/IVault.sol".into()
+                ),
+                (
+                    "contracts/Vault.sol".into(),
+                    "node_modules/@openzeppelin/contracts/access/Ownable.sol".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_perl_uses() {
+        let files = vec![
+            file(
+                "bin/checkout.pl",
+                Language::Perl,
+                &["Acme::Cart", "List::Util", "lib/util.pl"],
+            ),
+            file("lib/Acme/Cart.pm", Language::Perl, &[]),
+            file("lib/util.pl", Language::Perl, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("bin/checkout.pl".into(), "lib/Acme/Cart.pm".into()),
+                ("bin/checkout.pl".into(), "lib/util.pl".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fsharp_opens() {
+        let files = vec![
+            file(
+                "src/App/Program.fs",
+                Language::Fsharp,
+                &["System", "Acme.Store.Cart"],
+            ),
+            file("src/Store/Cart.fs", Language::Fsharp, &[]),
+            file("scripts/report.fsx", Language::Fsharp, &["Helpers.fsx"]),
+            file("scripts/Helpers.fsx", Language::Fsharp, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("src/App/Program.fs".into(), "src/Store/Cart.fs".into()),
+                ("scripts/report.fsx".into(), "scripts/Helpers.fsx".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nim_imports() {
+        let files = vec![
+            file(
+                "src/app.nim",
+                Language::Nim,
+                &["std/strutils", "./cart", "store/items"],
+            ),
+            file("src/cart.nim", Language::Nim, &[]),
+            file("src/store/items.nim", Language::Nim, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("src/app.nim".into(), "src/cart.nim".into()),
+                ("src/app.nim".into(), "src/store/items.nim".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gleam_imports() {
+        let files = vec![
+            file("src/app.gleam", Language::Gleam, &["gleam/io", "app/cart"]),
+            file("src/app/cart.gleam", Language::Gleam, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![("src/app.gleam".into(), "src/app/cart.gleam".into())]
+        );
+    }
+
+    #[test]
+    fn test_latex_inputs() {
+        let files = vec![
+            file(
+                "thesis/main.tex",
+                Language::Latex,
+                &["chapters/intro", "appendix.tex", "figures/plot.pgf"],
+            ),
+            file("thesis/chapters/intro.tex", Language::Latex, &[]),
+            file("thesis/appendix.tex", Language::Latex, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("thesis/main.tex".into(), "thesis/chapters/intro.tex".into()),
+                ("thesis/main.tex".into(), "thesis/appendix.tex".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        let files = vec![
+            file(
+                "docs/guide.md",
+                Language::Markdown,
+                &["./api.md", "/CHANGELOG.md", "tutorial/", "../src/lib.rs"],
+            ),
+            file("docs/api.md", Language::Markdown, &[]),
+            file("CHANGELOG.md", Language::Markdown, &[]),
+            file("docs/tutorial/README.md", Language::Markdown, &[]),
+            file("src/lib.rs", Language::Rust, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec![
+                "docs/api.md",
+                "CHANGELOG.md",
+                "docs/tutorial/README.md",
+                "src/lib.rs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_html_scripts() {
+        let files = vec![
+            file(
+                "site/index.html",
+                Language::Html,
+                &[
+                    "/static/app.js",
+                    "./format.js",
+                    "https://cdn.example.com/lib.js",
+                ],
+            ),
+            file("site/static/app.js", Language::Javascript, &[]),
+            file("site/format.js", Language::Javascript, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec!["site/static/app.js", "site/format.js"]
+        );
+    }
+
+    #[test]
+    fn test_zig_imports() {
+        let files = vec![
+            file("src/main.zig", Language::Zig, &["std", "store/cart.zig"]),
+            file("src/store/cart.zig", Language::Zig, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![("src/main.zig".into(), "src/store/cart.zig".into())]
+        );
+    }
+
+    #[test]
+    fn test_dart_imports() {
+        let files = vec![
+            file(
+                "lib/main.dart",
+                Language::Dart,
+                &[
+                    "package:flutter/material.dart",
+                    "package:shop/src/cart.dart",
+                    "widgets/badge.dart",
+                ],
+            ),
+            file("lib/src/cart.dart", Language::Dart, &[]),
+            file("lib/widgets/badge.dart", Language::Dart, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec!["lib/src/cart.dart", "lib/widgets/badge.dart"]
+        );
+    }
+
+    #[test]
+    fn test_julia_imports() {
+        let files = vec![
+            file(
+                "src/Shop.jl",
+                Language::Julia,
+                &["Geometry", "LinearAlgebra", "cart.jl"],
+            ),
+            file("src/cart.jl", Language::Julia, &[]),
+            file("lib/Geometry/src/Geometry.jl", Language::Julia, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec!["src/cart.jl", "lib/Geometry/src/Geometry.jl"]
+        );
+    }
+
+    #[test]
+    fn test_r_sources() {
+        let files = vec![
+            file(
+                "analysis/report.R",
+                Language::R,
+                &["dplyr", "R/utils.R", "helpers.R"],
+            ),
+            file("R/utils.R", Language::R, &[]),
+            file("analysis/helpers.R", Language::R, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec!["R/utils.R", "analysis/helpers.R"]
+        );
+    }
+
+    #[test]
+    fn test_shell_sources() {
+        let files = vec![
+            file(
+                "scripts/deploy.sh",
+                Language::Shell,
+                &[
+                    "$(dirname \"$0\")/lib/common.sh",
+                    "\"$ROOT\"/scripts/env.sh",
+                    "~/.bashrc",
+                ],
+            ),
+            file("scripts/lib/common.sh", Language::Shell, &[]),
+            file("scripts/env.sh", Language::Shell, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec!["scripts/lib/common.sh", "scripts/env.sh"]
+        );
+    }
+
+    #[test]
+    fn test_powershell_imports() {
+        let files = vec![
+            file(
+                "scripts/Deploy.ps1",
+                Language::Powershell,
+                &[
+                    "$PSScriptRoot\\lib\\Common.ps1",
+                    "$PSScriptRoot\\..\\modules\\Acme.Tools",
+                    "Az.Accounts",
+                ],
+            ),
+            file("scripts/lib/Common.ps1", Language::Powershell, &[]),
+            file(
+                "modules/Acme.Tools/Acme.Tools.psm1",
+                Language::Powershell,
+                &[],
+            ),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec![
+                "scripts/lib/Common.ps1",
+                "modules/Acme.Tools/Acme.Tools.psm1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_terraform_module_sources() {
+        let files = vec![
+            file(
+                "envs/prod/main.tf",
+                Language::Hcl,
+                &["../../modules/vpc", "terraform-aws-modules/eks/aws"],
+            ),
+            file("modules/vpc/main.tf", Language::Hcl, &[]),
+            file("modules/vpc/outputs.tf", Language::Hcl, &[]),
+            file("modules/vpc/nested/main.tf", Language::Hcl, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files)
+                .into_iter()
+                .map(|(_, to)| to)
+                .collect::<Vec<_>>(),
+            vec!["modules/vpc/main.tf", "modules/vpc/outputs.tf"]
+        );
+    }
+
+    #[test]
+    fn test_protobuf_imports() {
+        let files = vec![
+            file(
+                "proto/acme/store/v1/cart.proto",
+                Language::Protobuf,
+                &[
+                    "acme/store/v1/item.proto",
+                    "google/protobuf/timestamp.proto",
+                ],
+            ),
+            file("proto/acme/store/v1/item.proto", Language::Protobuf, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![(
+                "proto/acme/store/v1/cart.proto".into(),
+                "proto/acme/store/v1/item.proto".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_csharp_usings() {
+        let files = vec![
+            file(
+                "src/Acme.Api/Program.cs",
+                Language::Csharp,
+                &["Acme.Store", "Acme.Models", "System.Linq"],
+            ),
+            file("src/Acme.Store/Cart.cs", Language::Csharp, &[]),
+            file("src/Acme/Models/User.cs", Language::Csharp, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                (
+                    "src/Acme.Api/Program.cs".into(),
+                    "src/Acme.Store/Cart.cs".into()
+                ),
+                (
+                    "src/Acme.Api/Program.cs".into(),
+                    "src/Acme/Models/User.cs".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ruby_requires() {
+        let files = vec![
+            file(
+                "app/models/user.rb",
+                Language::Ruby,
+                &["concerns/searchable", "accounts/team", "json"],
+            ),
+            file("app/models/concerns/searchable.rb", Language::Ruby, &[]),
+            file("lib/accounts/team.rb", Language::Ruby, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                (
+                    "app/models/user.rb".into(),
+                    "app/models/concerns/searchable.rb".into()
+                ),
+                ("app/models/user.rb".into(), "lib/accounts/team.rb".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_php_uses() {
+        let files = vec![
+            file(
+                "app/Http/Controllers/UserController.php",
+                Language::Php,
+                &["App\\Models\\User", "Illuminate\\Http\\Request"],
+            ),
+            file("app/Models/User.php", Language::Php, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![(
+                "app/Http/Controllers/UserController.php".into(),
+                "app/Models/User.php".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_cpp_includes() {
+        let files = vec![
+            file(
+                "src/app/main.cc",
+                Language::Cpp,
+                &["util.h", "store/record.h", "vector"],
+            ),
+            file("src/app/util.h", Language::Cpp, &[]),
+            file("include/store/record.h", Language::Cpp, &[]),
+        ];
+
+        assert_eq!(
+            edges(&files),
+            vec![
+                ("src/app/main.cc".into(), "src/app/util.h".into()),
+                ("src/app/main.cc".into(), "include/store/record.h".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_go_imports_from_an_absolute_root() {
+        let files = vec![
+            file(
+                "/work/app/cmd/server/main.go",
+                Language::Go,
+                &["example.com/app/internal/store", "fmt"],
+            ),
+            file("/work/app/internal/store/store.go", Language::Go, &[]),
+        ];
+
+        let graph = DependencyGraph::build("/work/app", &files);
+        let edges: Vec<(usize, usize)> = graph
+            .graph
+            .raw_edges()
+            .iter()
+            .map(|e| (graph.graph[e.source()], graph.graph[e.target()]))
+            .collect();
+        assert_eq!(edges, vec![(0, 1)]);
+        // Without the root, `/work/app/internal/store` is no import's suffix
+        assert_eq!(DependencyGraph::build("/", &files).graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_mermaid_lists_connected_files_only() {
+        let files = vec![
+            file("a.ts", Language::Typescript, &["./b"]),
+            file("b.ts", Language::Typescript, &[]),
+            file("c.ts", Language::Typescript, &[]),
+        ];
+
+        let mermaid = DependencyGraph::build(".", &files).to_mermaid(&files);
+
+        assert!(mermaid.starts_with("```mermaid\ngraph TD\n"));
+        assert!(mermaid.contains("n0[\"a.ts\"]"));
+        assert!(mermaid.contains("n0 --> n1"));
+        assert!(!mermaid.contains("c.ts"));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::formatter::{self, FileSymbols, RepoStats};
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { margin-bottom: 0.25rem; }
+.meta { color: #59636e; margin-top: 0; }
+#search { width: 100%; max-width: 40rem; padding: 0.5rem; font-size: 1rem; margin: 1rem 0; }
+details { margin-left: 1rem; }
+summary { cursor: pointer; padding: 0.15rem 0; }
+summary .count { color: #59636e; font-size: 0.85em; }
+.dir > summary { font-weight: 600; }
+.imports { color: #59636e; font-size: 0.85em; margin: 0.25rem 0 0.5rem 1rem; }
+table { border-collapse: collapse; margin: 0.25rem 0 0.75rem 1rem; font-size: 0.9em; }
+th, td { text-align: left; padding: 0.15rem 0.75rem; border-bottom: 1px solid #d1d9e0; }
+td.name { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; white-space: pre; }
+.hidden { display: none; }
+"#;
+
+const SCRIPT: &str = r#"
+const search = document.getElementById("search");
+search.addEventListener("input", () => {
+  const query = search.value.trim().toLowerCase();
+  document.querySelectorAll(".file").forEach((file) => {
+    const pathMatch = file.dataset.path.toLowerCase().includes(query);
+    let rowMatch = false;
+    file.querySelectorAll("tbody tr").forEach((row) => {
+      const hit = !query || pathMatch || row.textContent.toLowerCase().includes(query);
+      row.classList.toggle("hidden", !hit);
+      rowMatch = rowMatch || hit;
+    });
+    const visible = !query || pathMatch || rowMatch;
+    file.classList.toggle("hidden", !visible);
+    file.open = query !== "" && visible;
+  });
+  document.querySelectorAll(".dir").forEach((dir) => {
+    const visible = dir.querySelector(".file:not(.hidden)") !== null;
+    dir.classList.toggle("hidden", !visible);
+    if (query) dir.open = visible;
+  });
+});
+"#;
+
+#[derive(Default)]
+struct DirNode<'a> {
+    dirs: BTreeMap<String, DirNode<'a>>,
+    files: Vec<(String, &'a FileSymbols)>,
+}
+
+impl<'a> DirNode<'a> {
+    fn insert(&mut self, file: &'a FileSymbols) {
+        let parts: Vec<String> = Path::new(&file.path)
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+
+        let Some((file_name, dirs)) = parts.split_last() else {
+            return;
+        };
+
+        let mut node = self;
+        for dir in dirs {
+            node = node.dirs.entry(dir.clone()).or_default();
+        }
+        node.files.push((file_name.clone(), file));
+    }
+
+    fn file_count(&self) -> usize {
+        self.files.len() + self.dirs.values().map(DirNode::file_count).sum::<usize>()
+    }
+
+    fn render(&self, out: &mut String) {
+        for (name, child) in &self.dirs {
+            out.push_str(&format!(
+                "<details class=\"dir\" open><summary>{}/ <span class=\"count\">({} files)</span></summary>\n",
+                escape(name),
+                child.file_count()
+            ));
+            child.render(out);
+            out.push_str("</details>\n");
+        }
+        for (name, file) in &self.files {
+            render_file(name, file, out);
+        }
+    }
+}
+
+fn render_file(name: &str, file: &FileSymbols, out: &mut String) {
+    out.push_str(&format!(
+        "<details class=\"file\" data-path=\"{}\"><summary>{} <span class=\"count\">({} symbols, {} lines)</span></summary>\n",
+        escape(&file.path),
+        escape(name),
+        file.symbols.len(),
+        file.lines
+    ));
+
+    if !file.imports.is_empty() {
+        out.push_str(&format!(
+            "<div class=\"imports\">imports: {}</div>\n",
+            escape(&file.imports.join(", "))
+        ));
+    }
+
+    if !file.symbols.is_empty() {
+        out.push_str(
+            "<table><thead><tr><th>Line</th><th>Kind</th><th>Symbol</th><th>Lines</th></tr></thead><tbody>\n",
+        );
+        for sym in &file.symbols {
+            out.push_str(&format!(
+                "<tr><td>L{}</td><td>{}</td><td class=\"name\">{}</td><td>{}</td></tr>\n",
+                sym.line,
+                escape(&sym.kind),
+                escape(&formatter::display_name(sym)),
+                sym.end_line - sym.line + 1
+            ));
+        }
+        out.push_str("</tbody></table>\n");
+    }
+
+    out.push_str("</details>\n");
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Builds a self-contained HTML report with a collapsible file tree, symbol
+/// tables, and client-side filtering.
+pub fn assemble_html(root: &str, stats: &RepoStats) -> String {
+    let mut tree = DirNode::default();
+    for file in &stats.files {
+        tree.insert(file);
+    }
+
+    let mut body = String::new();
+    tree.render(&mut body);
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Repository Map: {root}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>Repository Map</h1>\n<p class=\"meta\">Root: <code>{root}</code> &middot; Files: {count}</p>\n<input id=\"search\" type=\"search\" placeholder=\"Filter files and symbols...\" autofocus>\n<div id=\"tree\">\n{body}</div>\n<script>{SCRIPT}</script>\n</body>\n</html>\n",
+        root = escape(root),
+        count = stats.file_count,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::Language;
+    use crate::parser::Symbol;
+
+    fn file(path: &str, symbol: &str) -> FileSymbols {
+        FileSymbols {
+            path: path.to_string(),
+            language: Language::Rust,
+            lines: 3,
+            imports: vec!["std::fmt".to_string()],
+            symbols: vec![Symbol {
+                name: symbol.into(),
+                parent: Some("Config".into()),
+                line: 1,
+                kind: "function_item".into(),
+                end_line: 3,
+                test: false,
+            }],
+            omitted: 0,
+            references: vec![],
+        }
+    }
+
+    #[test]
+    fn test_html_tree_and_escaping() {
+        let mut stats = RepoStats::new();
+        stats.add_file(file("./src/lib.rs", "parse<T>"));
+        stats.add_file(file("./src/cli/args.rs", "build"));
+
+        let html = assemble_html(".", &stats);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<summary>src/ <span class=\"count\">(2 files)</span>"));
+        assert!(html.contains("<summary>cli/ <span class=\"count\">(1 files)</span>"));
+        assert!(html.contains("data-path=\"./src/lib.rs\""));
+        assert!(html.contains("Config &gt; parse&lt;T&gt;"));
+        assert!(!html.contains("parse<T>"));
+    }
+}
@@ -0,0 +1,484 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum, Serialize, Deserialize, Debug,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Rust,
+    Python,
+    Go,
+    Javascript,
+    Typescript,
+    Tsx,
+    Markdown,
+    Java,
+    Cpp,
+    Csharp,
+    Ruby,
+    Php,
+    Kotlin,
+    Swift,
+    Elixir,
+    Haskell,
+    Zig,
+    Dart,
+    Julia,
+    R,
+    Shell,
+    Powershell,
+    Hcl,
+    Graphql,
+    Protobuf,
+    Dockerfile,
+    Yaml,
+    Toml,
+    Json,
+    Html,
+    Vue,
+    Svelte,
+    Jupyter,
+    Clojure,
+    Erlang,
+    Nix,
+    Solidity,
+    Perl,
+    Groovy,
+    Fsharp,
+    Nim,
+    Gleam,
+    Rst,
+    Latex,
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self
+            .to_possible_value()
+            .expect("no language variants are skipped");
+        f.write_str(value.get_name())
+    }
+}
+
+pub fn infer_language(path: &Path) -> Option<Language> {
+    if let Some(language) = file_name_language(path) {
+        return Some(language);
+    }
+    match path.extension()?.to_str()? {
+        "rs" => Some(Language::Rust),
+        "py" => Some(Language::Python),
+        "go" => Some(Language::Go),
+        "js" | "jsx" => Some(Language::Javascript),
+        "ts" => Some(Language::Typescript),
+        "tsx" => Some(Language::Tsx),
+        "md" | "mdx" => Some(Language::Markdown),
+        "java" => Some(Language::Java),
+        "cs" | "csx" => Some(Language::Csharp),
+        "rb" | "rake" | "gemspec" | "ru" => Some(Language::Ruby),
+        "php" => Some(Language::Php),
+        "kt" | "kts" => Some(Language::Kotlin),
+        "swift" => Some(Language::Swift),
+        "ex" | "exs" => Some(Language::Elixir),
+        "hs" => Some(Language::Haskell),
+        "zig" => Some(Language::Zig),
+        "dart" => Some(Language::Dart),
+        "jl" => Some(Language::Julia),
+        "r" | "R" => Some(Language::R),
+        "sh" | "bash" | "zsh" | "ksh" => Some(Language::Shell),
+        "ps1" | "psm1" => Some(Language::Powershell),
+        "tf" | "tfvars" | "hcl" => Some(Language::Hcl),
+        "graphql" | "gql" => Some(Language::Graphql),
+        "proto" => Some(Language::Protobuf),
+        "dockerfile" => Some(Language::Dockerfile),
+        "yml" | "yaml" => Some(Language::Yaml),
+        "html" | "htm" => Some(Language::Html),
+        "vue" => Some(Language::Vue),
+        "svelte" => Some(Language::Svelte),
+        "ipynb" => Some(Language::Jupyter),
+        "clj" | "cljs" | "cljc" => Some(Language::Clojure),
+        "erl" | "hrl" => Some(Language::Erlang),
+        "nix" => Some(Language::Nix),
+        "sol" => Some(Language::Solidity),
+        "pl" | "pm" | "t" => Some(Language::Perl),
+        "groovy" | "gvy" | "gradle" => Some(Language::Groovy),
+        "fs" | "fsi" | "fsx" => Some(Language::Fsharp),
+        "nim" | "nims" => Some(Language::Nim),
+        "gleam" => Some(Language::Gleam),
+        "rst" => Some(Language::Rst),
+        "tex" | "ltx" => Some(Language::Latex),
+        "cpp" | "cc" | "cxx" | "c++" | "hpp" | "hh" | "hxx" | "h" | "ipp" | "tpp" => {
+            Some(Language::Cpp)
+        }
+        _ => None,
+    }
+}
+
+/// The language of files known by name rather than extension, like
+/// `Dockerfile` or `Dockerfile.dev`, and of the project manifests that are
+/// summarized, while other TOML and JSON files are not mapped.
+fn file_name_language(path: &Path) -> Option<Language> {
+    match path.file_name()?.to_str()? {
+        "Dockerfile" | "Containerfile" => Some(Language::Dockerfile),
+        name if name.starts_with("Dockerfile.") => Some(Language::Dockerfile),
+        "Cargo.toml" | "pyproject.toml" => Some(Language::Toml),
+        "package.json" => Some(Language::Json),
+        _ => None,
+    }
+}
+
+/// How much of an extensionless file is read looking for a shebang line.
+const SHEBANG_BYTES: usize = 256;
+/// How much of a file [`sniff_language`] looks at.
+const SNIFF_BYTES: usize = 4096;
+/// Fewer telltale lines than this and [`sniff_language`] doesn't guess.
+const SNIFF_MIN_SCORE: usize = 2;
+
+/// Line starts that give a language away, for [`sniff_language`].
+const TELLTALES: &[(Language, &[&str])] = &[
+    (
+        Language::Rust,
+        &[
+            "fn ",
+            "pub fn ",
+            "pub struct ",
+            "impl ",
+            "use std::",
+            "let mut ",
+            "#[derive(",
+        ],
+    ),
+    (
+        Language::Python,
+        &[
+            "def ",
+            "async def ",
+            "from ",
+            "if __name__ ==",
+            "elif ",
+            "self.",
+        ],
+    ),
+    (
+        Language::Go,
+        &["package ", "func ", "import (", "type ", ":= "],
+    ),
+    (
+        Language::Javascript,
+        &["function ", "const ", "module.exports", "require(", "var "],
+    ),
+    (
+        Language::Typescript,
+        &[
+            "interface ",
+            "export interface ",
+            "export type ",
+            "type ",
+            "enum ",
+        ],
+    ),
+    (
+        Language::Markdown,
+        &["# ", "## ", "### ", "```", "- [", "* "],
+    ),
+];
+
+/// Works out a file's language: extensions the project maps come first, then
+/// [`infer_language`]'s built-in ones, then the shebang line of a file with
+/// no extension. With [`Self::detect_content`] set, extensionless files
+/// without a shebang are sniffed too, and so are `.ts` files, which may be
+/// Qt translations rather than TypeScript.
+#[derive(Clone, Debug, Default)]
+pub struct LanguageDetector {
+    extensions: HashMap<String, Language>,
+    detect_content: bool,
+}
+
+impl LanguageDetector {
+    /// Parses files ending in `.ext` as `language`, overriding the built-in
+    /// mapping. A leading dot is ignored, and extensions match exactly.
+    pub fn map_extension(&mut self, ext: &str, language: Language) {
+        self.extensions
+            .insert(ext.trim_start_matches('.').to_string(), language);
+    }
+
+    /// Guesses the language of files whose extension is missing or
+    /// ambiguous from their content, at the cost of another read.
+    pub fn detect_content(&mut self, detect: bool) {
+        self.detect_content = detect;
+    }
+
+    /// Reads the start of the file only if it has no extension, or it needs
+    /// sniffing.
+    pub fn detect(&self, path: &Path) -> Option<Language> {
+        let Some(ext) = path.extension() else {
+            if let Some(language) = file_name_language(path) {
+                return Some(language);
+            }
+            let len = if self.detect_content {
+                SNIFF_BYTES
+            } else {
+                SHEBANG_BYTES
+            };
+            let head = crate::walk::read_head(path, len)?;
+            let head = String::from_utf8_lossy(&head);
+            return shebang_language(&head)
+                .or_else(|| self.detect_content.then(|| sniff_language(&head)).flatten());
+        };
+        let ext = ext.to_str()?;
+        if let Some(&language) = self.extensions.get(ext) {
+            return Some(language);
+        }
+        let language = infer_language(path)?;
+        if self.detect_content && ext == "ts" {
+            let head = crate::walk::read_head(path, SNIFF_BYTES)?;
+            // Qt Linguist `.ts` files are XML
+            if String::from_utf8_lossy(&head).trim_start().starts_with('<') {
+                return None;
+            }
+        }
+        Some(language)
+    }
+}
+
+/// The language most lines of `head` start like, if one clearly leads.
+pub fn sniff_language(head: &str) -> Option<Language> {
+    let mut scores: Vec<(Language, usize)> = TELLTALES
+        .iter()
+        .map(|&(language, starts)| {
+            let score = head
+                .lines()
+                .map(str::trim_start)
+                .filter(|line| starts.iter().any(|start| line.starts_with(start)))
+                .count();
+            (language, score)
+        })
+        .collect();
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    match scores[..] {
+        [(language, best), (_, second), ..] if best >= SNIFF_MIN_SCORE && best > second => {
+            Some(language)
+        }
+        _ => None,
+    }
+}
+
+/// The language a `#!` line at the start of `head` runs, like
+/// `#!/usr/bin/env python3` or `#!/usr/local/bin/node`.
+pub fn shebang_language(head: &str) -> Option<Language> {
+    let line = head.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = interpreter_name(words.next()?);
+    // `env` runs the next word that isn't a flag or a VAR=value
+    if program == "env" {
+        program = interpreter_name(words.find(|w| !w.starts_with('-') && !w.contains('='))?);
+    }
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match program {
+        "python" | "pypy" => Some(Language::Python),
+        "node" | "nodejs" => Some(Language::Javascript),
+        "deno" | "ts-node" | "tsx" | "bun" => Some(Language::Typescript),
+        "rust-script" | "cargo" => Some(Language::Rust),
+        "ruby" | "jruby" => Some(Language::Ruby),
+        "php" => Some(Language::Php),
+        "kotlin" => Some(Language::Kotlin),
+        "swift" => Some(Language::Swift),
+        "elixir" => Some(Language::Elixir),
+        "runhaskell" | "runghc" => Some(Language::Haskell),
+        "dart" => Some(Language::Dart),
+        "julia" => Some(Language::Julia),
+        "Rscript" => Some(Language::R),
+        "sh" | "bash" | "zsh" | "ksh" | "dash" => Some(Language::Shell),
+        "pwsh" => Some(Language::Powershell),
+        "escript" => Some(Language::Erlang),
+        "perl" => Some(Language::Perl),
+        "groovy" => Some(Language::Groovy),
+        _ => None,
+    }
+}
+
+fn interpreter_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// The tree-sitter grammar for `lang`. Languages without one bundled are
+/// outlined line by line instead; see [`crate::outline`].
+pub fn get_ts_language(lang: Language) -> Option<tree_sitter::Language> {
+    let grammar = match lang {
+        Language::Rust => tree_sitter_rust::LANGUAGE,
+        Language::Python => tree_sitter_python::LANGUAGE,
+        Language::Go => tree_sitter_go::LANGUAGE,
+        Language::Javascript => tree_sitter_javascript::LANGUAGE,
+        Language::Typescript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        Language::Tsx => tree_sitter_typescript::LANGUAGE_TSX,
+        Language::Markdown => tree_sitter_md::LANGUAGE,
+        Language::Java
+        | Language::Cpp
+        | Language::Csharp
+        | Language::Ruby
+        | Language::Php
+        | Language::Kotlin
+        | Language::Swift
+        | Language::Elixir
+        | Language::Haskell
+        | Language::Zig
+        | Language::Dart
+        | Language::Julia
+        | Language::R
+        | Language::Shell
+        | Language::Powershell
+        | Language::Hcl
+        | Language::Graphql
+        | Language::Protobuf
+        | Language::Dockerfile
+        | Language::Yaml
+        | Language::Toml
+        | Language::Json
+        | Language::Html
+        | Language::Vue
+        | Language::Svelte
+        | Language::Jupyter
+        | Language::Clojure
+        | Language::Erlang
+        | Language::Nix
+        | Language::Solidity
+        | Language::Perl
+        | Language::Groovy
+        | Language::Fsharp
+        | Language::Nim
+        | Language::Gleam
+        | Language::Rst
+        | Language::Latex => {
+            return None;
+        }
+    };
+    Some(grammar.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_infer_language() {
+        assert_eq!(infer_language(Path::new("main.rs")), Some(Language::Rust));
+        assert_eq!(
+            infer_language(Path::new("README.md")),
+            Some(Language::Markdown)
+        );
+        assert_eq!(
+            infer_language(Path::new("script.py")),
+            Some(Language::Python)
+        );
+        assert_eq!(infer_language(Path::new("photo.jpg")), None);
+        assert_eq!(
+            infer_language(Path::new("docker/Dockerfile.dev")),
+            Some(Language::Dockerfile)
+        );
+        assert_eq!(
+            infer_language(Path::new("web/package.json")),
+            Some(Language::Json)
+        );
+        assert_eq!(infer_language(Path::new("tsconfig.json")), None);
+        assert_eq!(
+            LanguageDetector::default().detect(Path::new("Dockerfile")),
+            Some(Language::Dockerfile)
+        );
+    }
+
+    #[test]
+    fn test_shebangs() {
+        let cases = [
+            (
+                "#!/usr/bin/env python3\nimport sys\n",
+                Some(Language::Python),
+            ),
+            ("#!/usr/bin/python3.11 -u\n", Some(Language::Python)),
+            (
+                "#!/usr/bin/env -S node --no-warnings\n",
+                Some(Language::Javascript),
+            ),
+            (
+                "#!/usr/bin/env -S deno run --allow-net\n",
+                Some(Language::Typescript),
+            ),
+            (
+                "#!/usr/bin/env NODE_ENV=production node\n",
+                Some(Language::Javascript),
+            ),
+            ("#!/usr/bin/env ruby -w\n", Some(Language::Ruby)),
+            ("#!/bin/sh\n", Some(Language::Shell)),
+            ("#!/usr/bin/env escript\n", Some(Language::Erlang)),
+            ("#!/usr/bin/perl -w\n", Some(Language::Perl)),
+            ("#!/usr/bin/env groovy\n", Some(Language::Groovy)),
+            (
+                "#!/usr/bin/env bash\nset -euo pipefail\n",
+                Some(Language::Shell),
+            ),
+            ("import sys\n", None),
+            ("\n#!/usr/bin/env python\n", None),
+        ];
+        for (head, expected) in cases {
+            assert_eq!(shebang_language(head), expected, "{:?}", head);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("deploy");
+        std::fs::write(&script, "#!/usr/bin/env python3\ndef main():\n    pass\n").unwrap();
+        let detector = LanguageDetector::default();
+        assert_eq!(detector.detect(&script), Some(Language::Python));
+        let other = dir.path().join("notes.txt");
+        std::fs::write(&other, "#!/usr/bin/env python3\n").unwrap();
+        assert_eq!(detector.detect(&other), None);
+    }
+
+    #[test]
+    fn test_sniffing() {
+        let rust = "use std::fs;\n\npub fn main() {\n    let mut x = 1;\n}\nfn helper() {}\n";
+        assert_eq!(sniff_language(rust), Some(Language::Rust));
+        let python =
+            "import os\n\ndef main():\n    if x:\n        pass\n    elif y:\n        pass\n";
+        assert_eq!(sniff_language(python), Some(Language::Python));
+        let go = "package main\n\nfunc main() {\n\tx := 1\n}\n";
+        assert_eq!(sniff_language(go), Some(Language::Go));
+        assert_eq!(sniff_language("hello world\nthis is prose\n"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("Tiltfile");
+        std::fs::write(&script, python).unwrap();
+        let qt = dir.path().join("app_de.ts");
+        std::fs::write(&qt, "<?xml version=\"1.0\"?>\n<TS version=\"2.1\">\n").unwrap();
+        let mut detector = LanguageDetector::default();
+        assert_eq!(detector.detect(&script), None);
+        assert_eq!(detector.detect(&qt), Some(Language::Typescript));
+        detector.detect_content(true);
+        assert_eq!(detector.detect(&script), Some(Language::Python));
+        assert_eq!(detector.detect(&qt), None);
+    }
+
+    #[test]
+    fn test_mapped_extensions() {
+        let mut detector = LanguageDetector::default();
+        detector.map_extension("pyi", Language::Python);
+        detector.map_extension(".mjs", Language::Javascript);
+        detector.map_extension("md", Language::Typescript);
+        assert_eq!(
+            detector.detect(Path::new("stubs/os.pyi")),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            detector.detect(Path::new("app.mjs")),
+            Some(Language::Javascript)
+        );
+        assert_eq!(
+            detector.detect(Path::new("README.md")),
+            Some(Language::Typescript)
+        );
+        assert_eq!(detector.detect(Path::new("main.rs")), Some(Language::Rust));
+        assert_eq!(detector.detect(Path::new("App.PYI")), None);
+    }
+}
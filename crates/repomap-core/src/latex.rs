@@ -0,0 +1,222 @@
+//! Section outlines of LaTeX documents, like the papers and theses kept
+//! beside a research project's code: `\chapter`, `\section`, and the rest,
+//! as headings leveled by how deep their command nests, starting from the
+//! outermost one the document uses. `\input` and `\include` are its
+//! imports, so a thesis split into chapter files maps as one.
+
+use crate::parser::{Extracted, Symbol};
+use regex::Regex;
+use smol_str::{SmolStr, format_smolstr};
+use std::sync::LazyLock;
+
+/// Sectioning commands, outermost first.
+const SECTIONS: &[&str] = &[
+    "part",
+    "chapter",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// Environments whose contents are not LaTeX, like a `verbatim` listing.
+const VERBATIM: &[&str] = &["verbatim", "lstlisting", "minted", "comment"];
+
+/// A sectioning command up to the brace opening its title, past a starred
+/// form or a short title for the table of contents, like `\section*{` or
+/// `\section[Short]{`.
+static SECTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\\(?P<command>[a-z]+)\*?\s*(?:\[[^\]]*\]\s*)?\{")
+        .expect("built-in pattern is valid")
+});
+
+static INPUT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\\(?:input|include|subfile)\s*\{(?P<path>[^}]+)\}")
+        .expect("built-in pattern is valid")
+});
+
+static BEGIN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\\begin\s*\{(?P<env>\w+)\*?\}").expect("built-in pattern is valid")
+});
+
+/// A command in a title, like the `\emph` of `\emph{Related} Work`.
+static COMMAND: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\\[a-zA-Z@]+\*?").expect("built-in pattern is valid"));
+
+/// Outlines the sections of `source` and finds the files it pulls in.
+pub fn extract(source: &str) -> Extracted {
+    let code = code(source);
+
+    // Command depth, title, first and last line
+    let mut sections = Vec::new();
+    for caps in SECTION.captures_iter(&code) {
+        let (Some(command), Some(all)) = (caps.name("command"), caps.get(0)) else {
+            continue;
+        };
+        let Some(depth) = SECTIONS.iter().position(|&name| name == command.as_str()) else {
+            continue;
+        };
+        let close = closing_brace(&code, all.end());
+        let title = plain(&code[all.end()..close]);
+        if title.is_empty() {
+            continue;
+        }
+        let line = code[..all.start()].matches('\n').count();
+        let end = line + code[all.start()..close].matches('\n').count();
+        sections.push((depth, title, line, end));
+    }
+
+    let outermost = sections.iter().map(|&(depth, ..)| depth).min().unwrap_or(0);
+    let symbols = sections
+        .into_iter()
+        .map(|(depth, title, line, end)| Symbol {
+            name: SmolStr::new(title),
+            parent: None,
+            line: line + 1,
+            kind: format_smolstr!("h{}", depth - outermost + 1),
+            end_line: end + 1,
+            test: false,
+        })
+        .collect();
+
+    let mut imports: Vec<String> = Vec::new();
+    for caps in INPUT.captures_iter(&code) {
+        let path = caps.name("path").map_or("", |path| path.as_str().trim());
+        if !path.is_empty() && !imports.iter().any(|seen| seen == path) {
+            imports.push(path.to_string());
+        }
+    }
+
+    Extracted {
+        symbols,
+        imports,
+        ..Extracted::default()
+    }
+}
+
+/// `source` with its comments and verbatim environments blanked out, line
+/// for line.
+fn code(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    // The `\end{...}` closing the verbatim environment a line is in
+    let mut verbatim: Option<String> = None;
+    for line in source.lines() {
+        if let Some(end) = &verbatim {
+            if line.contains(end.as_str()) {
+                verbatim = None;
+            }
+            code.push('\n');
+            continue;
+        }
+        let line = uncommented(line);
+        if let Some(env) = BEGIN
+            .captures(line)
+            .and_then(|caps| caps.name("env"))
+            .filter(|env| VERBATIM.contains(&env.as_str()))
+        {
+            let end = format!("\\end{{{}", env.as_str());
+            if !line.contains(&end) {
+                verbatim = Some(end);
+            }
+        }
+        code.push_str(line);
+        code.push('\n');
+    }
+    code
+}
+
+/// `line` before its `%` comment, if any; an escaped `\%` is text.
+fn uncommented(line: &str) -> &str {
+    let mut escaped = false;
+    for (at, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '%' => return &line[..at],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Where the group opened just before `start` closes, or the end of `code`.
+fn closing_brace(code: &str, start: usize) -> usize {
+    let mut depth = 1usize;
+    let mut escaped = false;
+    for (at, c) in code[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return start + at;
+                }
+            }
+            _ => {}
+        }
+    }
+    code.len()
+}
+
+/// A title as it reads, without its commands, braces, or line breaks.
+fn plain(title: &str) -> String {
+    COMMAND
+        .replace_all(title, "")
+        .replace(['{', '}', '\\'], "")
+        .replace('~', " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let source = r#"\documentclass{report}
+\usepackage{amsmath} % \section{Not this}
+
+\begin{document}
+
+\chapter{Introduction}\label{ch:intro}
+Some text, 50\% of it \textbf{bold}.
+
+\section[Short]{Background and
+  \emph{Related} Work}
+
+\subsection*{Acknowledgments}
+
+% \section{Commented out}
+
+\begin{verbatim}
+\section{Not this either}
+\end{verbatim}
+
+\input{chapters/methods}
+\include{appendix}
+
+\chapter{Results}
+\end{document}
+"#;
+        let extracted = extract(source);
+        assert_eq!(
+            extracted
+                .symbols
+                .iter()
+                .map(|sym| (format!("{} {}", sym.kind, sym.name), sym.line, sym.end_line))
+                .collect::<Vec<_>>(),
+            [
+                ("h1 Introduction".into(), 6, 6),
+                ("h2 Background and Related Work".into(), 9, 10),
+                ("h3 Acknowledgments".into(), 12, 12),
+                ("h1 Results".into(), 23, 23),
+            ]
+        );
+        assert_eq!(extracted.imports, ["chapters/methods", "appendix"]);
+    }
+}
@@ -0,0 +1,44 @@
+//! Repository map generation behind the `repomap` CLI: walks a tree,
+//! extracts symbols and imports with tree-sitter, and renders the result as
+//! Markdown, JSON, and the other supported formats.
+
+pub mod aider;
+pub mod budget;
+pub mod builder;
+pub mod cache;
+pub mod collapse;
+pub mod ctags;
+pub mod focus;
+pub mod formatter;
+pub mod generated;
+pub mod graph;
+pub mod html;
+pub mod languages;
+pub mod latex;
+pub mod manifest;
+pub mod markdown;
+pub mod markup;
+pub mod nix;
+pub mod notebook;
+pub mod outline;
+pub mod parser;
+pub mod queries;
+pub mod rank;
+pub mod rst;
+pub mod split;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod template;
+pub mod testing;
+pub mod tokens;
+pub mod walk;
+pub mod yaml;
+
+pub use budget::Omitted;
+pub use builder::{Exclusion, ListedFile, MapOutput, RepoMapBuilder};
+pub use formatter::{FileSymbols, OutputFormat, RepoStats};
+pub use languages::Language;
+pub use parser::{Symbol, SymbolKind};
+pub use queries::{QueryMode, QueryRegistry};
+pub use testing::TestFilter;
+pub use tokens::{Model, Tokenizer};
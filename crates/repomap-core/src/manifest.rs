@@ -0,0 +1,417 @@
+//! Summaries of the project manifests repomap recognizes by name, which
+//! describe a project rather than define code: `Cargo.toml`'s package, bins,
+//! features, and workspace members, `pyproject.toml`'s project and entry
+//! points, and `package.json`'s name, scripts, and workspaces. Read line by
+//! line, like [`crate::yaml`], so every entry keeps the lines it spans.
+
+use crate::parser::{Extracted, Symbol};
+use regex::Regex;
+use smol_str::SmolStr;
+use std::sync::LazyLock;
+
+/// A `[table]` or `[[array.of.tables]]` header.
+static TOML_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?P<array>\[)?\[\s*(?P<name>[^\[\]]+?)\s*\]")
+        .expect("built-in pattern is valid")
+});
+
+/// A `key = value` line, the key bare or quoted.
+static TOML_KEY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(?P<key>"[^"]*"|'[^']*'|[\w.-]+)\s*=\s*(?P<value>.*)$"#)
+        .expect("built-in pattern is valid")
+});
+
+static TOML_STRING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#""(?P<text>[^"]*)"|'(?P<literal>[^']*)'"#).expect("built-in pattern is valid")
+});
+
+/// One table of a TOML document and the keys set in it. Lines are
+/// zero-based.
+struct Table<'a> {
+    name: &'a str,
+    array: bool,
+    line: usize,
+    end: usize,
+    keys: Vec<Key<'a>>,
+}
+
+struct Key<'a> {
+    name: &'a str,
+    value: &'a str,
+    line: usize,
+    end: usize,
+}
+
+/// Summarizes a `Cargo.toml` or `pyproject.toml`.
+pub fn extract_toml(source: &str) -> Extracted {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut symbols = Vec::new();
+    for table in toml_tables(&lines) {
+        let name = table
+            .keys
+            .iter()
+            .find(|key| key.name == "name")
+            .map(|key| toml_string(key.value));
+        match (table.name, table.array) {
+            ("package" | "project" | "tool.poetry", false) => {
+                if let Some(name) = name {
+                    let kind = if table.name == "package" {
+                        "package"
+                    } else {
+                        "project"
+                    };
+                    symbols.push(symbol(name, None, kind, table.line, table.end));
+                }
+            }
+            ("bin", true) => {
+                if let Some(name) = name {
+                    symbols.push(symbol(name, None, "bin", table.line, table.end));
+                }
+            }
+            ("features", false) => symbols.extend(
+                table
+                    .keys
+                    .iter()
+                    .map(|key| symbol(key.name, None, "feature", key.line, key.end)),
+            ),
+            ("workspace", false) => {
+                for key in table.keys.iter().filter(|key| key.name == "members") {
+                    for (i, &line) in lines.iter().enumerate().take(key.end + 1).skip(key.line) {
+                        let line = if i == key.line { key.value } else { line };
+                        symbols.extend(
+                            TOML_STRING
+                                .captures_iter(line)
+                                .filter_map(|caps| caps.name("text").or(caps.name("literal")))
+                                .map(|member| symbol(member.as_str(), None, "member", i, i)),
+                        );
+                    }
+                }
+            }
+            ("project.scripts" | "project.gui-scripts" | "tool.poetry.scripts", false) => symbols
+                .extend(
+                    table
+                        .keys
+                        .iter()
+                        .map(|key| symbol(key.name, None, "script", key.line, key.end)),
+                ),
+            // Plugins, listed under their group
+            (name, false) if name.starts_with("project.entry-points.") => {
+                let group = toml_string(&name["project.entry-points.".len()..]);
+                symbols.extend(
+                    table
+                        .keys
+                        .iter()
+                        .map(|key| symbol(key.name, Some(group), "entry_point", key.line, key.end)),
+                );
+            }
+            _ => {}
+        }
+    }
+    Extracted {
+        symbols,
+        ..Extracted::default()
+    }
+}
+
+/// Every table in `lines`, the keys before the first header included as
+/// the unnamed root table.
+fn toml_tables<'a>(lines: &[&'a str]) -> Vec<Table<'a>> {
+    let mut tables = vec![Table {
+        name: "",
+        array: false,
+        line: 0,
+        end: 0,
+        keys: Vec::new(),
+    }];
+    // Brackets a multi-line value like `members = [` leaves open
+    let mut open = 0usize;
+    for (i, &line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let table = tables.last_mut().expect("the root table is always there");
+        if open > 0 {
+            open = brackets(line, open);
+            table.end = i;
+            if let Some(key) = table.keys.last_mut() {
+                key.end = i;
+            }
+            continue;
+        }
+        if let Some(caps) = TOML_HEADER.captures(line) {
+            tables.push(Table {
+                name: caps.name("name").map_or("", |name| name.as_str()),
+                array: caps.name("array").is_some(),
+                line: i,
+                end: i,
+                keys: Vec::new(),
+            });
+            continue;
+        }
+        table.end = i;
+        if let Some(caps) = TOML_KEY.captures(line) {
+            let value = caps.name("value").map_or("", |value| value.as_str());
+            open = brackets(value, 0);
+            table.keys.push(Key {
+                name: toml_string(caps.name("key").map_or("", |key| key.as_str())),
+                value,
+                line: i,
+                end: i,
+            });
+        }
+    }
+    tables
+}
+
+/// How many of the brackets in `text` are still open, starting from `open`;
+/// brackets in strings and comments don't count.
+fn brackets(text: &str, mut open: usize) -> usize {
+    let mut quote = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => break,
+            (None, '[' | '{') => open += 1,
+            (None, ']' | '}') => open = open.saturating_sub(1),
+            _ => {}
+        }
+    }
+    open
+}
+
+/// A TOML string or key without its quotes.
+fn toml_string(text: &str) -> &str {
+    let text = text.trim();
+    match text.chars().next() {
+        Some(q @ ('"' | '\'')) => text[1..].split(q).next().unwrap_or_default(),
+        _ => text.split_whitespace().next().unwrap_or_default(),
+    }
+}
+
+/// One string in a JSON document, with the brackets it sits in.
+struct JsonString<'a> {
+    text: &'a str,
+    /// Whether it is an object key, followed by `:`.
+    key: bool,
+    depth: usize,
+    line: usize,
+}
+
+/// Summarizes a `package.json`.
+pub fn extract_json(source: &str) -> Extracted {
+    let strings = json_strings(source);
+    let mut symbols = Vec::new();
+    for (at, string) in strings.iter().enumerate() {
+        if !string.key || string.depth != 1 {
+            continue;
+        }
+        // The strings in this key's value
+        let value = strings[at + 1..]
+            .iter()
+            .take_while(|next| next.depth > 1 || !next.key);
+        match string.text {
+            "name" => {
+                if let Some(name) = strings
+                    .get(at + 1)
+                    .filter(|next| !next.key && next.depth == 1)
+                {
+                    symbols.push(symbol(name.text, None, "package", string.line, name.line));
+                }
+            }
+            "scripts" => symbols.extend(
+                value
+                    .filter(|next| next.key && next.depth == 2)
+                    .map(|script| symbol(script.text, None, "script", script.line, script.line)),
+            ),
+            // An array of globs, or Yarn's `{ "packages": [...] }`
+            "workspaces" => {
+                let mut in_packages = false;
+                for next in value {
+                    if next.key && next.depth == 2 {
+                        in_packages = next.text == "packages";
+                    } else if !next.key && (next.depth == 2 || in_packages && next.depth == 3) {
+                        symbols.push(symbol(next.text, None, "member", next.line, next.line));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Extracted {
+        symbols,
+        ..Extracted::default()
+    }
+}
+
+/// Every string in `source` in order. Escapes are left as written.
+fn json_strings(source: &str) -> Vec<JsonString<'_>> {
+    let mut strings: Vec<JsonString> = Vec::new();
+    let mut depth = 0usize;
+    let mut line = 0;
+    let mut chars = source.char_indices();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth = depth.saturating_sub(1),
+            ':' => {
+                if let Some(last) = strings.last_mut() {
+                    last.key = true;
+                }
+            }
+            '"' => {
+                let mut escaped = false;
+                let mut end = source.len();
+                for (next_at, next) in chars.by_ref() {
+                    match next {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => {
+                            end = next_at;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                strings.push(JsonString {
+                    text: &source[at + 1..end],
+                    key: false,
+                    depth,
+                    line,
+                });
+            }
+            _ => {}
+        }
+    }
+    strings
+}
+
+fn symbol(name: &str, parent: Option<&str>, kind: &'static str, line: usize, end: usize) -> Symbol {
+    Symbol {
+        name: SmolStr::new(name),
+        parent: parent.map(SmolStr::new),
+        line: line + 1,
+        kind: SmolStr::new_static(kind),
+        end_line: end + 1,
+        test: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(extracted: Extracted) -> Vec<(String, Option<String>, usize, usize)> {
+        extracted
+            .symbols
+            .into_iter()
+            .map(|sym| {
+                (
+                    format!("{} {}", sym.kind, sym.name),
+                    sym.parent.map(|p| p.to_string()),
+                    sym.line,
+                    sym.end_line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cargo_toml() {
+        let source = r#"[workspace]
+members = [
+    "crates/core", # the library
+    'crates/cli',
+]
+
+[package]
+name = "repomap"
+version = "0.2.0"
+
+[[bin]]
+name = "repomap"
+path = "src/main.rs"
+
+[features]
+default = ["sqlite"]
+"sqlite" = ["dep:rusqlite"]
+
+[dependencies]
+name = "not-this"
+"#;
+        assert_eq!(
+            summary(extract_toml(source)),
+            [
+                ("member crates/core".into(), None, 3, 3),
+                ("member crates/cli".into(), None, 4, 4),
+                ("package repomap".into(), None, 7, 9),
+                ("bin repomap".into(), None, 11, 13),
+                ("feature default".into(), None, 16, 16),
+                ("feature sqlite".into(), None, 17, 17),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pyproject_toml() {
+        let source = r#"[project]
+name = "acme-store"
+dependencies = [
+    "requests",
+]
+
+[project.scripts]
+acme = "acme.cli:main"
+
+[project.entry-points."pytest11"]
+acme = "acme.testing.plugin"
+"#;
+        assert_eq!(
+            summary(extract_toml(source)),
+            [
+                ("project acme-store".into(), None, 1, 5),
+                ("script acme".into(), None, 8, 8),
+                ("entry_point acme".into(), Some("pytest11".into()), 11, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_package_json() {
+        let source = r#"{
+  "name": "@acme/store",
+  "scripts": {
+    "build": "tsc -b",
+    "test": "vitest --config \"vitest.config.ts\""
+  },
+  "workspaces": {
+    "packages": ["packages/*", "apps/*"],
+    "nohoist": ["**/react"]
+  },
+  "dependencies": {
+    "name": "not-this"
+  }
+}
+"#;
+        assert_eq!(
+            summary(extract_json(source)),
+            [
+                ("package @acme/store".into(), None, 2, 2),
+                ("script build".into(), None, 4, 4),
+                ("script test".into(), None, 5, 5),
+                ("member packages/*".into(), None, 8, 8),
+                ("member apps/*".into(), None, 8, 8),
+            ]
+        );
+        assert_eq!(
+            summary(extract_json(r#"{"workspaces": ["lib"], "name": "x"}"#)),
+            [
+                ("member lib".into(), None, 1, 1),
+                ("package x".into(), None, 1, 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Markdown beyond the headings its query finds: the `title`,
+//! `description`, and `tags` of a page's YAML front matter, which say more
+//! about a blog post or docs page than its headings do, and the links a
+//! document makes to other files in the repo, like `[setup](./setup.md)`,
+//! which stand in for its imports so a docs directory can be drawn as a
+//! graph. Fenced code blocks can be extracted too, like the `<script>`s of
+//! [`crate::markup`], for docs whose examples are the best map of an API.
+
+use crate::languages::{self, Language};
+use crate::parser::{self, Symbol};
+use crate::queries::QueryRegistry;
+use crate::yaml::{self, Entry};
+use clap::ValueEnum;
+use regex::Regex;
+use smol_str::SmolStr;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// An inline link or image, its target bare or in `<...>`.
+static INLINE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?P<image>!?)\[(?:[^\[\]]|\[[^\]]*\])*\]\(\s*(?:<(?P<angle>[^>]+)>|(?P<bare>[^\s)]+))",
+    )
+    .expect("built-in pattern is valid")
+});
+
+/// A link reference definition, like `[setup]: ./setup.md`.
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^ {0,3}\[[^\]]+\]:\s*(?:<(?P<angle>[^>]+)>|(?P<bare>\S+))")
+        .expect("built-in pattern is valid")
+});
+
+/// A line opening a fenced code block, and the info string after the fence.
+static FENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^ {0,3}(?P<fence>`{3,}|~{3,})\s*(?P<info>[^`]*)$")
+        .expect("built-in pattern is valid")
+});
+
+static CODE_SPAN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`+[^`]*`+").expect("built-in pattern is valid"));
+
+/// Puts the `title`, `description`, and `tags` set in `source`'s front
+/// matter ahead of its headings, one `tag` per tag. Anything else found in
+/// the front matter's lines, like a `title: ...` over the closing `---` read
+/// as a heading, is dropped.
+pub fn add_front_matter(source: &str, symbols: &mut Vec<Symbol>) {
+    let lines: Vec<&str> = source.lines().collect();
+    let Some(close) = front_matter_end(&lines) else {
+        return;
+    };
+    symbols.retain(|sym| sym.line > close + 1);
+
+    let mut matter = Vec::new();
+    for entry in yaml::entries(&lines, 1, close - 1, 0) {
+        match entry.key {
+            "title" | "description" => {
+                let text = text(&lines, &entry);
+                if !text.is_empty() {
+                    matter.push(symbol(&text, entry.key, entry.line, entry.end));
+                }
+            }
+            "tags" => matter.extend(
+                tags(&lines, &entry)
+                    .into_iter()
+                    .map(|(tag, line)| symbol(tag, "tag", line, line)),
+            ),
+            _ => {}
+        }
+    }
+    symbols.splice(0..0, matter);
+}
+
+/// The line closing the front matter `lines` open with, if they do.
+fn front_matter_end(lines: &[&str]) -> Option<usize> {
+    if lines.first()?.trim_end() != "---" {
+        return None;
+    }
+    let close = lines[1..]
+        .iter()
+        .position(|line| matches!(line.trim_end(), "---" | "..."))?;
+    Some(close + 1)
+}
+
+/// An entry's value on one line, folded if it goes on past the key's line,
+/// like a `>` block.
+fn text(lines: &[&str], entry: &Entry) -> String {
+    let first = yaml::scalar(entry.value);
+    let first = if first.starts_with(['>', '|']) {
+        ""
+    } else {
+        first
+    };
+    let rest = lines[entry.line + 1..=entry.end]
+        .iter()
+        .map(|line| line.trim());
+    std::iter::once(first)
+        .chain(rest)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The tags an entry lists, inline like `[rust, cli]` or `rust, cli`, or as
+/// `- rust` items, with the line each is on.
+fn tags<'a>(lines: &[&'a str], entry: &Entry<'a>) -> Vec<(&'a str, usize)> {
+    let value = yaml::scalar(entry.value);
+    if !value.is_empty() {
+        let list = value
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .unwrap_or(value);
+        return list
+            .split(',')
+            .map(yaml::scalar)
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| (tag, entry.line))
+            .collect();
+    }
+    lines
+        .iter()
+        .enumerate()
+        .take(entry.end + 1)
+        .skip(entry.line + 1)
+        .filter_map(|(i, &line)| Some((yaml::scalar(line.trim_start().strip_prefix("- ")?), i)))
+        .filter(|(tag, _)| !tag.is_empty())
+        .collect()
+}
+
+fn symbol(name: &str, kind: &str, line: usize, end: usize) -> Symbol {
+    Symbol {
+        name: SmolStr::new(name),
+        parent: None,
+        line: line + 1,
+        kind: SmolStr::new(kind),
+        end_line: end + 1,
+        test: false,
+    }
+}
+
+/// Extracts the fenced code blocks of `source` whose info string names a
+/// language, like ```` ```rust ````, adding their symbols to `symbols` with
+/// lines counted from the top of the document. Their imports are left out:
+/// an example's `use` says nothing about what the document depends on.
+pub fn add_code_blocks(source: &str, symbols: &mut Vec<Symbol>, registry: &QueryRegistry) {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(caps) = FENCE.captures(lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let fence = caps.name("fence").map_or("", |fence| fence.as_str());
+        let info = caps.name("info").map_or("", |info| info.as_str());
+        // Up to the closing fence, or the end of the document if it has none
+        let close = lines[i + 1..]
+            .iter()
+            .position(|line| closes(line, fence))
+            .map_or(lines.len(), |at| i + 1 + at);
+        if let Some(lang) = fence_language(info) {
+            let body = lines[i + 1..close].join("\n");
+            let offset = i + 1;
+            symbols.extend(
+                parser::extract_with(&body, lang, registry)
+                    .symbols
+                    .into_iter()
+                    .map(|sym| Symbol {
+                        line: sym.line + offset,
+                        end_line: sym.end_line + offset,
+                        ..sym
+                    }),
+            );
+        }
+        i = close + 1;
+    }
+    symbols.sort_by_key(|sym| sym.line);
+}
+
+/// Whether `line` closes a block opened with `fence`: the same character,
+/// at least as many times, and nothing after it.
+fn closes(line: &str, fence: &str) -> bool {
+    let Some(c) = fence.chars().next() else {
+        return false;
+    };
+    let trimmed = line.trim();
+    line.len() - line.trim_start().len() <= 3
+        && trimmed.len() >= fence.len()
+        && trimmed.chars().all(|next| next == c)
+}
+
+/// The language an info string's first word names, by name or extension,
+/// like `python` or `py`. Markdown and notebooks aren't embedded code.
+fn fence_language(info: &str) -> Option<Language> {
+    let word = info
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '{'))
+        .next()?
+        .to_ascii_lowercase();
+    if word.is_empty() {
+        return None;
+    }
+    Language::from_str(&word, true)
+        .ok()
+        .or_else(|| languages::infer_language(Path::new(&format!("block.{word}"))))
+        .filter(|lang| !matches!(lang, Language::Markdown | Language::Jupyter))
+}
+
+/// The files `source` links to, as written but without a `#fragment`, in
+/// the order first linked. Images, URLs, and links within the document
+/// are left out, and so is anything in code.
+pub fn links(source: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    // The fence a line is inside of, like "```"
+    let mut fence: Option<&str> = None;
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            fence = Some(open);
+            continue;
+        }
+
+        let line = CODE_SPAN.replace_all(line, "");
+        let targets = INLINE_LINK
+            .captures_iter(&line)
+            .filter(|caps| caps.name("image").is_some_and(|m| m.is_empty()))
+            .chain(REFERENCE.captures(&line))
+            .filter_map(|caps| caps.name("angle").or(caps.name("bare")))
+            .filter_map(|target| local_path(target.as_str()));
+        for target in targets {
+            if !links.contains(&target) {
+                links.push(target);
+            }
+        }
+    }
+    links
+}
+
+/// The file a link target names, unless it points outside the repo, like a
+/// URL, or only to a heading in the same document.
+fn local_path(target: &str) -> Option<String> {
+    let path = target.split(['#', '?']).next().unwrap_or_default();
+    let scheme = path
+        .split_once(':')
+        .is_some_and(|(scheme, _)| !scheme.contains('/'));
+    if path.is_empty() || scheme || path.starts_with("//") {
+        return None;
+    }
+    Some(path.replace("%20", " "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_blocks() {
+        let source = r#"# Usage
+
+```rust
+pub struct Config;
+
+fn load() -> Config {
+    Config
+}
+```
+
+````py title="app.py"
+class App:
+    pass
+
+EXAMPLE = """
+```
+"""
+````
+
+~~~
+fn untagged() {}
+~~~
+
+```text
+fn not_code() {}
+```
+"#;
+        let mut symbols = Vec::new();
+        add_code_blocks(source, &mut symbols, &QueryRegistry::default());
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|sym| (format!("{} {}", sym.kind, sym.name), sym.line, sym.end_line))
+                .collect::<Vec<_>>(),
+            [
+                ("struct_item Config".into(), 4, 4),
+                ("function_item load".into(), 6, 8),
+                ("class_definition App".into(), 12, 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let source = r#"# Guide
+
+See [the API](./api.md#auth), [setup](setup.md "Setup") and
+[the changelog](</CHANGELOG.md>).
+
+![diagram](images/arch.png)
+[home](https://example.com), [top](#guide), and [mail](mailto:a@b.c).
+Links in `[code](not-this.md)` don't count.
+
+```md
+[fenced](not-this-either.md)
+```
+
+[ref]: ../docs/Design%20Notes.md
+[again](./api.md)
+"#;
+        assert_eq!(
+            links(source),
+            [
+                "./api.md",
+                "setup.md",
+                "/CHANGELOG.md",
+                "../docs/Design Notes.md"
+            ]
+        );
+    }
+}
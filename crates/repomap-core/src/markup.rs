@@ -0,0 +1,631 @@
+//! Markup with code embedded in it. An HTML page lists its element ids,
+//! `<template>`s, and custom elements, a Vue component its name, props, and
+//! emits, and a Svelte component its name and props. All have their
+//! `<script>` blocks extracted like any JavaScript or TypeScript file, their
+//! symbols keeping the file's line numbers.
+
+use crate::languages::Language;
+use crate::parser::{self, Extracted, Symbol};
+use crate::queries::QueryRegistry;
+use regex::Regex;
+use smol_str::SmolStr;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static SCRIPT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<script\b(?P<attrs>[^>]*)>(?P<body>.*?)</script\s*>")
+        .expect("built-in pattern is valid")
+});
+
+static COMMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").expect("built-in pattern is valid"));
+
+/// An opening tag; attribute values may hold `>`.
+static TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<(?P<tag>[a-zA-Z][\w-]*)(?P<attrs>(?:[^>"']|"[^"]*"|'[^']*')*)>"#)
+        .expect("built-in pattern is valid")
+});
+
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?:^|\s)(?P<name>[\w:@.-]+)(?:\s*=\s*(?:"(?P<double>[^"]*)"|'(?P<single>[^']*)'|(?P<bare>[^\s"'>]+)))?"#,
+    )
+    .expect("built-in pattern is valid")
+});
+
+/// `customElements.define("x-cart", Cart)`, naming a custom element.
+static DEFINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"customElements\.define\(\s*["'`](?P<name>[\w-]+)"#)
+        .expect("built-in pattern is valid")
+});
+
+/// Where a Vue component declares its props, emits, or name: the `[` or `{`
+/// opening the list or object.
+static DEFINE_PROPS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bdefineProps\s*[<(]\s*(?P<open>[\[{])").expect("built-in pattern is valid")
+});
+
+static DEFINE_EMITS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bdefineEmits\s*[<(]\s*(?P<open>[\[{])").expect("built-in pattern is valid")
+});
+
+static DEFINE_OPTIONS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:\bexport\s+default\s*(?:defineComponent\s*\(\s*)?|\bdefineOptions\s*\(\s*)(?P<open>\{)",
+    )
+    .expect("built-in pattern is valid")
+});
+
+/// What follows an option's key: its value, if a list, object, or string.
+static OPTION_VALUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*\??\s*:\s*(?:(?P<open>[\[{])|["'`](?P<string>[^"'`]*)["'`])"#)
+        .expect("built-in pattern is valid")
+});
+
+/// A Svelte prop, `export let count = 0;`.
+static EXPORT_LET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*export\s+let\s+(?P<name>[\w$]+)").expect("built-in pattern is valid")
+});
+
+/// Svelte 5's props, `let { count = 0, label } = $props();`.
+static PROPS_RUNE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\blet\s*\{(?P<props>[^}]*)\}\s*(?::[^=]*)?=\s*\$props\s*\(")
+        .expect("built-in pattern is valid")
+});
+
+/// One name in a destructuring pattern, not a `...rest`.
+static DESTRUCTURED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|,)\s*(?P<name>[A-Za-z_$][\w$]*)").expect("built-in pattern is valid")
+});
+
+/// Summarizes an HTML page and extracts its inline scripts.
+pub fn extract_html(source: &str, registry: &QueryRegistry) -> Extracted {
+    let lines = LineIndex::new(source);
+    let mut extracted = Extracted::default();
+    // Where tags are not markup
+    let mut hidden: Vec<Range<usize>> = COMMENT.find_iter(source).map(|m| m.range()).collect();
+    // Listed once, where defined if the page defines it
+    let mut custom_elements: Vec<&str> = Vec::new();
+
+    for caps in SCRIPT.captures_iter(source) {
+        let (attrs, body) = (
+            &caps["attrs"],
+            caps.name("body").expect("group always matches"),
+        );
+        hidden.push(body.range());
+        if let Some(src) = attribute(attrs, "src") {
+            push_import(&mut extracted.imports, src);
+            continue;
+        }
+        let Some(lang) = script_language(attrs) else {
+            continue;
+        };
+        let offset = lines.line(body.start());
+        embed(&mut extracted, body.as_str(), offset, lang, registry);
+        for define in DEFINE.captures_iter(body.as_str()) {
+            let name = define.name("name").expect("group always matches");
+            let line = lines.line(body.start() + name.start());
+            custom_elements.push(name.as_str());
+            extracted
+                .symbols
+                .push(symbol(name.as_str(), "custom_element", line, line));
+        }
+    }
+
+    for caps in TAG.captures_iter(source) {
+        let tag = caps.name("tag").expect("group always matches");
+        if hidden.iter().any(|range| range.contains(&tag.start())) {
+            continue;
+        }
+        let line = lines.line(tag.start());
+        let id = attribute(&caps["attrs"], "id");
+        if tag.as_str().eq_ignore_ascii_case("template") {
+            let end = source[tag.end()..]
+                .find("</template>")
+                .map_or(line, |close| lines.line(tag.end() + close));
+            extracted
+                .symbols
+                .push(symbol(id.unwrap_or("template"), "template", line, end));
+        } else if let Some(id) = id {
+            extracted
+                .symbols
+                .push(symbol(&format!("#{id}"), "element", line, line));
+        }
+        if tag.as_str().contains('-') && !custom_elements.contains(&tag.as_str()) {
+            custom_elements.push(tag.as_str());
+            extracted
+                .symbols
+                .push(symbol(tag.as_str(), "custom_element", line, line));
+        }
+    }
+    extracted.symbols.sort_by_key(|sym| sym.line);
+    extracted
+}
+
+/// Extracts a Vue component's `<script>` and `<script setup>` blocks and
+/// lists the props and emits they declare, and its name if an option sets
+/// it; [`name_component`] names it after its file otherwise.
+pub fn extract_vue(source: &str, registry: &QueryRegistry) -> Extracted {
+    let lines = LineIndex::new(source);
+    let mut extracted = Extracted::default();
+    for caps in SCRIPT.captures_iter(source) {
+        let body = caps.name("body").expect("group always matches");
+        let Some(lang) = script_language(&caps["attrs"]) else {
+            continue;
+        };
+        embed(
+            &mut extracted,
+            body.as_str(),
+            lines.line(body.start()),
+            lang,
+            registry,
+        );
+
+        // Offsets into the script, and what they declare
+        let mut declared: Vec<(usize, &str, &'static str)> = Vec::new();
+        let script = body.as_str();
+        for (pattern, kind) in [(&DEFINE_PROPS, "prop"), (&DEFINE_EMITS, "emit")] {
+            for open in pattern
+                .captures_iter(script)
+                .filter_map(|caps| caps.name("open"))
+            {
+                declared.extend(
+                    members(&script[open.start()..])
+                        .into_iter()
+                        .map(|(name, at, _)| (open.start() + at, name, kind)),
+                );
+            }
+        }
+        for open in DEFINE_OPTIONS
+            .captures_iter(script)
+            .filter_map(|caps| caps.name("open"))
+        {
+            let options = &script[open.start()..];
+            for (key, at, end) in members(options) {
+                let Some(value) = OPTION_VALUE.captures(&options[end..]) else {
+                    continue;
+                };
+                match (key, value.name("open"), value.name("string")) {
+                    ("props" | "emits", Some(list), _) => {
+                        let kind = if key == "props" { "prop" } else { "emit" };
+                        let list_at = open.start() + end + list.start();
+                        declared.extend(
+                            members(&script[list_at..])
+                                .into_iter()
+                                .map(|(name, at, _)| (list_at + at, name, kind)),
+                        );
+                    }
+                    ("name", _, Some(name)) => {
+                        declared.push((open.start() + at, name.as_str(), "component"))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for (at, name, kind) in declared {
+            let line = lines.line(body.start() + at);
+            let end = if kind == "component" {
+                lines.line(source.trim_end().len())
+            } else {
+                line
+            };
+            extracted.symbols.push(symbol(name, kind, line, end));
+        }
+    }
+    extracted.symbols.sort_by_key(|sym| sym.line);
+    extracted
+}
+
+/// Extracts a Svelte component's `<script>` blocks and lists the props they
+/// declare; [`name_component`] names it after its file.
+pub fn extract_svelte(source: &str, registry: &QueryRegistry) -> Extracted {
+    let lines = LineIndex::new(source);
+    let mut extracted = Extracted::default();
+    for caps in SCRIPT.captures_iter(source) {
+        let body = caps.name("body").expect("group always matches");
+        let Some(lang) = script_language(&caps["attrs"]) else {
+            continue;
+        };
+        embed(
+            &mut extracted,
+            body.as_str(),
+            lines.line(body.start()),
+            lang,
+            registry,
+        );
+
+        let script = body.as_str();
+        let mut props: Vec<(usize, &str)> = EXPORT_LET
+            .captures_iter(script)
+            .filter_map(|caps| caps.name("name"))
+            .map(|name| (name.start(), name.as_str()))
+            .collect();
+        for group in PROPS_RUNE
+            .captures_iter(script)
+            .filter_map(|caps| caps.name("props"))
+        {
+            props.extend(
+                DESTRUCTURED
+                    .captures_iter(group.as_str())
+                    .filter_map(|caps| caps.name("name"))
+                    .map(|name| (group.start() + name.start(), name.as_str())),
+            );
+        }
+        for (at, name) in props {
+            let line = lines.line(body.start() + at);
+            extracted.symbols.push(symbol(name, "prop", line, line));
+        }
+    }
+    extracted.symbols.sort_by_key(|sym| sym.line);
+    extracted
+}
+
+/// Adds a Vue or Svelte component named after its file, like `CartBadge`
+/// for `CartBadge.vue`, spanning the whole file, unless it already has one.
+pub(crate) fn name_component(path: &str, lang: Language, lines: usize, symbols: &mut Vec<Symbol>) {
+    if !matches!(lang, Language::Vue | Language::Svelte)
+        || symbols.iter().any(|sym| sym.kind == "component")
+    {
+        return;
+    }
+    let Some(stem) = Path::new(path).file_stem().and_then(|stem| stem.to_str()) else {
+        return;
+    };
+    symbols.insert(0, symbol(stem, "component", 0, lines.saturating_sub(1)));
+}
+
+/// The names a `[...]` or `{...}` at the start of `text` lists at its top
+/// level, with where each starts and ends: the strings of an array, the keys
+/// of an object or type literal, and the event of a call signature like
+/// `(e: "change", id: number): void`.
+fn members(text: &str) -> Vec<(&str, usize, usize)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let array = text.starts_with('[');
+    let mut members = Vec::new();
+    let mut depth = 0usize;
+    // Inside a call signature whose event is still to come
+    let mut signature = false;
+    let mut chars = text.char_indices().peekable();
+    while let Some((at, c)) = chars.next() {
+        let (name, end, quoted) = match c {
+            '"' | '\'' | '`' => {
+                let mut escaped = false;
+                let mut close = text.len();
+                for (next_at, next) in chars.by_ref() {
+                    match next {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        _ if next == c => {
+                            close = next_at;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                (&text[at + 1..close], (close + 1).min(text.len()), true)
+            }
+            '[' | '{' | '(' => {
+                depth += 1;
+                signature = c == '(' && depth == 2 && !array;
+                continue;
+            }
+            ']' | '}' | ')' => {
+                depth = depth.saturating_sub(1);
+                signature &= depth >= 2;
+                if depth == 0 {
+                    break;
+                }
+                continue;
+            }
+            _ if is_word(c) => {
+                let mut end = at + c.len_utf8();
+                while let Some(&(next_at, next)) = chars.peek().filter(|&&(_, next)| is_word(next))
+                {
+                    end = next_at + next.len_utf8();
+                    chars.next();
+                }
+                (&text[at..end], end, false)
+            }
+            _ => continue,
+        };
+        let keyed = || {
+            text[end..]
+                .trim_start()
+                .trim_start_matches('?')
+                .trim_start()
+                .starts_with(':')
+        };
+        let listed = match depth {
+            1 if array => quoted,
+            1 => keyed(),
+            2 if signature && quoted => {
+                signature = false;
+                true
+            }
+            _ => false,
+        };
+        if listed && !name.is_empty() {
+            members.push((name, at, end));
+        }
+    }
+    members
+}
+
+/// Extracts `body`, which starts `offset` lines into the file, as `lang`,
+/// and adds what it finds to `into` with lines counted from the top of the
+/// file.
+pub(crate) fn embed(
+    into: &mut Extracted,
+    body: &str,
+    offset: usize,
+    lang: Language,
+    registry: &QueryRegistry,
+) {
+    let embedded = parser::extract_with(body, lang, registry);
+    into.symbols
+        .extend(embedded.symbols.into_iter().map(|sym| Symbol {
+            line: sym.line + offset,
+            end_line: sym.end_line + offset,
+            ..sym
+        }));
+    for import in embedded.imports {
+        push_import(&mut into.imports, &import);
+    }
+    into.references.extend(embedded.references);
+    into.references.sort_unstable();
+    into.references.dedup();
+}
+
+fn push_import(imports: &mut Vec<String>, import: &str) {
+    if !import.is_empty() && !imports.iter().any(|seen| seen == import) {
+        imports.push(import.to_string());
+    }
+}
+
+/// What a `<script>` with these attributes holds, if it's code: TypeScript
+/// with `lang="ts"`, otherwise JavaScript unless its `type` says it's data
+/// or a template.
+pub(crate) fn script_language(attrs: &str) -> Option<Language> {
+    match attribute(attrs, "lang")
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("ts" | "typescript") => return Some(Language::Typescript),
+        Some("tsx") => return Some(Language::Tsx),
+        _ => {}
+    }
+    match attribute(attrs, "type")
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        None | Some("module" | "text/javascript" | "application/javascript" | "text/babel") => {
+            Some(Language::Javascript)
+        }
+        Some(_) => None,
+    }
+}
+
+/// The value of the attribute `name` in a tag's attributes, or `""` if it
+/// has none, like `setup` in `<script setup>`.
+pub(crate) fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    ATTRIBUTE
+        .captures_iter(attrs)
+        .find(|caps| caps["name"].eq_ignore_ascii_case(name))
+        .map(|caps| {
+            ["double", "single", "bare"]
+                .iter()
+                .find_map(|group| caps.name(group))
+                .map_or("", |value| value.as_str())
+        })
+}
+
+pub(crate) fn symbol(name: &str, kind: &'static str, line: usize, end: usize) -> Symbol {
+    Symbol {
+        name: SmolStr::new(name),
+        parent: None,
+        line: line + 1,
+        kind: SmolStr::new_static(kind),
+        end_line: end + 1,
+        test: false,
+    }
+}
+
+/// Zero-based line numbers of byte offsets.
+pub(crate) struct LineIndex {
+    newlines: Vec<usize>,
+}
+
+impl LineIndex {
+    pub(crate) fn new(source: &str) -> Self {
+        Self {
+            newlines: source.match_indices('\n').map(|(at, _)| at).collect(),
+        }
+    }
+
+    pub(crate) fn line(&self, at: usize) -> usize {
+        self.newlines.partition_point(|&newline| newline < at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(extracted: &Extracted) -> Vec<(String, usize, usize)> {
+        extracted
+            .symbols
+            .iter()
+            .map(|sym| (format!("{} {}", sym.kind, sym.name), sym.line, sym.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_html() {
+        let source = r#"<!doctype html>
+<html>
+<head>
+  <script src="/js/vendor.js"></script>
+  <script type="application/ld+json">{"id": "not-this"}</script>
+</head>
+<body>
+  <!-- <div id="commented-out"></div> -->
+  <main id="app">
+    <cart-badge count="3"></cart-badge>
+    <cart-badge></cart-badge>
+  </main>
+  <template id="row">
+    <tr><td></td></tr>
+  </template>
+  <script type="module">
+    import { format } from "./format.js";
+
+    class CartBadge extends HTMLElement {
+      connectedCallback() {
+        this.textContent = format(this.getAttribute("count"));
+      }
+    }
+    customElements.define("cart-badge", CartBadge);
+  </script>
+</body>
+</html>
+"#;
+        let extracted = extract_html(source, &QueryRegistry::default());
+        assert_eq!(
+            summary(&extracted),
+            [
+                ("element #app".into(), 9, 9),
+                ("template row".into(), 13, 15),
+                ("class_declaration CartBadge".into(), 19, 23),
+                ("method_definition connectedCallback".into(), 20, 22),
+                ("custom_element cart-badge".into(), 24, 24),
+            ]
+        );
+        assert_eq!(extracted.imports, ["/js/vendor.js", "./format.js"]);
+        assert!(extracted.references.contains(&SmolStr::new("HTMLElement")));
+    }
+
+    #[test]
+    fn test_vue() {
+        let source = r#"<template>
+  <button @click="emit('change', 1)">{{ label }}</button>
+</template>
+
+<script setup lang="ts">
+import { computed } from "vue";
+import Badge from "./Badge.vue";
+
+const props = withDefaults(
+  defineProps<{ count: number; label?: string; onClick: () => void }>(),
+  { label: "Add" },
+);
+const emit = defineEmits<{
+  (e: "change", id: number): void;
+  "update:modelValue": [value: string];
+}>();
+
+function increment(by: number) {
+  emit("change", props.count + by);
+}
+</script>
+"#;
+        let mut extracted = extract_vue(source, &QueryRegistry::default());
+        name_component(
+            "src/components/CartButton.vue",
+            Language::Vue,
+            21,
+            &mut extracted.symbols,
+        );
+        assert_eq!(
+            summary(&extracted),
+            [
+                ("component CartButton".into(), 1, 21),
+                ("prop count".into(), 10, 10),
+                ("prop label".into(), 10, 10),
+                ("prop onClick".into(), 10, 10),
+                ("emit change".into(), 14, 14),
+                ("emit update:modelValue".into(), 15, 15),
+                ("function_declaration increment".into(), 18, 20),
+            ]
+        );
+        assert_eq!(extracted.imports, ["vue", "./Badge.vue"]);
+
+        let options = r#"<script>
+export default {
+  name: "LegacyCart",
+  props: ["items", "total"],
+  emits: ["checkout"],
+  data() {
+    return { name: "not-this" };
+  },
+};
+</script>
+"#;
+        let mut extracted = extract_vue(options, &QueryRegistry::default());
+        name_component("Cart.vue", Language::Vue, 10, &mut extracted.symbols);
+        assert_eq!(
+            summary(&extracted),
+            [
+                ("component LegacyCart".into(), 3, 10),
+                ("prop items".into(), 4, 4),
+                ("prop total".into(), 4, 4),
+                ("emit checkout".into(), 5, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_svelte() {
+        let source = r#"<script context="module" lang="ts">
+  export function preload() {}
+</script>
+
+<script lang="ts">
+  import Badge from "./Badge.svelte";
+
+  export let count = 0;
+  export let label: string;
+  let { size = "md", variant, ...rest } = $props();
+
+  function increment() {
+    count += 1;
+  }
+</script>
+
+<button on:click={increment}>{label}: {count}</button>
+"#;
+        let mut extracted = extract_svelte(source, &QueryRegistry::default());
+        name_component(
+            "src/lib/Counter.svelte",
+            Language::Svelte,
+            17,
+            &mut extracted.symbols,
+        );
+        assert_eq!(
+            summary(&extracted),
+            [
+                ("component Counter".into(), 1, 17),
+                ("function_declaration preload".into(), 2, 2),
+                ("prop count".into(), 8, 8),
+                ("prop label".into(), 9, 9),
+                ("prop size".into(), 10, 10),
+                ("prop variant".into(), 10, 10),
+                ("function_declaration increment".into(), 12, 14),
+            ]
+        );
+        assert_eq!(extracted.imports, ["./Badge.svelte"]);
+    }
+
+    #[test]
+    fn test_script_attributes() {
+        assert_eq!(script_language(""), Some(Language::Javascript));
+        assert_eq!(
+            script_language(r#" lang="ts" setup"#),
+            Some(Language::Typescript)
+        );
+        assert_eq!(script_language(r#" type="text/x-template""#), None);
+        assert_eq!(attribute(r#" lang="ts" setup"#, "setup"), Some(""));
+        assert_eq!(attribute(" id=main", "id"), Some("main"));
+        assert_eq!(attribute(r#" data-id="x""#, "id"), None);
+    }
+}
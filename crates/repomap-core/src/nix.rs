@@ -0,0 +1,607 @@
+//! Summaries of Nix files, which are usually one function returning one
+//! attribute set: the function's arguments, like a package's
+//! `{ lib, stdenv }:`, the attributes of the set, and for a flake, its
+//! inputs and outputs. There is no grammar bundled, so the file is
+//! tokenized and its brackets matched by hand.
+
+use crate::parser::{Extracted, Symbol};
+use smol_str::SmolStr;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Tok<'a> {
+    /// An identifier or attribute path, like `inputs.nixpkgs.url`.
+    Name(&'a str),
+    /// A path literal, like `./hardware.nix`.
+    Path(&'a str),
+    String,
+    Punct(char),
+}
+
+struct Token<'a> {
+    tok: Tok<'a>,
+    /// Zero-based.
+    line: usize,
+}
+
+/// One `path = value;` or `inherit` of an attribute set.
+struct Binding<'a> {
+    path: &'a str,
+    kind: &'static str,
+    line: usize,
+    end: usize,
+    /// The token its value starts at.
+    value: usize,
+}
+
+/// Summarizes a Nix file.
+pub fn extract(source: &str) -> Extracted {
+    let tokens = tokens(source);
+    let mut symbols = Vec::new();
+
+    // Curried, like `{ pkgs }: { config, ... }:`
+    let mut body = 0;
+    let mut formals = Vec::new();
+    loop {
+        let (names, next) = lambda(&tokens, body);
+        if next == body {
+            break;
+        }
+        formals.extend(names);
+        body = next;
+    }
+    symbols.extend(
+        formals
+            .iter()
+            .map(|&(name, line)| symbol(name, None, "formal", line, line)),
+    );
+
+    let top = attribute_set(&tokens, body)
+        .map(|open| bindings(&tokens, open))
+        .unwrap_or_default();
+    let flake = formals.is_empty() && top.iter().any(|b| b.path == "outputs");
+    for binding in &top {
+        if flake && let Some(input) = binding.path.strip_prefix("inputs.") {
+            let name = input.split('.').next().unwrap_or(input);
+            member(
+                &mut symbols,
+                name,
+                "inputs",
+                "input",
+                binding.line,
+                binding.end,
+            );
+            continue;
+        }
+        symbols.push(symbol(
+            binding.path,
+            None,
+            binding.kind,
+            binding.line,
+            binding.end,
+        ));
+        let (parent, kind, set) = match binding.path {
+            "inputs" if flake => ("inputs", "input", Some(binding.value)),
+            "outputs" if flake => {
+                let (_, body) = lambda(&tokens, binding.value);
+                ("outputs", "output", attribute_set(&tokens, body))
+            }
+            _ => continue,
+        };
+        let Some(open) =
+            set.filter(|&open| tokens.get(open).is_some_and(|t| t.tok == Tok::Punct('{')))
+        else {
+            continue;
+        };
+        for nested in bindings(&tokens, open) {
+            let name = nested.path.split('.').next().unwrap_or(nested.path);
+            member(&mut symbols, name, parent, kind, nested.line, nested.end);
+        }
+    }
+
+    let mut references: Vec<SmolStr> = tokens
+        .iter()
+        .filter_map(|token| match token.tok {
+            Tok::Name(path) => Some(path.split('.')),
+            _ => None,
+        })
+        .flatten()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(SmolStr::new)
+        .collect();
+    references.sort_unstable();
+    Extracted {
+        symbols,
+        imports: imports(&tokens),
+        references,
+    }
+}
+
+/// An input or output of a flake, which may be set over several bindings,
+/// like `inputs.nixpkgs.url` and `inputs.nixpkgs.flake`.
+fn member(
+    symbols: &mut Vec<Symbol>,
+    name: &str,
+    parent: &str,
+    kind: &'static str,
+    line: usize,
+    end: usize,
+) {
+    match symbols.last_mut() {
+        Some(last) if last.kind == kind && last.name == name => last.end_line = end + 1,
+        _ => symbols.push(symbol(name, Some(parent), kind, line, end)),
+    }
+}
+
+fn symbol(name: &str, parent: Option<&str>, kind: &'static str, line: usize, end: usize) -> Symbol {
+    Symbol {
+        name: SmolStr::new(name),
+        parent: parent.map(SmolStr::new),
+        line: line + 1,
+        kind: SmolStr::new_static(kind),
+        end_line: end + 1,
+        test: false,
+    }
+}
+
+/// The arguments of the function starting at `at`, if one does, and where
+/// its body starts.
+fn lambda<'a>(tokens: &[Token<'a>], at: usize) -> (Vec<(&'a str, usize)>, usize) {
+    let tok = |i: usize| tokens.get(i).map(|token| token.tok);
+    match tok(at) {
+        // `pkgs: ...`
+        Some(Tok::Name(name)) if tok(at + 1) == Some(Tok::Punct(':')) && !name.contains('.') => {
+            (vec![(name, tokens[at].line)], at + 2)
+        }
+        // `args@{ ... }: ...`
+        Some(Tok::Name(_))
+            if tok(at + 1) == Some(Tok::Punct('@')) && tok(at + 2) == Some(Tok::Punct('{')) =>
+        {
+            let close = matching(tokens, at + 2);
+            if tok(close + 1) == Some(Tok::Punct(':')) {
+                (formals(tokens, at + 2, close), close + 2)
+            } else {
+                (Vec::new(), at)
+            }
+        }
+        // `{ ... }: ...` or `{ ... }@args: ...`
+        Some(Tok::Punct('{')) => {
+            let close = matching(tokens, at);
+            match (tok(close + 1), tok(close + 2), tok(close + 3)) {
+                (Some(Tok::Punct(':')), ..) => (formals(tokens, at, close), close + 2),
+                (Some(Tok::Punct('@')), Some(Tok::Name(_)), Some(Tok::Punct(':'))) => {
+                    (formals(tokens, at, close), close + 4)
+                }
+                _ => (Vec::new(), at),
+            }
+        }
+        _ => (Vec::new(), at),
+    }
+}
+
+/// The names in a set of arguments like `{ lib, withGui ? false, ... }`.
+fn formals<'a>(tokens: &[Token<'a>], open: usize, close: usize) -> Vec<(&'a str, usize)> {
+    let mut formals = Vec::new();
+    let mut depth = 0usize;
+    let mut expect_name = true;
+    for token in &tokens[open + 1..close] {
+        match token.tok {
+            Tok::Punct('{' | '(' | '[') => depth += 1,
+            Tok::Punct('}' | ')' | ']') => depth = depth.saturating_sub(1),
+            Tok::Punct(',') if depth == 0 => expect_name = true,
+            Tok::Name(name) if depth == 0 && expect_name => {
+                formals.push((name, token.line));
+                expect_name = false;
+            }
+            _ => {}
+        }
+    }
+    formals
+}
+
+/// The `{` of the attribute set the expression at `at` evaluates to, past
+/// any `let ... in`, `with`, and the function it's passed to, like
+/// `stdenv.mkDerivation rec {`.
+fn attribute_set(tokens: &[Token], mut at: usize) -> Option<usize> {
+    loop {
+        match tokens.get(at)?.tok {
+            Tok::Name("let") => at = let_body(tokens, at)?,
+            Tok::Name("with" | "assert") => at = statement_end(tokens, at, tokens.len()) + 1,
+            Tok::Punct('{') => {
+                let (_, body) = lambda(tokens, at);
+                if body == at {
+                    return Some(at);
+                }
+                at = body;
+            }
+            _ => at += 1,
+        }
+    }
+}
+
+/// The token after the `in` of the `let` at `at`.
+fn let_body(tokens: &[Token], at: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(at) {
+        match token.tok {
+            Tok::Name("let") => depth += 1,
+            Tok::Name("in") => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The bindings of the attribute set opened at `open`.
+fn bindings<'a>(tokens: &[Token<'a>], open: usize) -> Vec<Binding<'a>> {
+    let close = matching(tokens, open);
+    let mut bindings = Vec::new();
+    let mut at = open + 1;
+    while at < close {
+        let end = statement_end(tokens, at, close);
+        match tokens[at].tok {
+            // `inherit (pkgs) hello;` too
+            Tok::Name("inherit") => {
+                let mut depth = 0usize;
+                for token in &tokens[at + 1..end] {
+                    match token.tok {
+                        Tok::Punct('(') => depth += 1,
+                        Tok::Punct(')') => depth = depth.saturating_sub(1),
+                        Tok::Name(name) if depth == 0 => bindings.push(Binding {
+                            path: name,
+                            kind: "inherit",
+                            line: token.line,
+                            end: token.line,
+                            value: at,
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+            Tok::Name(path) if tokens[at + 1].tok == Tok::Punct('=') => bindings.push(Binding {
+                path,
+                kind: "binding",
+                line: tokens[at].line,
+                end: tokens[end].line,
+                value: at + 2,
+            }),
+            _ => {}
+        }
+        at = end + 1;
+    }
+    bindings
+}
+
+/// The `;` ending the statement at `at`, or `limit` if none does first.
+/// The `;`s of a `let ... in` or a `with ...;` or `assert ...;` in its value
+/// don't end it.
+fn statement_end(tokens: &[Token], at: usize, limit: usize) -> usize {
+    let mut depth = 0usize;
+    // Open `let`s, and `with`s and `assert`s whose `;` is still to come
+    let mut lets = 0usize;
+    let mut prefixes = 0usize;
+    for (i, token) in tokens.iter().enumerate().take(limit).skip(at) {
+        match token.tok {
+            Tok::Punct('{' | '(' | '[') => depth += 1,
+            Tok::Punct('}' | ')' | ']') => depth = depth.saturating_sub(1),
+            Tok::Name("let") if depth == 0 => lets += 1,
+            Tok::Name("in") if depth == 0 => lets = lets.saturating_sub(1),
+            // The statement at `at` may itself be one, as in `attribute_set`
+            Tok::Name("with" | "assert") if depth == 0 && lets == 0 && i > at => prefixes += 1,
+            Tok::Punct(';') if depth == 0 && lets == 0 && prefixes > 0 => prefixes -= 1,
+            Tok::Punct(';') if depth == 0 && lets == 0 => return i,
+            _ => {}
+        }
+    }
+    limit.min(tokens.len() - 1)
+}
+
+/// The bracket closing the one at `open`, or the last token.
+fn matching(tokens: &[Token], open: usize) -> usize {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token.tok {
+            Tok::Punct('{' | '(' | '[') => depth += 1,
+            Tok::Punct('}' | ')' | ']') => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    tokens.len() - 1
+}
+
+/// The paths passed to `import` or `callPackage`, or listed in a module's
+/// `imports = [ ... ]`.
+fn imports(tokens: &[Token]) -> Vec<String> {
+    let mut imports: Vec<String> = Vec::new();
+    let mut push = |path: &str| {
+        if !imports.iter().any(|import| import == path) {
+            imports.push(path.to_string());
+        }
+    };
+    let tok = |i: usize| tokens.get(i).map(|token| token.tok);
+    for (i, token) in tokens.iter().enumerate() {
+        match token.tok {
+            Tok::Path(path) if i > 0 && imports_path(tokens[i - 1].tok) => push(path),
+            Tok::Name("imports")
+                if tok(i + 1) == Some(Tok::Punct('=')) && tok(i + 2) == Some(Tok::Punct('[')) =>
+            {
+                let close = matching(tokens, i + 2);
+                for token in &tokens[i + 3..close] {
+                    if let Tok::Path(path) = token.tok {
+                        push(path);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    imports
+}
+
+/// Whether `tok` is a function that imports the path passed to it, like
+/// `import` or `pkgs.callPackage`.
+fn imports_path(tok: Tok) -> bool {
+    match tok {
+        Tok::Name(function) => matches!(
+            function.rsplit('.').next(),
+            Some("import" | "callPackage" | "callPackages")
+        ),
+        _ => false,
+    }
+}
+
+fn tokens(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut line = 0;
+    let mut at = 0;
+    while let Some(c) = source[at..].chars().next() {
+        let rest = &source[at..];
+        let next = rest[c.len_utf8()..].chars().next();
+        let len = match c {
+            '#' => rest.find('\n').unwrap_or(rest.len()),
+            '/' if next == Some('*') => rest[2..].find("*/").map_or(rest.len(), |end| end + 4),
+            '"' => {
+                tokens.push(Token {
+                    tok: Tok::String,
+                    line,
+                });
+                string_len(rest)
+            }
+            '\'' if next == Some('\'') => {
+                tokens.push(Token {
+                    tok: Tok::String,
+                    line,
+                });
+                string_len(rest)
+            }
+            '.' | '~' | '/' if is_path_start(rest) => {
+                let len = rest
+                    .find(|c: char| !c.is_alphanumeric() && !"._-+/~".contains(c))
+                    .unwrap_or(rest.len());
+                tokens.push(Token {
+                    tok: Tok::Path(&rest[..len]),
+                    line,
+                });
+                len
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let len = name_len(rest);
+                tokens.push(Token {
+                    tok: Tok::Name(&rest[..len]),
+                    line,
+                });
+                len
+            }
+            // Comparisons, not bindings
+            '=' | '!' | '<' | '>' if next == Some('=') => 2,
+            '{' | '}' | '(' | ')' | '[' | ']' | '=' | ';' | ':' | ',' | '@' | '?' => {
+                tokens.push(Token {
+                    tok: Tok::Punct(c),
+                    line,
+                });
+                1
+            }
+            _ => c.len_utf8(),
+        };
+        line += rest[..len].matches('\n').count();
+        at += len;
+    }
+    tokens
+}
+
+fn is_path_start(rest: &str) -> bool {
+    rest.starts_with("./")
+        || rest.starts_with("../")
+        || rest.starts_with("~/")
+        || rest.starts_with('/') && rest[1..].starts_with(|c: char| c.is_alphanumeric())
+}
+
+/// The length of the identifier or attribute path at the start of `rest`.
+fn name_len(rest: &str) -> usize {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '\'' | '-');
+    let mut len = 0;
+    loop {
+        len += rest[len..]
+            .find(|c: char| !is_name(c))
+            .unwrap_or(rest.len() - len);
+        let after = &rest[len..];
+        if after.starts_with('.')
+            && after[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        {
+            len += 1;
+        } else {
+            return len;
+        }
+    }
+}
+
+/// The length of the string at the start of `rest`, `"..."` or `''...''`,
+/// interpolations included.
+fn string_len(rest: &str) -> usize {
+    let indented = rest.starts_with("''");
+    let mut interpolation = 0usize;
+    let mut chars = rest.char_indices().skip(if indented { 2 } else { 1 });
+    while let Some((at, c)) = chars.next() {
+        let next = rest[at + c.len_utf8()..].chars().next();
+        match c {
+            '\\' if !indented => {
+                chars.next();
+            }
+            '$' if next == Some('{') => {
+                interpolation += 1;
+                chars.next();
+            }
+            '}' if interpolation > 0 => interpolation -= 1,
+            '"' if !indented && interpolation == 0 => return at + 1,
+            '\'' if indented && interpolation == 0 && next == Some('\'') => {
+                // `'''`, `''$`, and `''\` are escapes
+                match rest[at + 2..].chars().next() {
+                    Some('\'' | '$' | '\\') => {
+                        chars.next();
+                        chars.next();
+                    }
+                    _ => return at + 2,
+                }
+            }
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(extracted: &Extracted) -> Vec<(String, Option<String>, usize, usize)> {
+        extracted
+            .symbols
+            .iter()
+            .map(|sym| {
+                (
+                    format!("{} {}", sym.kind, sym.name),
+                    sym.parent.as_ref().map(|p| p.to_string()),
+                    sym.line,
+                    sym.end_line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_package() {
+        let source = r#"{ lib
+, stdenv
+, fetchFromGitHub
+, withGui ? false
+, ...
+}@args:
+
+let
+  version = "2.12";
+in
+stdenv.mkDerivation rec {
+  pname = "hello";
+  inherit version;
+  src = fetchFromGitHub {
+    owner = "acme";
+    repo = "hello";
+  };
+  meta.description = "Says ${"hello"}; politely";
+  passthru.tests = import ./tests.nix { inherit lib; };
+}
+"#;
+        let extracted = extract(source);
+        assert_eq!(
+            summary(&extracted),
+            [
+                ("formal lib".into(), None, 1, 1),
+                ("formal stdenv".into(), None, 2, 2),
+                ("formal fetchFromGitHub".into(), None, 3, 3),
+                ("formal withGui".into(), None, 4, 4),
+                ("binding pname".into(), None, 12, 12),
+                ("inherit version".into(), None, 13, 13),
+                ("binding src".into(), None, 14, 17),
+                ("binding meta.description".into(), None, 18, 18),
+                ("binding passthru.tests".into(), None, 19, 19),
+            ]
+        );
+        assert_eq!(extracted.imports, ["./tests.nix"]);
+    }
+
+    #[test]
+    fn test_flake() {
+        let source = r#"{
+  description = "Acme tools";
+
+  inputs = {
+    nixpkgs.url = "github:NixOS/nixpkgs/nixos-unstable";
+    flake-utils = {
+      url = "github:numtide/flake-utils";
+    };
+  };
+  inputs.home-manager.url = "github:nix-community/home-manager";
+  inputs.home-manager.inputs.nixpkgs.follows = "nixpkgs";
+
+  outputs = { self, nixpkgs, flake-utils, ... }:
+    let
+      pkgs = import nixpkgs { system = "x86_64-linux"; };
+    in {
+      packages.x86_64-linux.default = pkgs.hello;
+      nixosModules.acme = import ./modules/acme.nix;
+      devShells = { };
+    };
+}
+"#;
+        let extracted = extract(source);
+        assert_eq!(
+            summary(&extracted),
+            [
+                ("binding description".into(), None, 2, 2),
+                ("binding inputs".into(), None, 4, 9),
+                ("input nixpkgs".into(), Some("inputs".into()), 5, 5),
+                ("input flake-utils".into(), Some("inputs".into()), 6, 8),
+                ("input home-manager".into(), Some("inputs".into()), 10, 11),
+                ("binding outputs".into(), None, 13, 20),
+                ("output packages".into(), Some("outputs".into()), 17, 17),
+                ("output nixosModules".into(), Some("outputs".into()), 18, 18),
+                ("output devShells".into(), Some("outputs".into()), 19, 19),
+            ]
+        );
+        assert_eq!(extracted.imports, ["./modules/acme.nix"]);
+    }
+
+    #[test]
+    fn test_module_imports() {
+        let source = r#"{ config, ... }:
+{
+  imports = [ ./hardware-configuration.nix ../common ];
+  networking.hostName = if config.x == 1 then "a" else "b";
+}
+"#;
+        let extracted = extract(source);
+        assert_eq!(
+            summary(&extracted),
+            [
+                ("formal config".into(), None, 1, 1),
+                ("binding imports".into(), None, 3, 3),
+                ("binding networking.hostName".into(), None, 4, 4),
+            ]
+        );
+        assert_eq!(
+            extracted.imports,
+            ["./hardware-configuration.nix", "../common"]
+        );
+    }
+}
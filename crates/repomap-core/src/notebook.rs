@@ -0,0 +1,161 @@
+//! Jupyter notebooks, mapped cell by cell: code cells are extracted together
+//! in the notebook's kernel language, Python unless its metadata names
+//! another, and markdown cells for their headings. A notebook's JSON lines
+//! mean nothing to a reader, so symbols' lines are cell numbers instead.
+
+use crate::languages::Language;
+use crate::parser::{self, Extracted};
+use crate::queries::QueryRegistry;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Notebook {
+    #[serde(default)]
+    cells: Vec<Cell>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+#[derive(Deserialize, Default)]
+struct Metadata {
+    language_info: Option<LanguageInfo>,
+}
+
+#[derive(Deserialize)]
+struct LanguageInfo {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: Source,
+}
+
+/// A cell's source, saved as one string or, more often, a list of lines
+/// that keep their `\n`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Source {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl Source {
+    fn text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Lines(lines) => lines.concat(),
+        }
+    }
+}
+
+/// Extracts a notebook's code and headings; a file that isn't a notebook
+/// yields nothing.
+pub fn extract(source: &str, registry: &QueryRegistry) -> Extracted {
+    let Ok(notebook) = serde_json::from_str::<Notebook>(source) else {
+        return Extracted::default();
+    };
+    let lang = notebook
+        .metadata
+        .language_info
+        .and_then(|info| Language::from_str(&info.name, true).ok())
+        .filter(|&lang| lang != Language::Jupyter)
+        .unwrap_or(Language::Python);
+
+    let mut code = String::new();
+    // The cell each line of `code` comes from
+    let mut cells = Vec::new();
+    let mut extracted = Extracted::default();
+    for (number, cell) in (1..).zip(&notebook.cells) {
+        let text = cell.source.text();
+        match cell.cell_type.as_str() {
+            // A `%%bash` cell magic runs the whole cell as something else
+            "code" if !text.trim_start().starts_with("%%") => {
+                for line in text.lines() {
+                    // Line magics and shell escapes, like `%matplotlib inline`
+                    let magic = line.trim_start().starts_with(['%', '!']);
+                    code.push_str(if magic { "" } else { line });
+                    code.push('\n');
+                    cells.push(number);
+                }
+            }
+            "markdown" => {
+                let headings = parser::extract_with(&text, Language::Markdown, registry);
+                extracted
+                    .symbols
+                    .extend(headings.symbols.into_iter().map(|mut sym| {
+                        sym.line = number;
+                        sym.end_line = number;
+                        sym
+                    }));
+            }
+            _ => {}
+        }
+    }
+
+    let code = parser::extract_with(&code, lang, registry);
+    let cell_of = |line: usize| cells.get(line.saturating_sub(1)).copied().unwrap_or(1);
+    extracted
+        .symbols
+        .extend(code.symbols.into_iter().map(|mut sym| {
+            sym.line = cell_of(sym.line);
+            sym.end_line = cell_of(sym.end_line);
+            sym
+        }));
+    extracted.symbols.sort_by_key(|sym| sym.line);
+    extracted.imports = code.imports;
+    extracted.references = code.references;
+    extracted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook() {
+        let source = r###"{
+ "cells": [
+  {"cell_type": "markdown", "metadata": {}, "source": ["# Churn analysis\n", "Some notes.\n", "## Setup"]},
+  {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["%matplotlib inline\n", "import pandas\n", "from churn.features import build"]},
+  {"cell_type": "code", "metadata": {}, "outputs": [], "source": "def load(path):\n    return pandas.read_csv(path)\n"},
+  {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["%%bash\n", "def not_this():\n"]},
+  {"cell_type": "raw", "metadata": {}, "source": ["def not_this_either(): pass"]},
+  {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["class Model:\n", "    def fit(self, df):\n", "        return self\n"]}
+ ],
+ "metadata": {"language_info": {"name": "python", "version": "3.12.0"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"###;
+        let extracted = extract(source, &QueryRegistry::default());
+        assert_eq!(
+            extracted
+                .symbols
+                .iter()
+                .map(|sym| (format!("{} {}", sym.kind, sym.name), sym.line, sym.end_line))
+                .collect::<Vec<_>>(),
+            [
+                ("h1 Churn analysis".into(), 1, 1),
+                ("h2 Setup".into(), 1, 1),
+                ("function_definition load".into(), 3, 3),
+                ("class_definition Model".into(), 6, 6),
+                ("function_definition fit".into(), 6, 6),
+            ]
+        );
+        assert_eq!(extracted.imports, ["pandas", "churn.features"]);
+        assert!(
+            extract("{not a notebook", &QueryRegistry::default())
+                .symbols
+                .is_empty()
+        );
+    }
+}
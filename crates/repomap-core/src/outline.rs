@@ -0,0 +1,4056 @@
+//! Line-based symbol extraction for languages repomap has no tree-sitter
+//! grammar for. Each language is a table of [`Rule`]s matched against whole
+//! lines; where a definition ends, and so what encloses what, comes from
+//! matching its braces or, for languages without them, its indentation.
+
+use crate::languages::Language;
+use crate::parser::{Extracted, Symbol};
+use clap::ValueEnum;
+use regex::Regex;
+use smol_str::SmolStr;
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, OnceLock};
+
+/// One kind of definition, spotted by a regex with a `name` group, or a
+/// `names` group for a comma-separated list like `attr_reader :a, :b`; a
+/// list ending in a comma goes on over the lines after. A `parent` group,
+/// like the receiver of Kotlin's `fun String.slug()`, stands in for whatever
+/// encloses the definition, and an `args` group, like the parameters of
+/// Erlang's `fib(N, Acc)`, adds their count to the name: `fib/2`.
+struct Rule {
+    /// The node kind the language's tree-sitter grammar reports, so symbols
+    /// read the same as in languages that have one.
+    kind: &'static str,
+    pattern: &'static str,
+    body: Body,
+    /// Whether it defines a test, like Zig's `test "parses" { ... }`.
+    test: bool,
+}
+
+/// What a definition's body holds, as far as the outline is concerned.
+#[derive(PartialEq)]
+enum Body {
+    /// Statements, like a method's; they are not searched.
+    Code,
+    /// Other definitions, like a class's members.
+    Members,
+    /// The rest of the file's definitions, like after C#'s `namespace Acme;`.
+    RestOfFile,
+    /// Definitions up to the next section, like a Dockerfile's build stage
+    /// up to the next `FROM`.
+    Section,
+}
+
+const fn container(kind: &'static str, pattern: &'static str) -> Rule {
+    Rule {
+        kind,
+        pattern,
+        body: Body::Members,
+        test: false,
+    }
+}
+
+const fn item(kind: &'static str, pattern: &'static str) -> Rule {
+    Rule {
+        kind,
+        pattern,
+        body: Body::Code,
+        test: false,
+    }
+}
+
+const fn test(kind: &'static str, pattern: &'static str) -> Rule {
+    Rule {
+        test: true,
+        ..item(kind, pattern)
+    }
+}
+
+const fn file_scoped(kind: &'static str, pattern: &'static str) -> Rule {
+    Rule {
+        kind,
+        pattern,
+        body: Body::RestOfFile,
+        test: false,
+    }
+}
+
+const fn section(kind: &'static str, pattern: &'static str) -> Rule {
+    Rule {
+        kind,
+        pattern,
+        body: Body::Section,
+        test: false,
+    }
+}
+
+/// How to tell where a definition ends.
+enum Blocks {
+    /// At the brace matching the first one it opens, or at a `;` before any.
+    Braces,
+    /// After the lines indented deeper than it, plus a closing line like
+    /// `end` at its own indentation. Lines like `rescue` at its indentation
+    /// continue it.
+    Indentation {
+        closers: &'static [&'static str],
+        continuations: &'static [&'static str],
+    },
+    /// At the `end` matching the first opener, like `function` or
+    /// `abstract type`, for languages that don't indent module bodies.
+    /// Words inside brackets, like `a[end]`, don't count.
+    Keywords {
+        openers: &'static [&'static str],
+        closer: &'static str,
+    },
+    /// At the parenthesis closing the form it opens, for Lisps.
+    Parens,
+    /// At the first line ending in this, like the `.` after the last clause
+    /// of an Erlang function.
+    Terminator(char),
+}
+
+/// How to outline one language.
+struct Outline {
+    /// Tried in order; the first match on a line wins.
+    rules: &'static [Rule],
+    blocks: Blocks,
+    /// Regexes whose `path` group is an import, or whose `paths` group is a
+    /// comma-separated list of them, like Nim's `import os, std/[strutils]`.
+    imports: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    /// Opening and closing delimiters of comments spanning lines, or of
+    /// docstrings like Julia's `"""`.
+    block_comments: &'static [(&'static str, &'static str)],
+    /// First words of statements some rule would otherwise take for a
+    /// definition, like `return new Foo(`.
+    statements: &'static [&'static str],
+    /// Whether `ALL_CAPS(...)` lines are calls to preprocessor macros, like
+    /// `DISALLOW_COPY(Foo);`, rather than definitions.
+    macro_calls: bool,
+    /// Whether a symbol's parent lists every enclosing definition, like
+    /// `Acme.Store > Cart`, rather than only the nearest.
+    parent_path: bool,
+    /// Whether consecutive definitions of one name are clauses of a single
+    /// function, like Elixir's `def fib(0)` and `def fib(n)`, or Haskell's
+    /// signature and the equations after it. The first one names the kind.
+    clauses: bool,
+    /// Whether a `::` in a name is part of it, like Perl's `Acme::Cart`,
+    /// rather than naming the scope it's defined in, like C++'s
+    /// `void Store::load()`.
+    qualified_names: bool,
+}
+
+macro_rules! java_modifiers {
+    () => {
+        r"^\s*(?:@[\w.]+(?:\([^)]*\))?\s+)*(?:(?:public|protected|private|static|final|abstract|sealed|non-sealed|strictfp|default|synchronized|native)\s+)*"
+    };
+}
+
+const JAVA: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container(
+            "record_declaration",
+            concat!(java_modifiers!(), r"record\s+(?P<name>\w+)\s*[(<]"),
+        ),
+        container(
+            "class_declaration",
+            concat!(java_modifiers!(), r"class\s+(?P<name>\w+)"),
+        ),
+        container(
+            "interface_declaration",
+            concat!(java_modifiers!(), r"interface\s+(?P<name>\w+)"),
+        ),
+        container(
+            "annotation_type_declaration",
+            concat!(java_modifiers!(), r"@interface\s+(?P<name>\w+)"),
+        ),
+        container(
+            "enum_declaration",
+            concat!(java_modifiers!(), r"enum\s+(?P<name>\w+)"),
+        ),
+        item(
+            "constructor_declaration",
+            r"^\s*(?:@[\w.]+(?:\([^)]*\))?\s+)*(?:public|protected|private)\s+(?P<name>[A-Z]\w*)\s*\(",
+        ),
+        item(
+            "method_declaration",
+            concat!(
+                java_modifiers!(),
+                r"(?:<(?:[^<>]|<[^<>]*>)*>\s+)?[\w.$]+(?:<[^=;]*>)?(?:\[\])*\s+(?P<name>\w+)\s*\("
+            ),
+        ),
+    ],
+    imports: &[r"^\s*import\s+(?:static\s+)?(?P<path>[\w.*]+)\s*;"],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[
+        "return", "new", "throw", "else", "case", "yield", "assert", "package",
+    ],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! cpp_template {
+    () => {
+        r"^\s*(?:template\s*<.*>\s*)?"
+    };
+}
+
+const CPP: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container(
+            "namespace_definition",
+            r"^\s*(?:inline\s+)?namespace\s+(?P<name>[\w:]+)\s*(?:\{.*)?$",
+        ),
+        container(
+            "class_specifier",
+            concat!(
+                cpp_template!(),
+                r"(?:typedef\s+)?class\s+(?:\w+\s+)*?(?P<name>[\w:]+)(?:\s+final)?\s*(?:[:{].*)?$"
+            ),
+        ),
+        container(
+            "struct_specifier",
+            concat!(
+                cpp_template!(),
+                r"(?:typedef\s+)?struct\s+(?:\w+\s+)*?(?P<name>[\w:]+)(?:\s+final)?\s*(?:[:{].*)?$"
+            ),
+        ),
+        container(
+            "union_specifier",
+            concat!(
+                cpp_template!(),
+                r"(?:typedef\s+)?union\s+(?P<name>\w+)\s*(?:\{.*)?$"
+            ),
+        ),
+        item(
+            "enum_specifier",
+            r"^\s*(?:typedef\s+)?enum\s+(?:class\s+|struct\s+)?(?P<name>\w+)\s*(?:[:{].*)?$",
+        ),
+        item(
+            "function_definition",
+            concat!(
+                cpp_template!(),
+                r"(?:[\w:<>,*&\[\]]+\s+|[\w:<>,\[\]]+[*&]+\s*)*(?P<name>(?:[\w<>]+::)*(?:~?\w+|operator\s*[^\s(]+))\s*\("
+            ),
+        ),
+    ],
+    imports: &[
+        r#"^\s*#\s*include\s*[<"](?P<path>[^>"]+)[>"]"#,
+        r"^\s*(?:export\s+)?import\s+(?P<path>[\w.:]+)\s*;",
+    ],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[
+        "return",
+        "new",
+        "delete",
+        "throw",
+        "else",
+        "case",
+        "goto",
+        "co_return",
+        "co_yield",
+        "typedef",
+        "using",
+        "if",
+        "while",
+        "for",
+        "switch",
+        "sizeof",
+        "static_assert",
+    ],
+    macro_calls: true,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! csharp_modifiers {
+    () => {
+        r"^\s*(?:\[[^\]]*\]\s*)*(?:(?:public|private|protected|internal|static|sealed|abstract|partial|readonly|unsafe|new|override|virtual|async|extern|file|required|ref|volatile)\s+)*"
+    };
+}
+
+macro_rules! csharp_type {
+    () => {
+        r"(?:[\w.]+(?:<[^=;]*?>)?[?\[\],]*|\([^)]*\))"
+    };
+}
+
+const CSHARP: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        file_scoped(
+            "file_scoped_namespace_declaration",
+            r"^\s*namespace\s+(?P<name>[\w.]+)\s*;",
+        ),
+        container("namespace_declaration", r"^\s*namespace\s+(?P<name>[\w.]+)"),
+        container(
+            "record_declaration",
+            concat!(
+                csharp_modifiers!(),
+                r"record\s+(?:class\s+|struct\s+)?(?P<name>\w+)"
+            ),
+        ),
+        container(
+            "class_declaration",
+            concat!(csharp_modifiers!(), r"class\s+(?P<name>\w+)"),
+        ),
+        container(
+            "struct_declaration",
+            concat!(csharp_modifiers!(), r"struct\s+(?P<name>\w+)"),
+        ),
+        container(
+            "interface_declaration",
+            concat!(csharp_modifiers!(), r"interface\s+(?P<name>\w+)"),
+        ),
+        item(
+            "enum_declaration",
+            concat!(csharp_modifiers!(), r"enum\s+(?P<name>\w+)"),
+        ),
+        item(
+            "constructor_declaration",
+            r"^\s*(?:\[[^\]]*\]\s*)*(?:(?:public|private|protected|internal|static)\s+)+(?P<name>[A-Z]\w*)\s*\(",
+        ),
+        item(
+            "method_declaration",
+            concat!(
+                csharp_modifiers!(),
+                csharp_type!(),
+                r"\s+(?:[\w.]+\.)?(?P<name>\w+)\s*(?:<[^>]*>)?\s*\("
+            ),
+        ),
+        item(
+            "property_declaration",
+            concat!(
+                csharp_modifiers!(),
+                csharp_type!(),
+                r"\s+(?P<name>\w+)\s*(?:\{|=>)"
+            ),
+        ),
+    ],
+    imports: &[r"^\s*(?:global\s+)?using\s+(?:static\s+)?(?:\w+\s*=\s*)?(?P<path>[\w.]+)\s*;"],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[
+        "return", "new", "throw", "else", "case", "yield", "await", "var", "using", "if", "while",
+        "for", "foreach", "switch", "lock",
+    ],
+    macro_calls: false,
+    parent_path: true,
+    clauses: false,
+    qualified_names: false,
+};
+
+/// A Ruby method name, including `valid?`, `name=` and operators.
+macro_rules! ruby_method_name {
+    () => {
+        r"(?P<name>[\w]+[?!=]?|\[\]=?|[+\-*/%<=>!~&|^]+)"
+    };
+}
+
+const RUBY: Outline = Outline {
+    blocks: Blocks::Indentation {
+        closers: &["end", "}"],
+        continuations: &["rescue", "ensure", "else", "elsif", "when", "in"],
+    },
+    rules: &[
+        container("class", r"^\s*class\s+(?P<name>[A-Z][\w:]*)"),
+        container("module", r"^\s*module\s+(?P<name>[A-Z][\w:]*)"),
+        item(
+            "singleton_method",
+            concat!(r"^\s*def\s+self\.", ruby_method_name!()),
+        ),
+        item(
+            "method",
+            concat!(
+                r"^\s*(?:(?:private|protected|public|module_function)\s+)?def\s+",
+                ruby_method_name!()
+            ),
+        ),
+        item("attr_reader", r"^\s*attr_reader[\s(]+(?P<names>[^)#]+)"),
+        item("attr_writer", r"^\s*attr_writer[\s(]+(?P<names>[^)#]+)"),
+        item("attr_accessor", r"^\s*attr_accessor[\s(]+(?P<names>[^)#]+)"),
+    ],
+    imports: &[r#"^\s*require(?:_relative)?[\s(]+['"](?P<path>[^'"]+)['"]"#],
+    line_comments: &["#"],
+    block_comments: &[("=begin", "=end")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! php_modifiers {
+    () => {
+        r"^\s*(?:(?:abstract|final|readonly)\s+)*"
+    };
+}
+
+const PHP: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        file_scoped(
+            "namespace_definition",
+            r"^\s*namespace\s+(?P<name>[\w\\]+)\s*;",
+        ),
+        container(
+            "namespace_definition",
+            r"^\s*namespace\s+(?P<name>[\w\\]+)\s*\{?\s*$",
+        ),
+        container(
+            "class_declaration",
+            concat!(php_modifiers!(), r"class\s+(?P<name>\w+)"),
+        ),
+        container("interface_declaration", r"^\s*interface\s+(?P<name>\w+)"),
+        container("trait_declaration", r"^\s*trait\s+(?P<name>\w+)"),
+        container("enum_declaration", r"^\s*enum\s+(?P<name>\w+)"),
+        item(
+            "method_declaration",
+            r"^\s*(?:(?:public|protected|private|static|abstract|final)\s+)+function\s+&?(?P<name>\w+)\s*\(",
+        ),
+        item(
+            "function_definition",
+            r"^\s*function\s+&?(?P<name>\w+)\s*\(",
+        ),
+    ],
+    // Top-level `use` only; indented ones pull traits into a class
+    imports: &[r"^use\s+(?:function\s+|const\s+)?\\?(?P<path>[\w\\]+)"],
+    line_comments: &["//", "#"],
+    block_comments: &[("/*", "*/")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! kotlin_modifiers {
+    () => {
+        r"^\s*(?:@[\w.:]+(?:\([^)]*\))?\s+)*(?:(?:public|private|protected|internal|open|abstract|final|sealed|data|enum|annotation|inner|value|inline|override|suspend|tailrec|operator|infix|external|expect|actual)\s+)*"
+    };
+}
+
+/// Type parameters like `<T : Comparable<T>>`.
+macro_rules! kotlin_generics {
+    () => {
+        r"<(?:[^<>]|<[^<>]*>)*>"
+    };
+}
+
+const KOTLIN: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        // Interfaces too, as in the grammar
+        container(
+            "class_declaration",
+            concat!(
+                kotlin_modifiers!(),
+                r"(?:class|interface|fun\s+interface)\s+(?P<name>\w+)"
+            ),
+        ),
+        container(
+            "object_declaration",
+            concat!(kotlin_modifiers!(), r"object\s+(?P<name>\w+)"),
+        ),
+        // Extension functions, listed under their receiver
+        item(
+            "function_declaration",
+            concat!(
+                kotlin_modifiers!(),
+                r"fun\s+(?:",
+                kotlin_generics!(),
+                r"\s*)?(?P<parent>[\w.]+(?:",
+                kotlin_generics!(),
+                r")?\??)\.(?P<name>\w+)\s*\("
+            ),
+        ),
+        item(
+            "function_declaration",
+            concat!(
+                kotlin_modifiers!(),
+                r"fun\s+(?:",
+                kotlin_generics!(),
+                r"\s*)?(?P<name>\w+|`[^`]+`)\s*\("
+            ),
+        ),
+    ],
+    imports: &[r"^\s*import\s+(?P<path>[\w.*]+)"],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! swift_modifiers {
+    () => {
+        r"^\s*(?:@\w+(?:\([^)]*\))?\s+)*(?:(?:public|private|fileprivate|internal|package|open)(?:\(set\))?\s+|(?:final|static|class|override|mutating|nonmutating|convenience|required|dynamic|indirect|nonisolated|distributed)\s+)*"
+    };
+}
+
+const SWIFT: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        // Structs, enums, actors and extensions too, as in the grammar
+        container(
+            "class_declaration",
+            concat!(
+                swift_modifiers!(),
+                r"(?:class|struct|enum|actor|extension)\s+(?P<name>[\w.]+)"
+            ),
+        ),
+        container(
+            "protocol_declaration",
+            concat!(swift_modifiers!(), r"protocol\s+(?P<name>\w+)"),
+        ),
+        item(
+            "init_declaration",
+            concat!(swift_modifiers!(), r"(?P<name>init)[?!]?\s*[(<]"),
+        ),
+        item(
+            "function_declaration",
+            concat!(
+                swift_modifiers!(),
+                r"func\s+(?P<name>\w+|[^\w\s(<]+)\s*[(<]"
+            ),
+        ),
+    ],
+    imports: &[
+        r"^\s*(?:@\w+\s+)*import\s+(?:(?:typealias|struct|class|enum|protocol|let|var|func)\s+)?(?P<path>[\w.]+)",
+    ],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! elixir_function_name {
+    () => {
+        r"\s+(?P<name>[a-z_]\w*[?!]?)"
+    };
+}
+
+const ELIXIR: Outline = Outline {
+    blocks: Blocks::Indentation {
+        closers: &["end"],
+        continuations: &["rescue", "catch", "else", "after"],
+    },
+    // Everything is a `call` in the grammar, so these are the macros called
+    rules: &[
+        container("defmodule", r"^\s*defmodule\s+(?P<name>[A-Z][\w.]*)"),
+        item("def", concat!(r"^\s*def", elixir_function_name!())),
+        item("defp", concat!(r"^\s*defp", elixir_function_name!())),
+        item(
+            "defmacro",
+            concat!(r"^\s*defmacro", elixir_function_name!()),
+        ),
+        item(
+            "defmacrop",
+            concat!(r"^\s*defmacrop", elixir_function_name!()),
+        ),
+    ],
+    // `alias Acme.{Cart, Store}` counts as `Acme`
+    imports: &[r"^\s*(?:alias|import|use)\s+(?P<path>[A-Z]\w*(?:\.[A-Z]\w*)*)"],
+    line_comments: &["#"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: true,
+    qualified_names: false,
+};
+
+const HASKELL: Outline = Outline {
+    blocks: Blocks::Indentation {
+        closers: &[],
+        continuations: &[],
+    },
+    rules: &[
+        // Just the header, so top-level functions stay functions
+        item("module", r"^module\s+(?P<name>[A-Z][\w.]*)"),
+        container("class", r"^\s*class\s+(?:.*=>\s*)?(?P<name>[A-Z][\w']*)"),
+        container(
+            "instance",
+            r"^\s*instance\s+(?:.*=>\s*)?(?P<name>[A-Z][^=]*?)\s*(?:where\s*)?$",
+        ),
+        item("data_type", r"^\s*data\s+(?:.*=>\s*)?(?P<name>[A-Z][\w']*)"),
+        item(
+            "newtype",
+            r"^\s*newtype\s+(?:.*=>\s*)?(?P<name>[A-Z][\w']*)",
+        ),
+        // (sic), as the grammar spells it
+        item("type_synomym", r"^\s*type\s+(?P<name>[A-Z][\w']*)"),
+        item(
+            "signature",
+            r"^\s*(?P<names>(?:[a-z_][\w']*|\([^)\s]+\))(?:\s*,\s*(?:[a-z_][\w']*|\([^)\s]+\)))*)\s*::",
+        ),
+        // Equations, so their `where` bindings are not taken for top-level
+        // ones; they join the signature before them
+        item(
+            "function",
+            r"^\s*(?P<name>[a-z_][\w']*)(?:\s+[^=\s!#$%&*+./<>?@\\^|~:-][^=]*)?\s=(?:[^=]|$)",
+        ),
+    ],
+    imports: &[r"^import\s+(?:safe\s+)?(?:qualified\s+)?(?P<path>[A-Z][\w.]*)"],
+    line_comments: &["--"],
+    block_comments: &[("{-", "-}")],
+    statements: &[
+        "infix", "infixl", "infixr", "foreign", "default", "deriving", "pattern",
+    ],
+    macro_calls: false,
+    parent_path: false,
+    clauses: true,
+    qualified_names: false,
+};
+
+/// `const Name = ` before a container type.
+macro_rules! zig_const {
+    () => {
+        r"^\s*(?:pub\s+)?const\s+(?P<name>\w+)\s*(?::[^=]*)?=\s*(?:extern\s+|packed\s+)?"
+    };
+}
+
+const ZIG: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container("struct_declaration", concat!(zig_const!(), r"struct\b")),
+        container("enum_declaration", concat!(zig_const!(), r"enum\b")),
+        container("union_declaration", concat!(zig_const!(), r"union\b")),
+        item(
+            "function_declaration",
+            r#"^\s*(?:pub\s+)?(?:(?:export|extern(?:\s+"\w+")?|inline|noinline)\s+)*fn\s+(?P<name>\w+)\s*\("#,
+        ),
+        test(
+            "test_declaration",
+            r#"^\s*test\s+"?(?P<name>[^"{]*?)"?\s*\{"#,
+        ),
+    ],
+    imports: &[r#"@import\("(?P<path>[^"]+)"\)"#],
+    // `\\` starts a line of a multiline string, which holds no code
+    line_comments: &["//", r"\\"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! dart_annotations {
+    () => {
+        r"^\s*(?:@\w+(?:\([^)]*\))?\s+)*"
+    };
+}
+
+const DART: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container(
+            "class_definition",
+            concat!(
+                dart_annotations!(),
+                r"(?:(?:abstract|base|final|interface|sealed|mixin)\s+)*class\s+(?P<name>\w+)"
+            ),
+        ),
+        container(
+            "mixin_declaration",
+            r"^\s*(?:base\s+)?mixin\s+(?P<name>\w+)",
+        ),
+        // An unnamed extension is listed under the type it extends
+        container(
+            "extension_declaration",
+            r"^\s*extension\s+on\s+(?P<name>\w+(?:<[^{]*>)?\??)",
+        ),
+        container(
+            "extension_declaration",
+            r"^\s*extension\s+(?:type\s+)?(?P<name>\w+)",
+        ),
+        container("enum_declaration", r"^\s*enum\s+(?P<name>\w+)"),
+        item(
+            "constructor_signature",
+            r"^\s*(?:(?:const|factory|external)\s+)*(?P<name>[A-Z]\w*(?:\.\w+)?)\s*\(",
+        ),
+        item(
+            "getter_signature",
+            r"^\s*(?:(?:static|external|abstract)\s+)*(?:[\w<>?,\[\] ]+?\s+)?get\s+(?P<name>\w+)",
+        ),
+        item(
+            "setter_signature",
+            r"^\s*(?:(?:static|external|abstract)\s+)*(?:void\s+)?set\s+(?P<name>\w+)\s*\(",
+        ),
+        item(
+            "function_signature",
+            concat!(
+                dart_annotations!(),
+                r"(?:(?:static|external|abstract)\s+)*(?:[\w<>?,\[\]. ]+?\s+)?(?P<name>\w+)\s*(?:<[^>]*>)?\s*",
+                // A body, `=>` or `;` after the parameters, so a field like
+                // `void Function(int) onTap;` is not taken for a `Function`
+                r"(?:\([^;]*?\)\s*(?:async\*?|sync\*)?\s*(?:\{|=>|;)|\([^)]*$)"
+            ),
+        ),
+    ],
+    imports: &[r#"^\s*(?:import|export|part)\s+['"](?P<path>[^'"]+)['"]"#],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[
+        "return", "new", "throw", "else", "case", "await", "yield", "assert", "if", "while", "for",
+        "switch", "super", "this", "library",
+    ],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const JULIA: Outline = Outline {
+    blocks: Blocks::Keywords {
+        openers: &[
+            "function",
+            "macro",
+            "module",
+            "baremodule",
+            "struct",
+            "abstract type",
+            "primitive type",
+            "if",
+            "for",
+            "while",
+            "begin",
+            "let",
+            "try",
+            "quote",
+            "do",
+        ],
+        closer: "end",
+    },
+    rules: &[
+        container("module_definition", r"^\s*(?:bare)?module\s+(?P<name>\w+)"),
+        item(
+            "struct_definition",
+            r"^\s*(?:mutable\s+)?struct\s+(?P<name>\w+)",
+        ),
+        item(
+            "abstract_definition",
+            r"^\s*abstract\s+type\s+(?P<name>\w+)",
+        ),
+        item("macro_definition", r"^\s*macro\s+(?P<name>\w+)"),
+        item(
+            "function_definition",
+            r"^\s*function\s+(?:[\w.]+\.)?(?P<name>\w+!?|:?[^\s(\w]+)",
+        ),
+        // `area(r::Circle) = pi * r.radius^2`
+        item(
+            "short_function_definition",
+            r"^\s*(?:[\w.]+\.)?(?P<name>\w+!?)(?:\{[^}]*\})?\([^=]*\)\s*(?:::\s*[\w{}.]+\s*)?(?:where\s+.*?)?=(?:[^=]|$)",
+        ),
+    ],
+    imports: &[
+        r"^\s*(?:using|import)\s+(?P<path>[\w.]+)",
+        r#"^\s*include\("(?P<path>[^"]+)"\)"#,
+    ],
+    line_comments: &["#"],
+    block_comments: &[("#=", "=#"), (r#"""""#, r#"""""#)],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const R: Outline = Outline {
+    blocks: Blocks::Braces,
+    // Classes are calls in the grammar, so these are the functions called
+    rules: &[
+        container(
+            "R6Class",
+            r"^\s*(?P<name>[\w.]+)\s*(?:<<?-|=)\s*(?:R6::)?R6Class\(",
+        ),
+        item(
+            "setClass",
+            r#"^\s*(?:[\w.]+\s*(?:<-|=)\s*)?(?:methods::)?set(?:Ref)?Class\(\s*(?:Class\s*=\s*)?["'](?P<name>[^"']+)"#,
+        ),
+        // Top-level functions and, in an `R6Class`, its methods
+        item(
+            "function_definition",
+            r"^\s*(?P<name>[\w.]+|`[^`]+`)\s*(?:<<?-|=)\s*(?:function\s*|\\)\(",
+        ),
+    ],
+    imports: &[
+        r#"(?:^|[\s(])(?:library|require|requireNamespace|source)\(\s*["']?(?P<path>[^"'),\s]+)"#,
+    ],
+    line_comments: &["#"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const SHELL: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        item("function_definition", r"^\s*function\s+(?P<name>[\w.:@-]+)"),
+        item("function_definition", r"^\s*(?P<name>[\w.:@-]+)\s*\(\s*\)"),
+    ],
+    // The path as written, like `"$(dirname "$0")/lib.sh"`
+    imports: &[r#"^\s*(?:source|\.)\s+(?P<path>(?:"[^"]*"|'[^']*'|\$\([^)]*\)|[^\s;&|])+)"#],
+    line_comments: &["#"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const POWERSHELL: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container("class_statement", r"(?i)^\s*class\s+(?P<name>\w+)"),
+        item("enum_statement", r"(?i)^\s*enum\s+(?P<name>\w+)"),
+        item(
+            "function_statement",
+            r"(?i)^\s*(?:function|filter|workflow)\s+(?:(?:global|script|local|private):)?(?P<name>[\w-]+)",
+        ),
+        // Methods and constructors; `Verb-Noun` calls have a hyphen
+        item(
+            "class_method_definition",
+            r"(?i)^\s*(?:(?:static|hidden)\s+)*(?:\[[\w.\[\], ]+\]\s*)?(?P<name>\w+)\s*\([^)]*\)\s*(?:\{.*)?$",
+        ),
+    ],
+    imports: &[
+        r#"(?i)^\s*Import-Module\s+(?:-Name\s+)?["']?(?P<path>[^"'\s;]+)"#,
+        r#"(?i)^\s*using\s+module\s+["']?(?P<path>[^"'\s;]+)"#,
+        r#"^\s*\.\s+["']?(?P<path>[^"'\s;]+)"#,
+    ],
+    line_comments: &["#"],
+    block_comments: &[("<#", "#>")],
+    statements: &[
+        "if", "elseif", "else", "while", "for", "foreach", "switch", "until", "do", "catch",
+        "trap", "return", "throw", "param",
+    ],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+/// A block label, quoted or not.
+macro_rules! hcl_label {
+    ($group:literal) => {
+        concat!(r#"\s+"?(?P<"#, $group, r#">[\w.-]+)"?"#)
+    };
+}
+
+const HCL: Outline = Outline {
+    blocks: Blocks::Braces,
+    // Every block is a `block` in the grammar, so these are the block
+    // types. Resources are listed under their resource type.
+    rules: &[
+        item(
+            "resource",
+            concat!(r"^\s*resource", hcl_label!("parent"), hcl_label!("name")),
+        ),
+        item(
+            "data",
+            concat!(r"^\s*data", hcl_label!("parent"), hcl_label!("name")),
+        ),
+        // A container, so its `source` is read
+        container("module", concat!(r"^\s*module", hcl_label!("name"))),
+        item("variable", concat!(r"^\s*variable", hcl_label!("name"))),
+        item("output", concat!(r"^\s*output", hcl_label!("name"))),
+    ],
+    imports: &[r#"^\s*source\s*=\s*"(?P<path>[^"]+)""#],
+    line_comments: &["#", "//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const GRAPHQL: Outline = Outline {
+    blocks: Blocks::Braces,
+    // Only the root operation types are containers, so only their fields,
+    // the API's entry points, are listed
+    rules: &[
+        container(
+            "object_type_definition",
+            r"^\s*(?:extend\s+)?type\s+(?P<name>Query|Mutation|Subscription)\b",
+        ),
+        item(
+            "object_type_definition",
+            r"^\s*(?:extend\s+)?type\s+(?P<name>\w+)",
+        ),
+        item(
+            "interface_type_definition",
+            r"^\s*(?:extend\s+)?interface\s+(?P<name>\w+)",
+        ),
+        item(
+            "enum_type_definition",
+            r"^\s*(?:extend\s+)?enum\s+(?P<name>\w+)",
+        ),
+        item(
+            "input_object_type_definition",
+            r"^\s*(?:extend\s+)?input\s+(?P<name>\w+)",
+        ),
+        item(
+            "union_type_definition",
+            r"^\s*(?:extend\s+)?union\s+(?P<name>\w+)",
+        ),
+        item(
+            "scalar_type_definition",
+            r"^\s*(?:extend\s+)?scalar\s+(?P<name>\w+)",
+        ),
+        item("directive_definition", r"^\s*directive\s+@(?P<name>\w+)"),
+        item("schema_definition", r"^\s*(?:extend\s+)?(?P<name>schema)\b"),
+        // Operations and fragments in client documents
+        item(
+            "operation_definition",
+            r"^\s*(?:query|mutation|subscription)\s+(?P<name>\w+)",
+        ),
+        item(
+            "fragment_definition",
+            r"^\s*fragment\s+(?P<name>\w+)\s+on\b",
+        ),
+        // `user(id: ID!): User`, not a selection like `user(id: 1) {`
+        item(
+            "field_definition",
+            r"^\s*(?P<name>\w+)\s*(?:\(.*\)\s*)?:\s*\[*\w+[\]!]*\s*(?:[=@].*)?$",
+        ),
+        // Arguments on the lines after
+        item("field_definition", r"^\s*(?P<name>\w+)\s*\(\s*$"),
+    ],
+    imports: &[],
+    line_comments: &["#"],
+    // Descriptions
+    block_comments: &[(r#"""""#, r#"""""#)],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const PROTOBUF: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container("message", r"^\s*message\s+(?P<name>\w+)"),
+        item("enum", r"^\s*enum\s+(?P<name>\w+)"),
+        container("service", r"^\s*service\s+(?P<name>\w+)"),
+        item("rpc", r"^\s*rpc\s+(?P<name>\w+)"),
+    ],
+    imports: &[r#"^\s*import\s+(?:public\s+|weak\s+)?"(?P<path>[^"]+)""#],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const DOCKERFILE: Outline = Outline {
+    blocks: Blocks::Braces,
+    // Instructions are listed under their build stage, named by its `AS`
+    // or else by its base image
+    rules: &[
+        section(
+            "from_instruction",
+            r"(?i)^\s*FROM\s+(?:--\S+\s+)*\S+\s+AS\s+(?P<name>\S+)",
+        ),
+        section(
+            "from_instruction",
+            r"(?i)^\s*FROM\s+(?:--\S+\s+)*(?P<name>\S+)",
+        ),
+        // Named by the stage or image copied from
+        item(
+            "copy_instruction",
+            r"(?i)^\s*COPY\s+(?:--\S+\s+)*?--from=(?P<name>\S+)",
+        ),
+        item(
+            "expose_instruction",
+            r"(?i)^\s*EXPOSE\s+(?P<name>.*?)\s*\\?$",
+        ),
+        item(
+            "entrypoint_instruction",
+            r"(?i)^\s*ENTRYPOINT\s+(?P<name>.*?)\s*\\?$",
+        ),
+        item("cmd_instruction", r"(?i)^\s*CMD\s+(?P<name>.*?)\s*\\?$"),
+    ],
+    imports: &[],
+    line_comments: &["#"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+/// A symbol being defined, after any metadata like `^:private`.
+macro_rules! clojure_name {
+    () => {
+        r#"\s+(?:\^:?[\w.-]+\s+)*(?P<name>[^\s()\[\]{}"^;][^\s()\[\]{}";]*)"#
+    };
+}
+
+/// A namespace, like `acme.shopping-cart`.
+macro_rules! clojure_namespace {
+    () => {
+        r"(?P<path>[a-zA-Z][\w*+!?-]*(?:\.[\w*+!?-]+)*)"
+    };
+}
+
+const CLOJURE: Outline = Outline {
+    blocks: Blocks::Parens,
+    // Everything is a list in the grammar, so these are the macros called
+    rules: &[
+        file_scoped("ns", concat!(r"^\s*\(ns", clojure_name!())),
+        item("defn", concat!(r"^\s*\(defn", clojure_name!())),
+        item("defn-", concat!(r"^\s*\(defn-", clojure_name!())),
+        item("defmacro", concat!(r"^\s*\(defmacro", clojure_name!())),
+        item(
+            "defprotocol",
+            concat!(r"^\s*\(defprotocol", clojure_name!()),
+        ),
+        item("defrecord", concat!(r"^\s*\(defrecord", clojure_name!())),
+        item("def", concat!(r"^\s*\(def(?:once)?", clojure_name!())),
+    ],
+    // The libs of a `(:require ...)`, one per line: the first after
+    // `:require`, then those given an alias or referred from
+    imports: &[
+        concat!(r"^\s*\(:?require\s+'?\[?", clojure_namespace!()),
+        concat!(
+            r"^\s*'?\[",
+            clojure_namespace!(),
+            r"\s+:(?:as|as-alias|refer|refer-macros|include-macros)\b"
+        ),
+        r"^\s*'?\[(?P<path>[a-zA-Z][\w*+!?-]*(?:\.[\w*+!?-]+)+)\]",
+    ],
+    line_comments: &[";"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const ERLANG: Outline = Outline {
+    blocks: Blocks::Terminator('.'),
+    // A function's clauses all run to its `.`, so the first stands for them
+    rules: &[
+        file_scoped(
+            "module_attribute",
+            r"^-module\(\s*(?P<name>[\w@.]+|'[^']*')\s*\)",
+        ),
+        item("export_attribute", r"^-export\(\s*\[(?P<names>[^\]%]*)"),
+        item(
+            "fun_decl",
+            r"^(?P<name>[a-z][\w@]*|'[^']*')\((?P<args>.*?)\)\s*(?:when\s.*)?->",
+        ),
+    ],
+    imports: &[
+        r#"^-include(?:_lib)?\(\s*"(?P<path>[^"]+)""#,
+        r"^-behaviou?r\(\s*(?P<path>\w+)",
+    ],
+    line_comments: &["%"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const SOLIDITY: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container(
+            "contract_declaration",
+            r"^\s*(?:abstract\s+)?contract\s+(?P<name>\w+)",
+        ),
+        container("interface_declaration", r"^\s*interface\s+(?P<name>\w+)"),
+        container("library_declaration", r"^\s*library\s+(?P<name>\w+)"),
+        item("function_definition", r"^\s*function\s+(?P<name>\w+)\s*\("),
+        item("constructor_definition", r"^\s*(?P<name>constructor)\s*\("),
+        item(
+            "fallback_receive_definition",
+            r"^\s*(?P<name>fallback|receive)\s*\(",
+        ),
+        item("modifier_definition", r"^\s*modifier\s+(?P<name>\w+)"),
+        item("event_definition", r"^\s*event\s+(?P<name>\w+)\s*\("),
+        item("error_declaration", r"^\s*error\s+(?P<name>\w+)\s*\("),
+        item("struct_declaration", r"^\s*struct\s+(?P<name>\w+)"),
+        item("enum_declaration", r"^\s*enum\s+(?P<name>\w+)"),
+    ],
+    // `import "./A.sol";`, `import {A} from "./A.sol";`, `import * as A from ...`
+    imports: &[r#"^\s*import\s+(?:[^"';]*?\bfrom\s+)?["'](?P<path>[^"']+)["']"#],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const PERL: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container(
+            "package_statement",
+            r"^\s*package\s+(?P<name>[\w:]+)(?:\s+v?[\d.]+)?\s*\{",
+        ),
+        // `package Acme::Cart;` runs to the next package
+        section("package_statement", r"^\s*package\s+(?P<name>[\w:]+)"),
+        item(
+            "subroutine_declaration_statement",
+            r"^\s*(?:(?:my|our|state)\s+)?sub\s+(?P<name>[\w:]+)",
+        ),
+    ],
+    // Modules, not pragmas like `use strict`, and parent classes
+    imports: &[
+        r#"^\s*use\s+(?:parent|base)\s+(?:-norequire\s*,\s*)?(?:qw\W\s*|["'])(?P<path>[\w:]+)"#,
+        r"^\s*(?:use|require)\s+(?P<path>[A-Z][\w:]*)",
+        r#"^\s*(?:require|do)\s+["'](?P<path>[^"']+)["']"#,
+    ],
+    line_comments: &["#"],
+    // POD
+    block_comments: &[
+        ("=pod", "=cut"),
+        ("=head", "=cut"),
+        ("=over", "=cut"),
+        ("=item", "=cut"),
+        ("=begin", "=cut"),
+        ("=for", "=cut"),
+        ("=encoding", "=cut"),
+    ],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: true,
+};
+
+// Groovy, and Gradle build scripts written in it
+const GROOVY: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        container(
+            "class_declaration",
+            concat!(java_modifiers!(), r"class\s+(?P<name>\w+)"),
+        ),
+        container(
+            "interface_declaration",
+            concat!(java_modifiers!(), r"interface\s+(?P<name>\w+)"),
+        ),
+        container(
+            "trait_declaration",
+            concat!(java_modifiers!(), r"trait\s+(?P<name>\w+)"),
+        ),
+        container(
+            "enum_declaration",
+            concat!(java_modifiers!(), r"enum\s+(?P<name>\w+)"),
+        ),
+        // Gradle's `plugins { id 'java' }` and `apply plugin: 'java'`
+        item(
+            "plugin",
+            r#"^\s*(?:id\s*\(?|apply\s+plugin\s*:)\s*["'](?P<name>[^"']+)["']"#,
+        ),
+        item("dependencies", r"^\s*(?P<name>dependencies)\s*\{"),
+        container(
+            "script_block",
+            r"^\s*(?P<name>allprojects|subprojects|buildscript)\s*\{",
+        ),
+        container(
+            "project",
+            r#"^\s*project\s*\(\s*["'](?P<name>[^"']+)["']\s*\)\s*\{"#,
+        ),
+        item(
+            "task",
+            r#"^\s*(?:tasks\.(?:register|create)\s*\(\s*["']|task\b\s*\(?\s*["']?)(?P<name>[\w-]+)"#,
+        ),
+        // Without a visibility modifier or a return type, a constructor or
+        // method is told from a call by the body it opens on the same line
+        item(
+            "constructor_declaration",
+            r"^\s*(?:@[\w.]+(?:\([^)]*\))?\s+)*(?:(?:public|protected|private)\s+)?(?P<name>[A-Z]\w*)\s*\([^)]*\)\s*\{",
+        ),
+        item(
+            "method_declaration",
+            concat!(
+                java_modifiers!(),
+                r"(?:<(?:[^<>]|<[^<>]*>)*>\s+)?[\w.$]+(?:<[^=;]*>)?(?:\[\])*\s+(?P<name>\w+)\s*\([^)]*\)\s*(?:throws\s+[\w.,\s]+)?\{"
+            ),
+        ),
+    ],
+    imports: &[
+        r"^\s*import\s+(?:static\s+)?(?P<path>[\w.*]+)",
+        // Gradle's `apply from: 'gradle/publishing.gradle'`
+        r#"^\s*apply\s+from\s*:\s*["'](?P<path>[^"']+)["']"#,
+    ],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    statements: &[
+        "return", "new", "throw", "else", "case", "yield", "assert", "package",
+    ],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const FSHARP: Outline = Outline {
+    blocks: Blocks::Indentation {
+        closers: &[],
+        continuations: &[],
+    },
+    rules: &[
+        section("namespace", r"^\s*namespace\s+(?:rec\s+)?(?P<name>[\w.]+)"),
+        container(
+            "module_defn",
+            r"^\s*module\s+(?:(?:private|internal|public|rec)\s+)*(?P<name>\w+)\s*=\s*$",
+        ),
+        // `module Acme.Cart` at the top of a file, holding all of it
+        file_scoped(
+            "named_module",
+            r"^module\s+(?:(?:private|internal|public|rec)\s+)*(?P<name>[\w.]+)\s*$",
+        ),
+        container(
+            "type_definition",
+            r"^\s*(?:\[<[^\]]*>\]\s*)*type\s+(?:\[<[^\]]*>\]\s*)?(?:(?:private|internal|public)\s+)?(?P<name>\w+)",
+        ),
+        // The rest of a group of recursive types; a lowercase name is
+        // another `let rec` function
+        container(
+            "type_definition",
+            r"^\s*and\s+(?:\[<[^\]]*>\]\s*)?(?:(?:private|internal|public)\s+)?(?P<name>[A-Z]\w*)",
+        ),
+        item(
+            "member_defn",
+            r"^\s*(?:static\s+)?(?:member|override|default|abstract(?:\s+member)?)\s+(?:val\s+)?(?:inline\s+)?(?:(?:private|internal|public)\s+)?(?:\w+\.)?(?P<name>\w+)",
+        ),
+        item(
+            "function_or_value_defn",
+            r"^\s*(?:\[<[^\]]*>\]\s*)*(?:let|and)\s+(?:(?:rec|inline|mutable|private|internal|public)\s+)*(?P<name>``[^`]+``|\(\|[^)]*\)|\w+)",
+        ),
+    ],
+    imports: &[
+        r"^\s*open\s+(?:type\s+)?(?P<path>[\w.]+)",
+        // Scripts' `#load "Helpers.fsx"`
+        r#"^\s*#load\s+"(?P<path>[^"]+)""#,
+    ],
+    line_comments: &["//"],
+    block_comments: &[("(*", "*)")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+macro_rules! nim_routine {
+    () => {
+        r"\s+(?P<name>\w+|`[^`]+`)\*?"
+    };
+}
+
+macro_rules! nim_type {
+    () => {
+        r"(?P<name>\w+)\*?\s*(?:\[[^\]]*\])?\s*(?:\{\.[^}]*\.\}\s*)?="
+    };
+}
+
+const NIM: Outline = Outline {
+    blocks: Blocks::Indentation {
+        closers: &[],
+        continuations: &[],
+    },
+    rules: &[
+        item("proc_declaration", concat!(r"^\s*proc", nim_routine!())),
+        item("func_declaration", concat!(r"^\s*func", nim_routine!())),
+        item("method_declaration", concat!(r"^\s*method", nim_routine!())),
+        item(
+            "iterator_declaration",
+            concat!(r"^\s*iterator", nim_routine!()),
+        ),
+        item(
+            "converter_declaration",
+            concat!(r"^\s*converter", nim_routine!()),
+        ),
+        item(
+            "template_declaration",
+            concat!(r"^\s*template", nim_routine!()),
+        ),
+        item("macro_declaration", concat!(r"^\s*macro", nim_routine!())),
+        item("type_declaration", concat!(r"^\s*type\s+", nim_type!())),
+        // Under a `type` line of its own, a definition is told from a
+        // constant by the type it makes
+        item(
+            "type_declaration",
+            concat!(
+                r"^\s*",
+                nim_type!(),
+                r"\s*(?:ref\s+|ptr\s+)?(?:object|enum|tuple|distinct|concept)\b"
+            ),
+        ),
+    ],
+    imports: &[
+        r"^\s*(?:import|include)\s+(?P<paths>.*?)\s*(?:\bexcept\b.*|#.*)?$",
+        r"^\s*from\s+(?P<path>\S+)\s+import\b",
+    ],
+    line_comments: &["#"],
+    block_comments: &[("#[", "]#")],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+const GLEAM: Outline = Outline {
+    blocks: Blocks::Braces,
+    rules: &[
+        item(
+            "type_alias",
+            r"^\s*(?:pub\s+)?type\s+(?P<name>[A-Z]\w*)(?:\([^)]*\))?\s*=",
+        ),
+        item(
+            "type_definition",
+            r"^\s*(?:pub\s+)?(?:opaque\s+)?type\s+(?P<name>[A-Z]\w*)",
+        ),
+        item("constant", r"^\s*(?:pub\s+)?const\s+(?P<name>\w+)"),
+        // Externals too, which have no body
+        item("function", r"^\s*(?:pub\s+)?fn\s+(?P<name>\w+)\s*\("),
+    ],
+    imports: &[r"^\s*import\s+(?P<path>[\w/]+)"],
+    line_comments: &["//"],
+    block_comments: &[],
+    statements: &[],
+    macro_calls: false,
+    parent_path: false,
+    clauses: false,
+    qualified_names: false,
+};
+
+fn outline(lang: Language) -> Option<&'static Outline> {
+    match lang {
+        Language::Java => Some(&JAVA),
+        Language::Cpp => Some(&CPP),
+        Language::Csharp => Some(&CSHARP),
+        Language::Ruby => Some(&RUBY),
+        Language::Php => Some(&PHP),
+        Language::Kotlin => Some(&KOTLIN),
+        Language::Swift => Some(&SWIFT),
+        Language::Elixir => Some(&ELIXIR),
+        Language::Haskell => Some(&HASKELL),
+        Language::Zig => Some(&ZIG),
+        Language::Dart => Some(&DART),
+        Language::Julia => Some(&JULIA),
+        Language::R => Some(&R),
+        Language::Shell => Some(&SHELL),
+        Language::Powershell => Some(&POWERSHELL),
+        Language::Hcl => Some(&HCL),
+        Language::Graphql => Some(&GRAPHQL),
+        Language::Protobuf => Some(&PROTOBUF),
+        Language::Dockerfile => Some(&DOCKERFILE),
+        Language::Clojure => Some(&CLOJURE),
+        Language::Erlang => Some(&ERLANG),
+        Language::Solidity => Some(&SOLIDITY),
+        Language::Perl => Some(&PERL),
+        Language::Groovy => Some(&GROOVY),
+        Language::Fsharp => Some(&FSHARP),
+        Language::Nim => Some(&NIM),
+        Language::Gleam => Some(&GLEAM),
+        _ => None,
+    }
+}
+
+struct Compiled {
+    rules: Vec<(Regex, &'static Rule)>,
+    imports: Vec<Regex>,
+}
+
+// Compiled the first time a file of the language is outlined, like the
+// tree-sitter queries
+static COMPILED: LazyLock<HashMap<Language, OnceLock<Compiled>>> = LazyLock::new(|| {
+    Language::value_variants()
+        .iter()
+        .filter(|&&lang| outline(lang).is_some())
+        .map(|&lang| (lang, OnceLock::new()))
+        .collect()
+});
+
+fn compiled(lang: Language, outline: &'static Outline) -> &'static Compiled {
+    COMPILED[&lang].get_or_init(|| Compiled {
+        rules: outline
+            .rules
+            .iter()
+            .map(|rule| {
+                (
+                    Regex::new(rule.pattern).expect("built-in rule is valid"),
+                    rule,
+                )
+            })
+            .collect(),
+        imports: outline
+            .imports
+            .iter()
+            .map(|pattern| Regex::new(pattern).expect("built-in import rule is valid"))
+            .collect(),
+    })
+}
+
+/// Outlines `source` as `lang`: its definitions, imports, and the
+/// identifiers it mentions.
+pub fn extract(source: &str, lang: Language) -> Extracted {
+    let Some(outline) = outline(lang) else {
+        return Extracted::default();
+    };
+    let compiled = compiled(lang, outline);
+    let lines: Vec<&str> = source.lines().collect();
+    let code = code_lines(&lines, outline);
+
+    let mut symbols = Vec::new();
+    let mut imports: Vec<String> = Vec::new();
+    // Containers still open at the current line: last line, name
+    let mut open: Vec<(usize, SmolStr)> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if !code[i] {
+            i += 1;
+            continue;
+        }
+        if let Some(paths) = compiled.imports.iter().find_map(|re| {
+            let caps = re.captures(line)?;
+            match (caps.name("path"), caps.name("paths")) {
+                (Some(path), _) => Some(vec![path.as_str().to_string()]),
+                (None, Some(list)) => Some(split_paths(list.as_str())),
+                (None, None) => None,
+            }
+        }) {
+            for path in paths {
+                let path = path.trim_matches(['"', '\'']).to_string();
+                if !path.is_empty() && !imports.contains(&path) {
+                    imports.push(path);
+                }
+            }
+            i += 1;
+            continue;
+        }
+        let Some((rule, mut names, receiver, mut more)) = definition(line, outline, compiled)
+        else {
+            i += 1;
+            continue;
+        };
+
+        let end = match rule.body {
+            Body::RestOfFile => lines.len() - 1,
+            Body::Section => section_end(&lines, &code, i, compiled),
+            Body::Code | Body::Members => block_end(&lines, &code, i, outline),
+        };
+        // The rest of a list like `-export([start/0,`, up to its bracket
+        for (j, line) in lines.iter().enumerate().take(end + 1).skip(i + 1) {
+            if !more {
+                break;
+            }
+            if code[j] {
+                let line = structure(line, outline);
+                let list = line.split([')', ']']).next().unwrap_or_default();
+                more = list.trim_end().ends_with(',');
+                names.extend(split_names(list));
+            }
+        }
+        open.retain(|&(open_end, _)| open_end >= i);
+        let enclosing = match open.as_slice() {
+            _ if receiver.is_some() => receiver,
+            [] => None,
+            [.., (_, nearest)] if !outline.parent_path => Some(nearest.clone()),
+            all => Some(SmolStr::new(
+                all.iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" > "),
+            )),
+        };
+        for name in names {
+            // `void Store::load()` defined outside its class
+            let (parent, name) = match name.rsplit_once("::") {
+                Some((scope, name)) if !scope.is_empty() && !outline.qualified_names => {
+                    (Some(SmolStr::new(scope)), SmolStr::new(name))
+                }
+                _ => (enclosing.clone(), name),
+            };
+            if let Some(last) = symbols.last_mut().filter(|last: &&mut Symbol| {
+                outline.clauses
+                    && rule.body == Body::Code
+                    && last.name == name
+                    && last.parent == parent
+            }) {
+                last.end_line = end + 1;
+                continue;
+            }
+            symbols.push(Symbol {
+                name: name.clone(),
+                parent,
+                line: i + 1,
+                kind: SmolStr::new_static(rule.kind),
+                end_line: end + 1,
+                test: rule.test,
+            });
+            if rule.body != Body::Code {
+                open.push((end, name));
+            }
+        }
+        i = if rule.body == Body::Code {
+            end + 1
+        } else {
+            i + 1
+        };
+    }
+
+    Extracted {
+        symbols,
+        imports,
+        references: references(&lines, &code),
+    }
+}
+
+/// The rule `line` matches, the names it defines, the parent it names, if
+/// any, and whether its list of names goes on to the next line.
+fn definition(
+    line: &str,
+    outline: &Outline,
+    compiled: &Compiled,
+) -> Option<(&'static Rule, Vec<SmolStr>, Option<SmolStr>, bool)> {
+    let first_word = line
+        .trim_start()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()?;
+    if outline.statements.contains(&first_word) {
+        return None;
+    }
+    let (rule, names, parent, more) = compiled.rules.iter().find_map(|(re, rule)| {
+        let caps = re.captures(line)?;
+        let (names, more) = match (caps.name("name"), caps.name("names"), caps.name("args")) {
+            (Some(name), _, Some(args)) => {
+                let arity = arity(&structure(args.as_str(), outline));
+                (
+                    vec![SmolStr::new(format!("{}/{arity}", name.as_str()))],
+                    false,
+                )
+            }
+            (Some(name), _, None) => (vec![SmolStr::new(name.as_str())], false),
+            (None, Some(list), _) => (
+                split_names(list.as_str()),
+                list.as_str().trim_end().ends_with(','),
+            ),
+            (None, None, _) => return None,
+        };
+        let parent = caps
+            .name("parent")
+            .map(|parent| SmolStr::new(parent.as_str()));
+        Some((*rule, names, parent, more))
+    })?;
+    if outline.macro_calls
+        && rule.body == Body::Code
+        && names.iter().any(|name| is_macro_name(name))
+    {
+        return None;
+    }
+    Some((rule, names, parent, more))
+}
+
+/// The imports in a comma-separated list like `os, ./cart as c`, where
+/// `std/[strutils, tables]` stands for `std/strutils` and `std/tables`.
+fn split_paths(list: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (at, c) in list.char_indices().chain([(list.len(), ',')]) {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(list[start..at].trim());
+                start = at + 1;
+            }
+            _ => {}
+        }
+    }
+    let mut paths = Vec::new();
+    for item in items {
+        match item.split_once('[') {
+            Some((prefix, group)) => paths.extend(
+                group
+                    .trim_end_matches(']')
+                    .split(',')
+                    .filter_map(|name| name.split_whitespace().next())
+                    .map(|name| format!("{prefix}{name}")),
+            ),
+            // Without an alias like `as c`
+            None => paths.extend(item.split_whitespace().next().map(str::to_string)),
+        }
+    }
+    paths
+}
+
+/// The names in a comma-separated list like `:a, :b`.
+fn split_names(list: &str) -> Vec<SmolStr> {
+    list.split(',')
+        .map(|name| {
+            name.trim()
+                .trim_start_matches(':')
+                .trim_matches(['"', '\''])
+        })
+        .filter(|name| !name.is_empty())
+        .map(SmolStr::new)
+        .collect()
+}
+
+/// How many parameters `args` holds, like the 2 in `fib(N, Acc)`. Commas
+/// nested in brackets or binaries like `<<Id:8, Rest/binary>>` don't count.
+fn arity(args: &str) -> usize {
+    if args.trim().is_empty() {
+        return 0;
+    }
+    let mut depth = 0usize;
+    let mut count = 1;
+    let mut prev = ' ';
+    for c in args.chars() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            '<' if prev == '<' => depth += 1,
+            '>' if prev == '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => count += 1,
+            _ => {}
+        }
+        prev = c;
+    }
+    count
+}
+
+fn is_macro_name(name: &str) -> bool {
+    name.len() > 1
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// The last line of code before the next section starts after `start`.
+fn section_end(lines: &[&str], code: &[bool], start: usize, compiled: &Compiled) -> usize {
+    let mut end = start;
+    for (j, line) in lines.iter().enumerate().skip(start + 1) {
+        if !code[j] {
+            continue;
+        }
+        if compiled
+            .rules
+            .iter()
+            .any(|(re, rule)| rule.body == Body::Section && re.is_match(line))
+        {
+            break;
+        }
+        end = j;
+    }
+    end
+}
+
+/// How many lines a signature may run before its body or `;`.
+const SIGNATURE_LINES: usize = 16;
+
+/// The last line of the definition starting at `start`.
+fn block_end(lines: &[&str], code: &[bool], start: usize, outline: &Outline) -> usize {
+    match outline.blocks {
+        Blocks::Braces => brace_end(lines, code, start, outline),
+        Blocks::Indentation {
+            closers,
+            continuations,
+        } => indented_end(lines, code, start, closers, continuations),
+        Blocks::Keywords { openers, closer } => {
+            keyword_end(lines, code, start, outline, openers, closer)
+        }
+        Blocks::Parens => paren_end(lines, code, start),
+        Blocks::Terminator(terminator) => {
+            let mut end = start;
+            for (j, line) in lines.iter().enumerate().skip(start) {
+                if !code[j] {
+                    continue;
+                }
+                end = j;
+                if structure(line, outline).trim_end().ends_with(terminator) {
+                    break;
+                }
+            }
+            end
+        }
+    }
+}
+
+/// The line closing the form opened at `start`. Strings, which may span
+/// lines, character literals like `\)`, and `;` comments don't count.
+fn paren_end(lines: &[&str], code: &[bool], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    for (j, line) in lines.iter().enumerate().skip(start) {
+        if !code[j] && !in_string {
+            continue;
+        }
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = !in_string,
+                _ if in_string => {}
+                ';' => break,
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return j;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    lines.len() - 1
+}
+
+/// The line with the `closer` matching the first opener at or after `start`,
+/// or `start` itself if it opens nothing, like Julia's `f(x) = 2x`.
+fn keyword_end(
+    lines: &[&str],
+    code: &[bool],
+    start: usize,
+    outline: &Outline,
+    openers: &[&str],
+    closer: &str,
+) -> usize {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut depth = 0usize;
+    let mut brackets = 0usize;
+    for (j, line) in lines.iter().enumerate().skip(start) {
+        if !code[j] {
+            continue;
+        }
+        let line = structure(line, outline);
+        let mut prev = ' ';
+        for (at, c) in line.char_indices() {
+            match c {
+                '(' | '[' => brackets += 1,
+                ')' | ']' => brackets = brackets.saturating_sub(1),
+                // A word's start, not a field like `x.end`
+                _ if brackets == 0 && is_word(c) && !is_word(prev) && prev != '.' => {
+                    let rest = &line[at..];
+                    if starts_with_token(rest, closer) && depth > 0 {
+                        depth -= 1;
+                        if depth == 0 {
+                            return j;
+                        }
+                    } else if openers.iter().any(|word| starts_with_token(rest, word)) {
+                        depth += 1;
+                    }
+                }
+                _ => {}
+            }
+            prev = c;
+        }
+        if depth == 0 {
+            return start;
+        }
+    }
+    lines.len() - 1
+}
+
+/// Characters that start a line continuing a signature rather than a new
+/// statement, like a `{` on its own line or `: Base(args)`.
+const SIGNATURE_CONTINUATIONS: &[char] = &[')', ']', '{', ':', '=', '.', '-', ',', '>'];
+
+/// The line closing the first brace opened at or after `start`, or ending
+/// the definition with `;` if it has no body. Without either, as in Kotlin's
+/// `fun area() = w * h`, it ends before the next line back at its own
+/// indentation. Braces inside parentheses, like Dart's named parameters
+/// `({required int id})`, don't count.
+fn brace_end(lines: &[&str], code: &[bool], start: usize, outline: &Outline) -> usize {
+    let indent = indentation(lines[start]);
+    let mut depth = 0;
+    let mut parens = 0usize;
+    let mut last = start;
+    for (j, line) in lines.iter().enumerate().skip(start) {
+        if !code[j] {
+            continue;
+        }
+        if depth == 0 && j > start {
+            if j > start + SIGNATURE_LINES {
+                return start;
+            }
+            if indentation(line) <= indent
+                && !line.trim_start().starts_with(SIGNATURE_CONTINUATIONS)
+            {
+                return last;
+            }
+        }
+        last = j;
+        for c in structure(line, outline).chars() {
+            match c {
+                '(' => parens += 1,
+                ')' => parens = parens.saturating_sub(1),
+                _ if parens > 0 => {}
+                '{' => depth += 1,
+                '}' if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return j;
+                    }
+                }
+                ';' if depth == 0 => return j,
+                _ => {}
+            }
+        }
+    }
+    if depth > 0 { lines.len() - 1 } else { last }
+}
+
+/// The last line indented deeper than `start`, or the closer after it.
+fn indented_end(
+    lines: &[&str],
+    code: &[bool],
+    start: usize,
+    closers: &[&str],
+    continuations: &[&str],
+) -> usize {
+    let indent = indentation(lines[start]);
+    let mut end = start;
+    for (j, line) in lines.iter().enumerate().skip(start + 1) {
+        if !code[j] {
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let depth = indentation(line);
+        if depth > indent
+            || depth == indent
+                && continuations
+                    .iter()
+                    .any(|word| starts_with_token(trimmed, word))
+        {
+            end = j;
+            continue;
+        }
+        if depth == indent && closers.iter().any(|word| starts_with_token(trimmed, word)) {
+            end = j;
+        }
+        break;
+    }
+    end
+}
+
+/// Whether `text` starts with `token`, and a word token like `end` is not
+/// just the start of a longer word.
+fn starts_with_token(text: &str, token: &str) -> bool {
+    let Some(rest) = text.strip_prefix(token) else {
+        return false;
+    };
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    !token.ends_with(is_word) || !rest.starts_with(is_word)
+}
+
+/// Tabs count as four columns.
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// `line` without its string and character literals or trailing comment,
+/// so the braces left are the code's own. A `'` right after an identifier
+/// or bracket is an operator, like Julia's transpose `A'`, or a digit
+/// separator. A `#` comment starts a word, so shell's `${#items[@]}` is code.
+fn structure(line: &str, outline: &Outline) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.char_indices();
+    while let Some((at, c)) = chars.next() {
+        let word_start = line[..at]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace);
+        if outline.line_comments.iter().any(|comment| {
+            line[at..].starts_with(comment) && (word_start || !comment.starts_with('#'))
+        }) {
+            break;
+        }
+        let after_operand = line[..at]
+            .chars()
+            .next_back()
+            .is_some_and(|p| p.is_alphanumeric() || matches!(p, '_' | ')' | ']' | '\''));
+        if c == '"' || c == '\'' && !after_operand {
+            let mut escaped = false;
+            for (_, next) in chars.by_ref() {
+                match next {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    _ if next == c => break,
+                    _ => {}
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Whether each line holds code, as opposed to being blank or a comment.
+fn code_lines(lines: &[&str], outline: &Outline) -> Vec<bool> {
+    // The closing delimiter of the comment a line is in
+    let mut in_comment = None;
+    lines
+        .iter()
+        .map(|line| {
+            let trimmed = line.trim();
+            if let Some(close) = in_comment {
+                if trimmed.contains(close) {
+                    in_comment = None;
+                }
+                return false;
+            }
+            if let Some((rest, close)) = outline
+                .block_comments
+                .iter()
+                .find_map(|&(open, close)| Some((trimmed.strip_prefix(open)?, close)))
+            {
+                if !rest.contains(close) {
+                    in_comment = Some(close);
+                }
+                return false;
+            }
+            !trimmed.is_empty()
+                && !outline
+                    .line_comments
+                    .iter()
+                    .any(|comment| trimmed.starts_with(comment))
+        })
+        .collect()
+}
+
+/// Every distinct identifier-like word on a line of code, sorted.
+fn references(lines: &[&str], code: &[bool]) -> Vec<SmolStr> {
+    let mut seen = HashSet::new();
+    for (line, _) in lines.iter().zip(code).filter(|&(_, &code)| code) {
+        seen.extend(
+            line.split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_')),
+        );
+    }
+    let mut references: Vec<SmolStr> = seen.into_iter().map(SmolStr::new).collect();
+    references.sort_unstable();
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline_of(source: &str, lang: Language) -> Vec<(String, Option<String>, usize, usize)> {
+        extract(source, lang)
+            .symbols
+            .into_iter()
+            .map(|sym| {
+                (
+                    format!("{} {}", sym.kind, sym.name),
+                    sym.parent.map(|p| p.to_string()),
+                    sym.line,
+                    sym.end_line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_rules_compile() {
+        for &lang in Language::value_variants() {
+            if let Some(outline) = outline(lang) {
+                compiled(lang, outline);
+            }
+        }
+    }
+
+    #[test]
+    fn test_java() {
+        let source = r#"package com.example.store;
+
+import java.util.List;
+import static java.util.Objects.requireNonNull;
+
+/**
+ * A store. public class NotThis {}
+ */
+@Service
+public final class Store<T> implements Repository<T> {
+    private final List<T> items = new ArrayList<>();
+
+    public Store(List<T> items) {
+        this.items = requireNonNull(items);
+    }
+
+    @Override
+    public <R extends Comparable<R>> List<R> map(Function<T, R> f) {
+        if (items.isEmpty()) {
+            return new ArrayList<>();
+        } else if (f == null) {
+            throw new IllegalArgumentException("f");
+        }
+        return items.stream().map(f).toList();
+    }
+
+    enum Mode { READ, WRITE }
+
+    public record Entry(String key, T value) {
+        String describe() { return key; }
+    }
+}
+
+interface Repository<T> {
+    List<T> all();
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Java),
+            [
+                ("class_declaration Store".into(), None, 10, 32),
+                (
+                    "constructor_declaration Store".into(),
+                    Some("Store".into()),
+                    13,
+                    15
+                ),
+                (
+                    "method_declaration map".into(),
+                    Some("Store".into()),
+                    18,
+                    25
+                ),
+                ("enum_declaration Mode".into(), Some("Store".into()), 27, 27),
+                (
+                    "record_declaration Entry".into(),
+                    Some("Store".into()),
+                    29,
+                    31
+                ),
+                (
+                    "method_declaration describe".into(),
+                    Some("Entry".into()),
+                    30,
+                    30
+                ),
+                ("interface_declaration Repository".into(), None, 34, 36),
+                (
+                    "method_declaration all".into(),
+                    Some("Repository".into()),
+                    35,
+                    35
+                ),
+            ]
+        );
+        let extracted = extract(source, Language::Java);
+        assert_eq!(
+            extracted.imports,
+            ["java.util.List", "java.util.Objects.requireNonNull"]
+        );
+        assert!(
+            extracted
+                .references
+                .contains(&SmolStr::new("requireNonNull"))
+        );
+        assert!(!extracted.references.contains(&SmolStr::new("NotThis")));
+    }
+
+    #[test]
+    fn test_braces() {
+        let source =
+            "class A\n{\n    void f()\n    {\n        g(\"}\", '{');\n    }\n}\nclass B {}\n";
+        assert_eq!(
+            outline_of(source, Language::Java),
+            [
+                ("class_declaration A".into(), None, 1, 7),
+                ("method_declaration f".into(), Some("A".into()), 3, 6),
+                ("class_declaration B".into(), None, 8, 8),
+            ]
+        );
+        assert_eq!(structure(r#"f("a\"}", '}') { // }"#, &JAVA), "f(, ) { ");
+    }
+
+    #[test]
+    fn test_csharp() {
+        let source = r#"using System;
+using static System.Math;
+using Json = System.Text.Json;
+
+namespace Acme.Store
+{
+    /// <summary>A cart.</summary>
+    [Serializable]
+    public sealed partial class Cart : IDisposable
+    {
+        private readonly List<Item> _items = new();
+
+        public Cart(IClock clock) { _clock = clock; }
+
+        public decimal Total => _items.Sum(i => i.Price);
+
+        public string Name { get; set; } = "";
+
+        public async Task<bool> CheckoutAsync(CancellationToken token = default)
+        {
+            if (_items.Count == 0) { return false; }
+            await _payments.ChargeAsync(Total, token);
+            return true;
+        }
+
+        void IDisposable.Dispose() => _items.Clear();
+
+        public record Item(string Sku, decimal Price);
+
+        private enum State { Open, Closed }
+    }
+
+    public interface IClock
+    {
+        DateTime Now { get; }
+        (int, int) Split(DateTime at);
+    }
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Csharp),
+            [
+                ("namespace_declaration Acme.Store".into(), None, 5, 38),
+                (
+                    "class_declaration Cart".into(),
+                    Some("Acme.Store".into()),
+                    9,
+                    31
+                ),
+                (
+                    "constructor_declaration Cart".into(),
+                    Some("Acme.Store > Cart".into()),
+                    13,
+                    13
+                ),
+                (
+                    "property_declaration Total".into(),
+                    Some("Acme.Store > Cart".into()),
+                    15,
+                    15
+                ),
+                (
+                    "property_declaration Name".into(),
+                    Some("Acme.Store > Cart".into()),
+                    17,
+                    17
+                ),
+                (
+                    "method_declaration CheckoutAsync".into(),
+                    Some("Acme.Store > Cart".into()),
+                    19,
+                    24
+                ),
+                (
+                    "method_declaration Dispose".into(),
+                    Some("Acme.Store > Cart".into()),
+                    26,
+                    26
+                ),
+                (
+                    "record_declaration Item".into(),
+                    Some("Acme.Store > Cart".into()),
+                    28,
+                    28
+                ),
+                (
+                    "enum_declaration State".into(),
+                    Some("Acme.Store > Cart".into()),
+                    30,
+                    30
+                ),
+                (
+                    "interface_declaration IClock".into(),
+                    Some("Acme.Store".into()),
+                    33,
+                    37
+                ),
+                (
+                    "property_declaration Now".into(),
+                    Some("Acme.Store > IClock".into()),
+                    35,
+                    35
+                ),
+                (
+                    "method_declaration Split".into(),
+                    Some("Acme.Store > IClock".into()),
+                    36,
+                    36
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Csharp).imports,
+            ["System", "System.Math", "System.Text.Json"]
+        );
+
+        let scoped = "namespace Acme;\n\npublic class A\n{\n    public void Run() { }\n}\n";
+        assert_eq!(
+            outline_of(scoped, Language::Csharp),
+            [
+                ("file_scoped_namespace_declaration Acme".into(), None, 1, 6),
+                ("class_declaration A".into(), Some("Acme".into()), 3, 6),
+                (
+                    "method_declaration Run".into(),
+                    Some("Acme > A".into()),
+                    5,
+                    5
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ruby() {
+        let source = r#"require "json"
+require_relative "support/helpers"
+
+# A user. class NotThis
+module Accounts
+  class User < ApplicationRecord
+    attr_reader :name, :email
+    attr_accessor(:role)
+
+    def self.find_by_email(email)
+      where(email: email).first
+    end
+
+    def admin?
+      role == "admin"
+    rescue NoMethodError
+      false
+    end
+
+    def ==(other)
+      other.id == id
+    end
+
+    private def slug = name.downcase
+
+    class << self
+      def table = "users"
+    end
+  end
+end
+
+class Accounts::Team; end
+"#;
+        assert_eq!(
+            outline_of(source, Language::Ruby),
+            [
+                ("module Accounts".into(), None, 5, 30),
+                ("class User".into(), Some("Accounts".into()), 6, 29),
+                ("attr_reader name".into(), Some("User".into()), 7, 7),
+                ("attr_reader email".into(), Some("User".into()), 7, 7),
+                ("attr_accessor role".into(), Some("User".into()), 8, 8),
+                (
+                    "singleton_method find_by_email".into(),
+                    Some("User".into()),
+                    10,
+                    12
+                ),
+                ("method admin?".into(), Some("User".into()), 14, 18),
+                ("method ==".into(), Some("User".into()), 20, 22),
+                ("method slug".into(), Some("User".into()), 24, 24),
+                ("method table".into(), Some("User".into()), 27, 27),
+                ("class Team".into(), Some("Accounts".into()), 32, 32),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Ruby).imports,
+            ["json", "support/helpers"]
+        );
+        assert!(starts_with_token("end", "end"));
+        assert!(!starts_with_token("ending", "end"));
+    }
+
+    #[test]
+    fn test_php() {
+        let source = r#"<?php
+
+declare(strict_types=1);
+
+namespace App\Models;
+
+use Illuminate\Database\Eloquent\Model;
+use function App\Support\slugify;
+
+#[ObservedBy(UserObserver::class)]
+final class User extends Model implements HasName
+{
+    use HasFactory, Notifiable;
+
+    protected $fillable = ['name', 'email'];
+
+    public function posts(): HasMany
+    {
+        return $this->hasMany(Post::class, "author_id");
+    }
+
+    public static function &registry(): array { return self::$registry; }
+
+    abstract protected function label(): string;
+}
+
+interface HasName
+{
+    public function name(): string;
+}
+
+trait Greets
+{
+    function greet() { echo "hi {$this->name}"; }
+}
+
+enum Status: string
+{
+    case Active = 'active';
+}
+
+function helper(array $items) {
+    return array_map(fn ($i) => $i * 2, $items);
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Php),
+            [
+                ("namespace_definition App\\Models".into(), None, 5, 44),
+                (
+                    "class_declaration User".into(),
+                    Some("App\\Models".into()),
+                    11,
+                    25
+                ),
+                (
+                    "method_declaration posts".into(),
+                    Some("User".into()),
+                    17,
+                    20
+                ),
+                (
+                    "method_declaration registry".into(),
+                    Some("User".into()),
+                    22,
+                    22
+                ),
+                (
+                    "method_declaration label".into(),
+                    Some("User".into()),
+                    24,
+                    24
+                ),
+                (
+                    "interface_declaration HasName".into(),
+                    Some("App\\Models".into()),
+                    27,
+                    30
+                ),
+                (
+                    "method_declaration name".into(),
+                    Some("HasName".into()),
+                    29,
+                    29
+                ),
+                (
+                    "trait_declaration Greets".into(),
+                    Some("App\\Models".into()),
+                    32,
+                    35
+                ),
+                (
+                    "function_definition greet".into(),
+                    Some("Greets".into()),
+                    34,
+                    34
+                ),
+                (
+                    "enum_declaration Status".into(),
+                    Some("App\\Models".into()),
+                    37,
+                    40
+                ),
+                (
+                    "function_definition helper".into(),
+                    Some("App\\Models".into()),
+                    42,
+                    44
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Php).imports,
+            [
+                "Illuminate\\Database\\Eloquent\\Model",
+                "App\\Support\\slugify"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kotlin() {
+        let source = r#"package com.acme.store
+
+import com.acme.model.User
+import kotlinx.coroutines.flow.*
+
+data class Order(
+    val id: Long,
+    val items: List<Item>,
+)
+
+interface Repository<T> {
+    fun find(id: Long): T?
+}
+
+class Store(private val repo: Repository<Order>) : Repository<Order> by repo {
+    override fun find(id: Long): Order? = repo.find(id)
+
+    suspend fun <T : Comparable<T>> sorted(
+        items: List<T>,
+    ): List<T> {
+        return items.sorted()
+    }
+}
+
+object Registry {
+    fun register(store: Store) {
+        stores += store
+    }
+}
+
+fun String.slugify(): String =
+    lowercase().replace(' ', '-')
+
+fun <T> List<T>.second(): T = this[1]
+"#;
+        assert_eq!(
+            outline_of(source, Language::Kotlin),
+            [
+                ("class_declaration Order".into(), None, 6, 9),
+                ("class_declaration Repository".into(), None, 11, 13),
+                (
+                    "function_declaration find".into(),
+                    Some("Repository".into()),
+                    12,
+                    12
+                ),
+                ("class_declaration Store".into(), None, 15, 23),
+                (
+                    "function_declaration find".into(),
+                    Some("Store".into()),
+                    16,
+                    16
+                ),
+                (
+                    "function_declaration sorted".into(),
+                    Some("Store".into()),
+                    18,
+                    22
+                ),
+                ("object_declaration Registry".into(), None, 25, 29),
+                (
+                    "function_declaration register".into(),
+                    Some("Registry".into()),
+                    26,
+                    28
+                ),
+                (
+                    "function_declaration slugify".into(),
+                    Some("String".into()),
+                    31,
+                    32
+                ),
+                (
+                    "function_declaration second".into(),
+                    Some("List<T>".into()),
+                    34,
+                    34
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Kotlin).imports,
+            ["com.acme.model.User", "kotlinx.coroutines.flow.*"]
+        );
+    }
+
+    #[test]
+    fn test_swift() {
+        let source = r#"import Foundation
+@testable import Core
+
+public final class Store: ObservableObject {
+    @Published private(set) var items: [Item] = []
+
+    convenience init?(json: Data) {
+        self.init(name: "")
+    }
+
+    func add(_ item: Item) async throws {
+        items.append(item)
+    }
+
+    static func == (lhs: Store, rhs: Store) -> Bool { lhs.name == rhs.name }
+}
+
+protocol Repository {
+    func find(id: Int) -> Value?
+    func all() -> [Value]
+}
+
+extension Array where Element == Item {
+    func total() -> Int { reduce(0) { $0 + $1.price } }
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Swift),
+            [
+                ("class_declaration Store".into(), None, 4, 16),
+                ("init_declaration init".into(), Some("Store".into()), 7, 9),
+                (
+                    "function_declaration add".into(),
+                    Some("Store".into()),
+                    11,
+                    13
+                ),
+                (
+                    "function_declaration ==".into(),
+                    Some("Store".into()),
+                    15,
+                    15
+                ),
+                ("protocol_declaration Repository".into(), None, 18, 21),
+                (
+                    "function_declaration find".into(),
+                    Some("Repository".into()),
+                    19,
+                    19
+                ),
+                (
+                    "function_declaration all".into(),
+                    Some("Repository".into()),
+                    20,
+                    20
+                ),
+                ("class_declaration Array".into(), None, 23, 25),
+                (
+                    "function_declaration total".into(),
+                    Some("Array".into()),
+                    24,
+                    24
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Swift).imports,
+            ["Foundation", "Core"]
+        );
+    }
+
+    #[test]
+    fn test_elixir() {
+        let source = r#"defmodule Acme.Cart do
+  use GenServer
+  alias Acme.{Item, Store}
+
+  def start_link(opts), do: GenServer.start_link(__MODULE__, opts)
+
+  def total(%{items: []}), do: 0
+
+  def total(%{items: items}) do
+    Enum.reduce(items, 0, &(&1.price + &2))
+  end
+
+  def valid?(cart) do
+    cart.items != []
+  rescue
+    _ -> false
+  end
+
+  defp price(item), do: item.price
+
+  defmacro with_cart(do: block) do
+    quote do
+      def generated, do: unquote(block)
+    end
+  end
+
+  defmodule Line do
+    def new(sku), do: %__MODULE__{sku: sku}
+  end
+end
+"#;
+        assert_eq!(
+            outline_of(source, Language::Elixir),
+            [
+                ("defmodule Acme.Cart".into(), None, 1, 30),
+                ("def start_link".into(), Some("Acme.Cart".into()), 5, 5),
+                ("def total".into(), Some("Acme.Cart".into()), 7, 11),
+                ("def valid?".into(), Some("Acme.Cart".into()), 13, 17),
+                ("defp price".into(), Some("Acme.Cart".into()), 19, 19),
+                (
+                    "defmacro with_cart".into(),
+                    Some("Acme.Cart".into()),
+                    21,
+                    25
+                ),
+                ("defmodule Line".into(), Some("Acme.Cart".into()), 27, 29),
+                ("def new".into(), Some("Line".into()), 28, 28),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Elixir).imports,
+            ["GenServer", "Acme"]
+        );
+    }
+
+    #[test]
+    fn test_haskell() {
+        let source = r#"module Acme.Store
+  ( Store (..)
+  , total
+  ) where
+
+import qualified Data.Map.Strict as Map
+import Acme.Item
+
+data Store = Store
+  { storeItems :: Map.Map Text Item
+  } deriving (Show)
+
+newtype Sku = Sku Text
+
+type Price = Int
+
+class Priced a where
+  price :: a -> Price
+
+instance (Show a) => Show (Tree a) where
+  show Leaf = "."
+  show (Node l x r) = show l <> show x <> show r
+
+total :: Store -> Price
+total store = sum (map price items)
+  where
+    items :: [Item]
+    items = Map.elems (storeItems store)
+
+fib :: Int -> Int
+fib 0 = 0
+fib n = fib (n - 1) + fib (n - 2)
+
+(<+>) :: Price -> Price -> Price
+a <+> b = a + b
+
+helper x = x * 2
+"#;
+        assert_eq!(
+            outline_of(source, Language::Haskell),
+            [
+                ("module Acme.Store".into(), None, 1, 4),
+                ("data_type Store".into(), None, 9, 11),
+                ("newtype Sku".into(), None, 13, 13),
+                ("type_synomym Price".into(), None, 15, 15),
+                ("class Priced".into(), None, 17, 18),
+                ("signature price".into(), Some("Priced".into()), 18, 18),
+                ("instance Show (Tree a)".into(), None, 20, 22),
+                ("function show".into(), Some("Show (Tree a)".into()), 21, 22),
+                ("signature total".into(), None, 24, 28),
+                ("signature fib".into(), None, 30, 32),
+                ("signature (<+>)".into(), None, 34, 34),
+                ("function helper".into(), None, 37, 37),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Haskell).imports,
+            ["Data.Map.Strict", "Acme.Item"]
+        );
+    }
+
+    #[test]
+    fn test_zig() {
+        let source = r#"const std = @import("std");
+const Item = @import("item.zig").Item;
+
+pub const Store = struct {
+    items: std.ArrayList(Item),
+
+    pub fn total(self: *const Store) u32 {
+        var sum: u32 = 0;
+        for (self.items.items) |item| {
+            sum += item.price;
+        }
+        return sum;
+    }
+};
+
+pub const Color = enum(u8) { red, green };
+
+const Value = union(enum) { int: i64, float: f64 };
+
+const usage =
+    \\usage: store {
+    \\  --verbose
+;
+
+test "total sums prices" {
+    try std.testing.expect(true);
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Zig),
+            [
+                ("struct_declaration Store".into(), None, 4, 14),
+                (
+                    "function_declaration total".into(),
+                    Some("Store".into()),
+                    7,
+                    13
+                ),
+                ("enum_declaration Color".into(), None, 16, 16),
+                ("union_declaration Value".into(), None, 18, 18),
+                ("test_declaration total sums prices".into(), None, 25, 27),
+            ]
+        );
+        let extracted = extract(source, Language::Zig);
+        assert_eq!(extracted.imports, ["std", "item.zig"]);
+        assert!(extracted.symbols.last().unwrap().test);
+    }
+
+    #[test]
+    fn test_dart() {
+        let source = r#"import 'package:flutter/material.dart';
+import '../models/item.dart' show Item;
+
+class Cart extends ChangeNotifier {
+  Cart.empty() : items = [];
+
+  final List<Item> items;
+  final void Function(int) onChange = print;
+
+  int get total => items.fold(0, (sum, i) => sum + i.price);
+
+  Future<void> checkout({required bool express}) async {
+    if (express) {
+      print('express ${items.length}');
+    }
+  }
+}
+
+mixin Loggable on Object {
+  void log(String msg) => print(msg);
+}
+
+extension on String {
+  String get shout => toUpperCase();
+}
+
+void main() {
+  runApp(const CartPage());
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Dart),
+            [
+                ("class_definition Cart".into(), None, 4, 17),
+                (
+                    "constructor_signature Cart.empty".into(),
+                    Some("Cart".into()),
+                    5,
+                    5
+                ),
+                ("getter_signature total".into(), Some("Cart".into()), 10, 10),
+                (
+                    "function_signature checkout".into(),
+                    Some("Cart".into()),
+                    12,
+                    16
+                ),
+                ("mixin_declaration Loggable".into(), None, 19, 21),
+                (
+                    "function_signature log".into(),
+                    Some("Loggable".into()),
+                    20,
+                    20
+                ),
+                ("extension_declaration String".into(), None, 23, 25),
+                (
+                    "getter_signature shout".into(),
+                    Some("String".into()),
+                    24,
+                    24
+                ),
+                ("function_signature main".into(), None, 27, 29),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Dart).imports,
+            ["package:flutter/material.dart", "../models/item.dart"]
+        );
+    }
+
+    #[test]
+    fn test_julia() {
+        let source = r#"module Geometry
+
+using LinearAlgebra
+include("shapes.jl")
+
+"""
+Compute the area. Returns 0 at the end if unknown; for example
+    area(Circle(1.0)) = 3.14
+"""
+function area end
+
+abstract type Shape end
+
+mutable struct Polygon{T} <: Shape
+    points::Vector{T}
+end
+
+area(c::Circle) = pi * c.radius^2
+
+function area(p::Polygon)
+    s = 0.0
+    for i in 1:length(p.points)
+        s += p.points[end]' * p.points[i]
+    end
+    return s
+end
+
+macro timed2(ex)
+    quote
+        $(esc(ex))
+    end
+end
+
+end
+"#;
+        assert_eq!(
+            outline_of(source, Language::Julia),
+            [
+                ("module_definition Geometry".into(), None, 1, 34),
+                (
+                    "function_definition area".into(),
+                    Some("Geometry".into()),
+                    10,
+                    10
+                ),
+                (
+                    "abstract_definition Shape".into(),
+                    Some("Geometry".into()),
+                    12,
+                    12
+                ),
+                (
+                    "struct_definition Polygon".into(),
+                    Some("Geometry".into()),
+                    14,
+                    16
+                ),
+                (
+                    "short_function_definition area".into(),
+                    Some("Geometry".into()),
+                    18,
+                    18
+                ),
+                (
+                    "function_definition area".into(),
+                    Some("Geometry".into()),
+                    20,
+                    26
+                ),
+                (
+                    "macro_definition timed2".into(),
+                    Some("Geometry".into()),
+                    28,
+                    32
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Julia).imports,
+            ["LinearAlgebra", "shapes.jl"]
+        );
+    }
+
+    #[test]
+    fn test_r() {
+        let source = r#"library(dplyr)
+suppressPackageStartupMessages(require("ggplot2"))
+
+fit_model <- function(df, formula = y ~ x) {
+  if (nrow(df) == 0) {
+    stop("empty")
+  }
+  lm(formula, data = df)
+}
+
+square <- \(x) x^2
+
+setClass("Person", representation(name = "character"))
+
+Person <- R6::R6Class("Person",
+  public = list(
+    greet = function() {
+      cat("Hello, ", self$name, "\n")
+    }
+  )
+)
+"#;
+        assert_eq!(
+            outline_of(source, Language::R),
+            [
+                ("function_definition fit_model".into(), None, 4, 9),
+                ("function_definition square".into(), None, 11, 11),
+                ("setClass Person".into(), None, 13, 13),
+                ("R6Class Person".into(), None, 15, 21),
+                (
+                    "function_definition greet".into(),
+                    Some("Person".into()),
+                    17,
+                    19
+                ),
+            ]
+        );
+        assert_eq!(extract(source, Language::R).imports, ["dplyr", "ggplot2"]);
+    }
+
+    #[test]
+    fn test_shell() {
+        let source = r#"#!/usr/bin/env bash
+set -euo pipefail
+source "$(dirname "$0")/lib/common.sh"
+. ./env.sh
+
+usage() {
+    echo "usage: deploy [-n] <env>" >&2
+    exit 1
+}
+
+function deploy {
+    local count=${#targets[@]}
+    if [[ $# -eq 0 ]]; then
+        usage
+    fi
+    cat <<EOF
+deploying $count targets
+EOF
+}
+
+log() { echo "[$(date)] $*"; }
+
+deploy "$@"
+"#;
+        assert_eq!(
+            outline_of(source, Language::Shell),
+            [
+                ("function_definition usage".into(), None, 6, 9),
+                ("function_definition deploy".into(), None, 11, 19),
+                ("function_definition log".into(), None, 21, 21),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Shell).imports,
+            ["$(dirname \"$0\")/lib/common.sh", "./env.sh"]
+        );
+    }
+
+    #[test]
+    fn test_powershell() {
+        let source = r#"Import-Module Az.Accounts
+. $PSScriptRoot\lib\Common.ps1
+
+<#
+.SYNOPSIS
+  function Fake-Thing {
+#>
+
+class Deployment {
+    [string] $Name
+
+    Deployment([string] $name) {
+        $this.Name = $name
+    }
+
+    [void] Run([Stage] $stage) {
+        if ($stage -eq [Stage]::Prod) {
+            Write-Host "prod: $($this.Name)"
+        }
+    }
+}
+
+function Invoke-Deploy {
+    param([string] $Target)
+    $items = @{ a = 1; b = 2 }
+}
+
+filter Get-Even { if ($_ % 2 -eq 0) { $_ } }
+"#;
+        assert_eq!(
+            outline_of(source, Language::Powershell),
+            [
+                ("class_statement Deployment".into(), None, 9, 21),
+                (
+                    "class_method_definition Deployment".into(),
+                    Some("Deployment".into()),
+                    12,
+                    14
+                ),
+                (
+                    "class_method_definition Run".into(),
+                    Some("Deployment".into()),
+                    16,
+                    20
+                ),
+                ("function_statement Invoke-Deploy".into(), None, 23, 26),
+                ("function_statement Get-Even".into(), None, 28, 28),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Powershell).imports,
+            ["Az.Accounts", "$PSScriptRoot\\lib\\Common.ps1"]
+        );
+    }
+
+    #[test]
+    fn test_hcl() {
+        let source = r#"variable "region" {
+  type    = string
+  default = "us-east-1"
+}
+
+data "aws_ami" "ubuntu" {
+  most_recent = true
+}
+
+# The log bucket
+resource "aws_s3_bucket" "logs" {
+  bucket = "acme-${var.region}-logs"
+
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+module "vpc" {
+  source = "./modules/vpc"
+}
+
+output "bucket_arn" {
+  value = aws_s3_bucket.logs.arn
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Hcl),
+            [
+                ("variable region".into(), None, 1, 4),
+                ("data ubuntu".into(), Some("aws_ami".into()), 6, 8),
+                ("resource logs".into(), Some("aws_s3_bucket".into()), 11, 17),
+                ("module vpc".into(), None, 19, 21),
+                ("output bucket_arn".into(), None, 23, 25),
+            ]
+        );
+        assert_eq!(extract(source, Language::Hcl).imports, ["./modules/vpc"]);
+    }
+
+    #[test]
+    fn test_graphql() {
+        let source = r#"schema {
+  query: Query
+}
+
+"""
+The root query. type Nope { }
+"""
+type Query {
+  "A user by id"
+  user(id: ID!): User
+  users(
+    first: Int = 10
+    after: String
+  ): [User!]!
+}
+
+extend type Mutation {
+  createUser(input: CreateUserInput!): User @auth
+}
+
+interface Node {
+  id: ID!
+}
+
+type User implements Node {
+  id: ID!
+  name: String
+}
+
+enum Role {
+  ADMIN
+  MEMBER
+}
+
+input CreateUserInput {
+  name: String!
+}
+
+union SearchResult = User | Post
+scalar DateTime
+
+query GetUser($id: ID!) {
+  user(id: $id) {
+    name
+  }
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Graphql),
+            [
+                ("schema_definition schema".into(), None, 1, 3),
+                ("object_type_definition Query".into(), None, 8, 15),
+                ("field_definition user".into(), Some("Query".into()), 10, 10),
+                (
+                    "field_definition users".into(),
+                    Some("Query".into()),
+                    11,
+                    14
+                ),
+                ("object_type_definition Mutation".into(), None, 17, 19),
+                (
+                    "field_definition createUser".into(),
+                    Some("Mutation".into()),
+                    18,
+                    18
+                ),
+                ("interface_type_definition Node".into(), None, 21, 23),
+                ("object_type_definition User".into(), None, 25, 28),
+                ("enum_type_definition Role".into(), None, 30, 33),
+                (
+                    "input_object_type_definition CreateUserInput".into(),
+                    None,
+                    35,
+                    37
+                ),
+                ("union_type_definition SearchResult".into(), None, 39, 39),
+                ("scalar_type_definition DateTime".into(), None, 40, 40),
+                ("operation_definition GetUser".into(), None, 42, 46),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_protobuf() {
+        let source = r#"syntax = "proto3";
+
+package acme.store.v1;
+
+import "google/protobuf/timestamp.proto";
+import public "acme/store/v1/item.proto";
+
+// A cart. message NotThis {}
+message Cart {
+  string id = 1;
+  repeated Item items = 2;
+
+  message Line {
+    string sku = 1;
+  }
+
+  enum Status {
+    STATUS_UNSPECIFIED = 0;
+    STATUS_OPEN = 1;
+  }
+}
+
+service CartService {
+  rpc GetCart(GetCartRequest) returns (Cart);
+  rpc WatchCart(GetCartRequest) returns (stream Cart) {
+    option (google.api.http) = { get: "/v1/carts/{id}" };
+  }
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Protobuf),
+            [
+                ("message Cart".into(), None, 9, 21),
+                ("message Line".into(), Some("Cart".into()), 13, 15),
+                ("enum Status".into(), Some("Cart".into()), 17, 20),
+                ("service CartService".into(), None, 23, 28),
+                ("rpc GetCart".into(), Some("CartService".into()), 24, 24),
+                ("rpc WatchCart".into(), Some("CartService".into()), 25, 27),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Protobuf).imports,
+            [
+                "google/protobuf/timestamp.proto",
+                "acme/store/v1/item.proto"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dockerfile() {
+        let source = r#"# syntax=docker/dockerfile:1
+FROM node:20-alpine AS deps
+WORKDIR /app
+COPY package.json package-lock.json ./
+RUN npm ci
+
+FROM node:20-alpine AS build
+WORKDIR /app
+COPY --from=deps /app/node_modules ./node_modules
+COPY . .
+RUN npm run build
+
+FROM nginx:1.27
+COPY --link --from=build /app/dist /usr/share/nginx/html
+EXPOSE 80 443
+ENTRYPOINT ["nginx"]
+CMD ["-g", "daemon off;"]
+"#;
+        assert_eq!(
+            outline_of(source, Language::Dockerfile),
+            [
+                ("from_instruction deps".into(), None, 2, 5),
+                ("from_instruction build".into(), None, 7, 11),
+                ("copy_instruction deps".into(), Some("build".into()), 9, 9),
+                ("from_instruction nginx:1.27".into(), None, 13, 17),
+                (
+                    "copy_instruction build".into(),
+                    Some("nginx:1.27".into()),
+                    14,
+                    14
+                ),
+                (
+                    "expose_instruction 80 443".into(),
+                    Some("nginx:1.27".into()),
+                    15,
+                    15
+                ),
+                (
+                    r#"entrypoint_instruction ["nginx"]"#.into(),
+                    Some("nginx:1.27".into()),
+                    16,
+                    16
+                ),
+                (
+                    r#"cmd_instruction ["-g", "daemon off;"]"#.into(),
+                    Some("nginx:1.27".into()),
+                    17,
+                    17
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clojure() {
+        let source = r#"(ns acme.cart
+  "Shopping carts."
+  (:require [clojure.string :as str]
+            [acme.db :refer [query]]
+            [acme.util.money]))
+
+(def ^:private tax-rate 0.08)
+
+(defonce registry (atom {}))
+
+(defprotocol Priced
+  (price [this] "The price, taxes included."))
+
+(defrecord Item [sku qty]
+  Priced
+  (price [this] (* qty 2)))
+
+(defn- subtotal
+  "Adds up (the items."
+  [items]
+  (reduce + (map price items)))
+
+(defn total [cart]
+  (let [s (subtotal (:items cart))]
+    (str/join [s \)])))
+
+(defmacro with-cart [cart & body]
+  `(let [~'cart ~cart] ~@body))
+"#;
+        assert_eq!(
+            outline_of(source, Language::Clojure),
+            [
+                ("ns acme.cart".into(), None, 1, 28),
+                ("def tax-rate".into(), Some("acme.cart".into()), 7, 7),
+                ("def registry".into(), Some("acme.cart".into()), 9, 9),
+                (
+                    "defprotocol Priced".into(),
+                    Some("acme.cart".into()),
+                    11,
+                    12
+                ),
+                ("defrecord Item".into(), Some("acme.cart".into()), 14, 16),
+                ("defn- subtotal".into(), Some("acme.cart".into()), 18, 21),
+                ("defn total".into(), Some("acme.cart".into()), 23, 25),
+                (
+                    "defmacro with-cart".into(),
+                    Some("acme.cart".into()),
+                    27,
+                    28
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Clojure).imports,
+            ["clojure.string", "acme.db", "acme.util.money"]
+        );
+    }
+
+    #[test]
+    fn test_erlang() {
+        let source = r#"%% A shopping cart.
+-module(cart).
+-behaviour(gen_server).
+
+-include("cart.hrl").
+-include_lib("kernel/include/logger.hrl").
+
+-export([start_link/0, total/1,
+         init/1]).
+
+-record(state, {items = []}).
+
+start_link() ->
+    gen_server:start_link({local, ?MODULE}, ?MODULE, [], []).
+
+total([]) -> 0;
+total([#item{price = P} | Rest]) ->
+    P + total(Rest).
+
+init({Items, <<Id:8, _/binary>>}) when is_list(Items) ->
+    {ok, #state{items = Items}, Id}.
+
+'price of'(Item, "a, b") -> Item.
+"#;
+        assert_eq!(
+            outline_of(source, Language::Erlang),
+            [
+                ("module_attribute cart".into(), None, 2, 23),
+                (
+                    "export_attribute start_link/0".into(),
+                    Some("cart".into()),
+                    8,
+                    9
+                ),
+                ("export_attribute total/1".into(), Some("cart".into()), 8, 9),
+                ("export_attribute init/1".into(), Some("cart".into()), 8, 9),
+                ("fun_decl start_link/0".into(), Some("cart".into()), 13, 14),
+                ("fun_decl total/1".into(), Some("cart".into()), 16, 18),
+                ("fun_decl init/1".into(), Some("cart".into()), 20, 21),
+                ("fun_decl 'price of'/2".into(), Some("cart".into()), 23, 23),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Erlang).imports,
+            ["gen_server", "cart.hrl", "kernel/include/logger.hrl"]
+        );
+    }
+
+    #[test]
+    fn test_solidity() {
+        let source = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "./IVault.sol";
+import {ERC20} from "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+
+/// @notice A vault. contract NotThis {}
+interface IToken {
+    function transfer(address to, uint256 amount) external returns (bool);
+}
+
+library Math {
+    function max(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a >= b ? a : b;
+    }
+}
+
+abstract contract Vault is IVault, Ownable {
+    event Deposited(address indexed from, uint256 amount);
+
+    struct Position {
+        uint256 shares;
+    }
+
+    modifier onlyActive() {
+        require(active, "inactive");
+        _;
+    }
+
+    constructor(address token) Ownable(msg.sender) {
+        asset = token;
+    }
+
+    function deposit(uint256 amount)
+        external
+        onlyActive
+        returns (uint256)
+    {
+        emit Deposited(msg.sender, amount);
+        return amount;
+    }
+
+    receive() external payable {}
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Solidity),
+            [
+                ("interface_declaration IToken".into(), None, 8, 10),
+                (
+                    "function_definition transfer".into(),
+                    Some("IToken".into()),
+                    9,
+                    9
+                ),
+                ("library_declaration Math".into(), None, 12, 16),
+                (
+                    "function_definition max".into(),
+                    Some("Math".into()),
+                    13,
+                    15
+                ),
+                ("contract_declaration Vault".into(), None, 18, 44),
+                (
+                    "event_definition Deposited".into(),
+                    Some("Vault".into()),
+                    19,
+                    19
+                ),
+                (
+                    "struct_declaration Position".into(),
+                    Some("Vault".into()),
+                    21,
+                    23
+                ),
+                (
+                    "modifier_definition onlyActive".into(),
+                    Some("Vault".into()),
+                    25,
+                    28
+                ),
+                (
+                    "constructor_definition constructor".into(),
+                    Some("Vault".into()),
+                    30,
+                    32
+                ),
+                (
+                    "function_definition deposit".into(),
+                    Some("Vault".into()),
+                    34,
+                    41
+                ),
+                (
+                    "fallback_receive_definition receive".into(),
+                    Some("Vault".into()),
+                    43,
+                    43
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Solidity).imports,
+            [
+                "./IVault.sol",
+                "@openzeppelin/contracts/token/ERC20/ERC20.sol"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_perl() {
+        let source = r#"package Acme::Cart;
+use strict;
+use warnings;
+use parent -norequire, 'Acme::Base';
+use List::Util qw(sum);
+require Acme::Store;
+
+=head1 NAME
+
+sub not_this { }
+
+=cut
+
+our $VERSION = '1.0';
+
+sub new {
+    my ($class, %args) = @_;
+    return bless { items => [] }, $class;
+}
+
+sub total {
+    my $self = shift;
+    return sum(map { $_->{price} } @{ $self->{items} });
+}
+
+package Acme::Cart::Item {
+    sub price { $_[0]{price} }
+}
+
+package main;
+
+sub run { Acme::Cart->new->total }
+
+1;
+"#;
+        assert_eq!(
+            outline_of(source, Language::Perl),
+            [
+                ("package_statement Acme::Cart".into(), None, 1, 24),
+                (
+                    "subroutine_declaration_statement new".into(),
+                    Some("Acme::Cart".into()),
+                    16,
+                    19
+                ),
+                (
+                    "subroutine_declaration_statement total".into(),
+                    Some("Acme::Cart".into()),
+                    21,
+                    24
+                ),
+                ("package_statement Acme::Cart::Item".into(), None, 26, 28),
+                (
+                    "subroutine_declaration_statement price".into(),
+                    Some("Acme::Cart::Item".into()),
+                    27,
+                    27
+                ),
+                ("package_statement main".into(), None, 30, 34),
+                (
+                    "subroutine_declaration_statement run".into(),
+                    Some("main".into()),
+                    32,
+                    32
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Perl).imports,
+            ["Acme::Base", "List::Util", "Acme::Store"]
+        );
+    }
+
+    #[test]
+    fn test_groovy() {
+        let source = r#"package com.acme.store
+
+import groovy.transform.CompileStatic
+import com.acme.model.Item
+
+@CompileStatic
+class Cart {
+    List<Item> items = []
+
+    Cart(List<Item> items) {
+        this.items = items
+    }
+
+    def add(Item item) {
+        items << item
+        println total()
+    }
+
+    BigDecimal total() {
+        items.sum { it.price } ?: 0
+    }
+
+    static Cart empty() { new Cart([]) }
+}
+
+trait Priced {
+    BigDecimal price() { 0 }
+}
+
+enum Currency { USD, EUR }
+"#;
+        assert_eq!(
+            outline_of(source, Language::Groovy),
+            [
+                ("class_declaration Cart".into(), None, 7, 24),
+                (
+                    "constructor_declaration Cart".into(),
+                    Some("Cart".into()),
+                    10,
+                    12
+                ),
+                ("method_declaration add".into(), Some("Cart".into()), 14, 17),
+                (
+                    "method_declaration total".into(),
+                    Some("Cart".into()),
+                    19,
+                    21
+                ),
+                (
+                    "method_declaration empty".into(),
+                    Some("Cart".into()),
+                    23,
+                    23
+                ),
+                ("trait_declaration Priced".into(), None, 26, 28),
+                (
+                    "method_declaration price".into(),
+                    Some("Priced".into()),
+                    27,
+                    27
+                ),
+                ("enum_declaration Currency".into(), None, 30, 30),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Groovy).imports,
+            ["groovy.transform.CompileStatic", "com.acme.model.Item"]
+        );
+    }
+
+    #[test]
+    fn test_gradle() {
+        let source = r#"plugins {
+    id 'java-library'
+    id("org.springframework.boot") version "3.2.0"
+}
+
+apply plugin: 'jacoco'
+apply from: 'gradle/publishing.gradle'
+
+repositories {
+    mavenCentral()
+}
+
+dependencies {
+    implementation project(':core')
+    testImplementation 'org.junit.jupiter:junit-jupiter:5.10.0'
+}
+
+subprojects {
+    dependencies {
+        implementation 'org.slf4j:slf4j-api:2.0.9'
+    }
+}
+
+tasks.register('bundle', Zip) {
+    from 'build/libs'
+}
+
+task hello {
+    doLast { println 'hello' }
+}
+
+tasks.named('test') {
+    useJUnitPlatform()
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Groovy),
+            [
+                ("plugin java-library".into(), None, 2, 2),
+                ("plugin org.springframework.boot".into(), None, 3, 3),
+                ("plugin jacoco".into(), None, 6, 6),
+                ("dependencies dependencies".into(), None, 13, 16),
+                ("script_block subprojects".into(), None, 18, 22),
+                (
+                    "dependencies dependencies".into(),
+                    Some("subprojects".into()),
+                    19,
+                    21
+                ),
+                ("task bundle".into(), None, 24, 26),
+                ("task hello".into(), None, 28, 30),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Groovy).imports,
+            ["gradle/publishing.gradle"]
+        );
+    }
+
+    #[test]
+    fn test_fsharp() {
+        let source = r#"namespace Acme.Store
+
+open System
+open System.Collections.Generic
+open type System.Math
+
+/// A line in the cart
+type Item = { Name: string; Price: decimal }
+
+type Shape =
+    | Circle of radius: float
+    | Square of side: float
+
+type IPriced =
+    abstract member Price: decimal
+
+type Cart(items: Item list) =
+    let mutable discount = 0m
+
+    member this.Items = items
+    member _.Total() =
+        let sum = items |> List.sumBy (fun i -> i.Price)
+        sum - discount
+    static member Empty = Cart([])
+    override this.ToString() = sprintf "%d items" items.Length
+
+type Order = { Lines: Line list }
+and Line = { Item: Item; Quantity: int }
+
+module Pricing =
+    let rec total (items: Item list) =
+        match items with
+        | [] -> 0m
+        | item :: rest -> item.Price + total rest
+
+    (* let notThis = 0 *)
+    let private round2 (x: decimal) = Math.Round(x, 2)
+
+namespace Acme.Store.Tests
+
+module CartTests =
+    let ``empty cart totals zero`` () = ()
+"#;
+        let store = || Some("Acme.Store".to_string());
+        let cart = || Some("Cart".to_string());
+        assert_eq!(
+            outline_of(source, Language::Fsharp),
+            [
+                ("namespace Acme.Store".into(), None, 1, 37),
+                ("type_definition Item".into(), store(), 8, 8),
+                ("type_definition Shape".into(), store(), 10, 12),
+                ("type_definition IPriced".into(), store(), 14, 15),
+                ("member_defn Price".into(), Some("IPriced".into()), 15, 15),
+                ("type_definition Cart".into(), store(), 17, 25),
+                ("function_or_value_defn discount".into(), cart(), 18, 18),
+                ("member_defn Items".into(), cart(), 20, 20),
+                ("member_defn Total".into(), cart(), 21, 23),
+                ("member_defn Empty".into(), cart(), 24, 24),
+                ("member_defn ToString".into(), cart(), 25, 25),
+                ("type_definition Order".into(), store(), 27, 27),
+                ("type_definition Line".into(), store(), 28, 28),
+                ("module_defn Pricing".into(), store(), 30, 37),
+                (
+                    "function_or_value_defn total".into(),
+                    Some("Pricing".into()),
+                    31,
+                    34
+                ),
+                (
+                    "function_or_value_defn round2".into(),
+                    Some("Pricing".into()),
+                    37,
+                    37
+                ),
+                ("namespace Acme.Store.Tests".into(), None, 39, 42),
+                (
+                    "module_defn CartTests".into(),
+                    Some("Acme.Store.Tests".into()),
+                    41,
+                    42
+                ),
+                (
+                    "function_or_value_defn ``empty cart totals zero``".into(),
+                    Some("CartTests".into()),
+                    42,
+                    42
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Fsharp).imports,
+            ["System", "System.Collections.Generic", "System.Math"]
+        );
+        assert_eq!(
+            outline_of("module Acme.Cart\n\nlet empty = []\n", Language::Fsharp),
+            [
+                ("named_module Acme.Cart".into(), None, 1, 3),
+                (
+                    "function_or_value_defn empty".into(),
+                    Some("Acme.Cart".into()),
+                    3,
+                    3
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nim() {
+        let source = r#"import std/[strutils, tables], os
+import ./cart as c
+from math import sqrt
+include helpers
+
+type
+  Item* = object
+    name*: string
+    price: float
+
+  Shape* = ref object of RootObj
+  Color {.pure.} = enum
+    red, green
+  Id = distinct int
+
+type Money* = distinct float
+
+proc total*(items: seq[Item]): float =
+  ## Sums the prices
+  for item in items:
+    result += item.price
+
+func double(x: int): int = x * 2
+
+method area(s: Shape): float {.base.} =
+  0.0
+
+iterator pairs*(t: Table): (string, int) =
+  discard
+
+template check*(cond: untyped) =
+  assert cond
+
+macro debug(args: varargs[untyped]): untyped =
+  result = newStmtList()
+
+proc `$`*(i: Item): string = i.name
+
+#[
+proc notThis() = discard
+]#
+
+when isMainModule:
+  proc main() =
+    echo total(@[])
+"#;
+        assert_eq!(
+            outline_of(source, Language::Nim),
+            [
+                ("type_declaration Item".into(), None, 7, 9),
+                ("type_declaration Shape".into(), None, 11, 11),
+                ("type_declaration Color".into(), None, 12, 13),
+                ("type_declaration Id".into(), None, 14, 14),
+                ("type_declaration Money".into(), None, 16, 16),
+                ("proc_declaration total".into(), None, 18, 21),
+                ("func_declaration double".into(), None, 23, 23),
+                ("method_declaration area".into(), None, 25, 26),
+                ("iterator_declaration pairs".into(), None, 28, 29),
+                ("template_declaration check".into(), None, 31, 32),
+                ("macro_declaration debug".into(), None, 34, 35),
+                ("proc_declaration `$`".into(), None, 37, 37),
+                ("proc_declaration main".into(), None, 44, 45),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Nim).imports,
+            [
+                "std/strutils",
+                "std/tables",
+                "os",
+                "./cart",
+                "math",
+                "helpers"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gleam() {
+        let source = r#"import gleam/io
+import gleam/list.{map, filter}
+import app/cart.{type Cart, Item} as c
+
+/// A shopping cart
+pub type Cart {
+  Cart(items: List(Item))
+}
+
+pub opaque type Id {
+  Id(Int)
+}
+
+type Prices =
+  List(Int)
+
+pub const limit = 10
+
+pub fn total(cart: Cart) -> Int {
+  list.fold(cart.items, 0, fn(acc, item) { acc + item.price })
+}
+
+fn helper() {
+  Nil
+}
+
+@external(erlang, "os", "cmd")
+pub fn cmd(command: String) -> String
+"#;
+        assert_eq!(
+            outline_of(source, Language::Gleam),
+            [
+                ("type_definition Cart".into(), None, 6, 8),
+                ("type_definition Id".into(), None, 10, 12),
+                ("type_alias Prices".into(), None, 14, 15),
+                ("constant limit".into(), None, 17, 17),
+                ("function total".into(), None, 19, 21),
+                ("function helper".into(), None, 23, 25),
+                ("function cmd".into(), None, 28, 28),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Gleam).imports,
+            ["gleam/io", "gleam/list", "app/cart"]
+        );
+    }
+
+    #[test]
+    fn test_cpp() {
+        let source = r#"#include <vector>
+#include "store/record.h"
+
+namespace acme {
+namespace detail {
+int helper(int x);
+}  // namespace detail
+
+template <typename T>
+class Store : public Base<T> {
+ public:
+  explicit Store(std::vector<T> items) : items_(std::move(items)) {}
+  virtual ~Store();
+  const T& get(size_t i) const;
+  static Store* Create() {
+    if (x) { return nullptr; }
+    return new Store({});
+  }
+  bool operator==(const Store& other) const;
+
+ private:
+  struct Slot { int index; };
+  std::vector<T> items_;
+  DISALLOW_COPY_AND_ASSIGN(Store);
+};
+
+enum class Mode : uint8_t { kRead, kWrite };
+
+}  // namespace acme
+
+template <typename T>
+Store<T>::~Store() {}
+
+void acme::Store::Flush() {
+  items_.clear();
+}
+"#;
+        assert_eq!(
+            outline_of(source, Language::Cpp),
+            [
+                ("namespace_definition acme".into(), None, 4, 29),
+                (
+                    "namespace_definition detail".into(),
+                    Some("acme".into()),
+                    5,
+                    7
+                ),
+                (
+                    "function_definition helper".into(),
+                    Some("detail".into()),
+                    6,
+                    6
+                ),
+                ("class_specifier Store".into(), Some("acme".into()), 10, 25),
+                (
+                    "function_definition Store".into(),
+                    Some("Store".into()),
+                    12,
+                    12
+                ),
+                (
+                    "function_definition ~Store".into(),
+                    Some("Store".into()),
+                    13,
+                    13
+                ),
+                (
+                    "function_definition get".into(),
+                    Some("Store".into()),
+                    14,
+                    14
+                ),
+                (
+                    "function_definition Create".into(),
+                    Some("Store".into()),
+                    15,
+                    18
+                ),
+                (
+                    "function_definition operator==".into(),
+                    Some("Store".into()),
+                    19,
+                    19
+                ),
+                ("struct_specifier Slot".into(), Some("Store".into()), 22, 22),
+                ("enum_specifier Mode".into(), Some("acme".into()), 27, 27),
+                (
+                    "function_definition ~Store".into(),
+                    Some("Store<T>".into()),
+                    32,
+                    32
+                ),
+                (
+                    "function_definition Flush".into(),
+                    Some("acme::Store".into()),
+                    34,
+                    36
+                ),
+            ]
+        );
+        assert_eq!(
+            extract(source, Language::Cpp).imports,
+            ["vector", "store/record.h"]
+        );
+    }
+}
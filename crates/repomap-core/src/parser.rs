@@ -0,0 +1,724 @@
+use crate::languages::{self, Language};
+use crate::latex;
+use crate::manifest;
+use crate::markdown;
+use crate::markup;
+use crate::nix;
+use crate::notebook;
+use crate::outline;
+use crate::queries::{LanguageQueries, QueryRegistry};
+use crate::rst;
+use crate::testing;
+use crate::yaml;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use smol_str::{SmolStr, format_smolstr};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use tree_sitter::{Node, Parser, Query, QueryCursor, StreamingIterator};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Symbol {
+    pub name: SmolStr,
+    pub parent: Option<SmolStr>,
+    pub line: usize,
+    pub kind: SmolStr,
+    pub end_line: usize,
+    /// Test code: a symbol in a test file, named like a test, or inside a
+    /// Rust `#[cfg(test)]` module; see [`crate::testing`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub test: bool,
+}
+
+/// A symbol kind shared across languages, for filtering and for formats with
+/// a fixed kind vocabulary. [`Symbol::kind`] keeps the raw tree-sitter node.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, ValueEnum, Debug)]
+pub enum SymbolKind {
+    #[value(alias = "functions")]
+    Function,
+    #[value(alias = "methods")]
+    Method,
+    #[value(alias = "classes")]
+    Class,
+    #[value(alias = "structs")]
+    Struct,
+    #[value(alias = "interfaces")]
+    Interface,
+    #[value(alias = "types")]
+    Type,
+    #[value(alias = "headings")]
+    Heading,
+    #[value(alias = "macros")]
+    Macro,
+    /// Test suites and cases from `describe`/`it`/`test` blocks.
+    #[value(alias = "tests")]
+    Test,
+    /// Anything the other kinds don't cover.
+    Other,
+}
+
+impl SymbolKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Function => "function",
+            Self::Method => "method",
+            Self::Class => "class",
+            Self::Struct => "struct",
+            Self::Interface => "interface",
+            Self::Type => "type",
+            Self::Heading => "heading",
+            Self::Macro => "macro",
+            Self::Test => "test",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl Symbol {
+    /// The language-neutral kind of this symbol. Functions nested in a parent
+    /// count as methods.
+    pub fn normalized_kind(&self) -> SymbolKind {
+        match self.kind.as_str() {
+            "function_item"
+            | "function_definition"
+            | "function_declaration"
+            | "method"
+            | "def"
+            | "defp"
+            | "defmacro"
+            | "defmacrop"
+            | "signature"
+            | "function"
+            | "function_signature"
+            | "short_function_definition"
+            | "function_statement"
+            | "function_signature_item" => {
+                if self.parent.is_some() {
+                    SymbolKind::Method
+                } else {
+                    SymbolKind::Function
+                }
+            }
+            "method_declaration" | "method_definition" => SymbolKind::Method,
+            "constructor_declaration"
+            | "init_declaration"
+            | "singleton_method"
+            | "constructor_signature"
+            | "class_method_definition" => SymbolKind::Method,
+            "struct_item" | "struct_specifier" | "struct_declaration" | "struct_definition" => {
+                SymbolKind::Struct
+            }
+            "class_definition" | "class_declaration" | "record_declaration" | "class_specifier"
+            | "class" | "object_declaration" | "R6Class" | "setClass" | "class_statement" => {
+                SymbolKind::Class
+            }
+            "interface_declaration" | "protocol_declaration" | "trait_item" => {
+                SymbolKind::Interface
+            }
+            "type_spec"
+            | "enum_item"
+            | "union_item"
+            | "type_item"
+            | "enum_declaration"
+            | "annotation_type_declaration"
+            | "enum_specifier"
+            | "union_specifier"
+            | "data_type"
+            | "newtype"
+            | "type_synomym"
+            | "union_declaration"
+            | "abstract_definition" => SymbolKind::Type,
+            "macro" | "macro_definition" | "macro_declaration" => SymbolKind::Macro,
+            "describe" | "context" | "suite" | "it" | "test" | "test_declaration" => {
+                SymbolKind::Test
+            }
+            kind if kind.starts_with('h') && kind[1..].parse::<usize>().is_ok() => {
+                SymbolKind::Heading
+            }
+            _ => SymbolKind::Other,
+        }
+    }
+}
+
+thread_local! {
+    // set_language is cheap; allocating a parser per file is not
+    static PARSER: RefCell<Parser> = RefCell::new(Parser::new());
+}
+
+/// Everything [`extract`] pulls out of one parse.
+#[derive(Default)]
+pub struct Extracted {
+    pub symbols: Vec<Symbol>,
+    pub imports: Vec<String>,
+    /// Distinct identifiers the file mentions, sorted; feeds the ranking.
+    pub references: Vec<SmolStr>,
+}
+
+/// Parses `source` once with this thread's parser, runs both queries for
+/// `lang` over the tree, and collects the identifiers it mentions.
+/// Languages without a grammar are outlined instead; see [`crate::outline`],
+/// and so are reStructuredText's and LaTeX's sections; see [`crate::rst`]
+/// and [`crate::latex`]. YAML, project manifests, and Nix are summarized;
+/// see [`crate::yaml`], [`crate::manifest`], and [`crate::nix`], and markup
+/// and notebooks have their embedded code extracted; see [`crate::markup`]
+/// and [`crate::notebook`].
+pub fn extract(source: &str, lang: Language) -> Extracted {
+    extract_with(source, lang, &QueryRegistry::default())
+}
+
+/// [`extract`] with the queries in `registry`.
+pub fn extract_with(source: &str, lang: Language, registry: &QueryRegistry) -> Extracted {
+    match registry.get(lang) {
+        Some(queries) => query(source, lang, queries),
+        None => match lang {
+            Language::Yaml => yaml::extract(source),
+            Language::Toml => manifest::extract_toml(source),
+            Language::Json => manifest::extract_json(source),
+            Language::Html => markup::extract_html(source, registry),
+            Language::Vue => markup::extract_vue(source, registry),
+            Language::Svelte => markup::extract_svelte(source, registry),
+            Language::Jupyter => notebook::extract(source, registry),
+            Language::Nix => nix::extract(source),
+            Language::Rst => rst::extract(source),
+            Language::Latex => latex::extract(source),
+            _ => outline::extract(source, lang),
+        },
+    }
+}
+
+fn query(source: &str, lang: Language, queries: &LanguageQueries) -> Extracted {
+    let Some(ts_lang) = languages::get_ts_language(lang) else {
+        return Extracted::default();
+    };
+    let tree = PARSER.with_borrow_mut(|parser| {
+        parser
+            .set_language(&ts_lang)
+            .expect("Error loading grammar");
+        parser.parse(source, None)
+    });
+    let Some(tree) = tree else {
+        return Extracted::default();
+    };
+
+    let mut symbols = extract_symbols(source, tree.root_node(), &queries.symbols);
+    match lang {
+        Language::Rust => {
+            testing::mark_rust_tests(source, tree.root_node(), &mut symbols);
+            mark_rust_macros(source, tree.root_node(), &mut symbols);
+        }
+        Language::Javascript | Language::Typescript | Language::Tsx => {
+            symbols.extend(testing::extract_js_tests(source, tree.root_node()));
+            symbols.sort_by_key(|sym| sym.line);
+        }
+        Language::Markdown => markdown::add_front_matter(source, &mut symbols),
+        _ => {}
+    }
+    Extracted {
+        symbols,
+        imports: match &queries.imports {
+            Some(query) => extract_imports(source, tree.root_node(), query),
+            // Kept only if the builder is asked to; see `RepoMapBuilder::doc_links`
+            None if lang == Language::Markdown => markdown::links(source),
+            None => Vec::new(),
+        },
+        references: extract_references(source, tree.root_node()),
+    }
+}
+
+pub fn extract_symbols(source: &str, root: Node, query: &Query) -> Vec<Symbol> {
+    let mut cursor = QueryCursor::new();
+
+    let mut symbols = Vec::new();
+    let source_bytes = source.as_bytes();
+
+    let mut matches = cursor.matches(query, root, source_bytes);
+
+    while let Some(m) = matches.next() {
+        let mut name = SmolStr::default();
+        let mut parent = None;
+        // The trait a Rust impl is of, shown as `Trait for Type`
+        let mut implemented = None;
+        let mut kind = SmolStr::default();
+        let mut start_line = 0;
+        let mut end_line = 0;
+
+        for capture in m.captures {
+            let capture_name = query.capture_names()[capture.index as usize];
+            let node = capture.node;
+
+            match capture_name {
+                "name" => {
+                    if let Some(n) = source.get(node.start_byte()..node.end_byte()) {
+                        name = SmolStr::new(n.trim());
+                    }
+                }
+                "parent" => {
+                    if let Some(p) = source.get(node.start_byte()..node.end_byte()) {
+                        parent = Some(SmolStr::new(p));
+                    }
+                }
+                "trait" => implemented = source.get(node.start_byte()..node.end_byte()),
+                "item" => {
+                    // Node kinds are 'static in the grammar, so this never allocates
+                    let node_kind = node.kind();
+                    kind = SmolStr::new_static(node_kind);
+                    start_line = node.start_position().row + 1;
+                    end_line = node.end_position().row + 1;
+
+                    if node_kind == "atx_heading"
+                        && let Some(raw_text) = source.get(node.start_byte()..node.end_byte())
+                    {
+                        let level = raw_text.chars().take_while(|&c| c == '#').count();
+                        kind = format_smolstr!("h{}", level);
+                        name = SmolStr::new(raw_text.trim_start_matches('#').trim());
+                        // The node runs on to the start of the next line
+                        end_line = start_line + raw_text.trim_end().matches('\n').count();
+                    } else if node_kind == "setext_heading"
+                        && let Some(raw_text) = source.get(node.start_byte()..node.end_byte())
+                    {
+                        // The title's lines, then the `===` or `---` under them
+                        let raw_text = raw_text.trim_end();
+                        let (title, underline) =
+                            raw_text.rsplit_once('\n').unwrap_or((raw_text, ""));
+                        let level = if underline.trim_start().starts_with('=') {
+                            1
+                        } else {
+                            2
+                        };
+                        kind = format_smolstr!("h{}", level);
+                        name = SmolStr::new(title.split_whitespace().collect::<Vec<_>>().join(" "));
+                        end_line = start_line + raw_text.matches('\n').count();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let (Some(implemented), Some(ty)) = (implemented, &parent) {
+            parent = Some(format_smolstr!("{implemented} for {ty}"));
+        }
+
+        if !name.is_empty() && start_line > 0 {
+            let is_duplicate = symbols
+                .iter()
+                .any(|s: &Symbol| s.line == start_line && s.parent.is_some() && parent.is_none());
+            if !is_duplicate {
+                symbols.push(Symbol {
+                    name,
+                    kind,
+                    parent,
+                    line: start_line,
+                    end_line,
+                    test: false,
+                });
+            }
+        }
+    }
+
+    symbols
+}
+
+/// Gives Rust macros the kind `macro`: `macro_rules!` definitions, and
+/// functions marked `#[proc_macro]`, `#[proc_macro_derive]`, or
+/// `#[proc_macro_attribute]`.
+fn mark_rust_macros(source: &str, root: Node, symbols: &mut [Symbol]) {
+    let mut proc_macros = HashSet::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "function_item" && is_proc_macro(source, node) {
+            proc_macros.insert(node.start_position().row + 1);
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+
+    for sym in symbols {
+        let proc_macro = sym.kind == "function_item" && proc_macros.contains(&sym.line);
+        if proc_macro || sym.kind == "macro_definition" {
+            sym.kind = SmolStr::new_static("macro");
+        }
+    }
+}
+
+fn is_proc_macro(source: &str, node: Node) -> bool {
+    let mut sibling = node.prev_named_sibling();
+    while let Some(attr) = sibling.filter(|s| s.kind() == "attribute_item") {
+        let text: String = source[attr.byte_range()]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if text.starts_with("#[proc_macro") {
+            return true;
+        }
+        sibling = attr.prev_named_sibling();
+    }
+    false
+}
+
+pub fn extract_imports(source: &str, root: Node, query: &Query) -> Vec<String> {
+    let mut cursor = QueryCursor::new();
+    let source_bytes = source.as_bytes();
+    let mut imports = Vec::new();
+
+    let mut matches = cursor.matches(query, root, source_bytes);
+
+    while let Some(m) = matches.next() {
+        for capture in m.captures {
+            let capture_name = query.capture_names()[capture.index as usize];
+            if capture_name == "import"
+                && let Some(text) = source.get(capture.node.start_byte()..capture.node.end_byte())
+            {
+                // Clean up the import string (remove quotes, trim)
+                let cleaned = text.trim().trim_matches('"').trim_matches('\'').to_string();
+                if !cleaned.is_empty() && !imports.contains(&cleaned) {
+                    imports.push(cleaned);
+                }
+            }
+        }
+    }
+
+    imports
+}
+
+/// Every distinct identifier-like leaf (`identifier`, `type_identifier`,
+/// `field_identifier`, ...) under `root`.
+pub fn extract_references(source: &str, root: Node) -> Vec<SmolStr> {
+    let mut seen = HashSet::new();
+    let mut cursor = root.walk();
+    'walk: loop {
+        let node = cursor.node();
+        if node.child_count() == 0
+            && node.kind().ends_with("identifier")
+            && let Some(text) = source.get(node.start_byte()..node.end_byte())
+        {
+            seen.insert(text);
+        }
+
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        while cursor.goto_parent() {
+            if cursor.goto_next_sibling() {
+                continue 'walk;
+            }
+        }
+        break;
+    }
+
+    let mut references: Vec<SmolStr> = seen.into_iter().map(SmolStr::new).collect();
+    references.sort_unstable();
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::display_name;
+    use tree_sitter::Tree;
+
+    fn parse(code: &str, lang: &tree_sitter::Language, query: &str) -> (Tree, Query) {
+        let mut parser = Parser::new();
+        parser.set_language(lang).unwrap();
+        (
+            parser.parse(code, None).unwrap(),
+            Query::new(lang, query).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_extract_parses_once_for_both_queries() {
+        let extracted = extract("use std::fs;\nfn load() { fs::read(PATH) }", Language::Rust);
+        assert_eq!(extracted.symbols[0].name, "load");
+        assert_eq!(extracted.imports, vec!["std::fs"]);
+        assert_eq!(
+            extracted.references,
+            vec!["PATH", "fs", "load", "read", "std"]
+        );
+    }
+
+    #[test]
+    fn test_rust_symbol_extraction() {
+        let code = "struct MyStruct { field: i32 } fn my_func() {}";
+        let lang = tree_sitter_rust::LANGUAGE.into();
+        let query = "(function_item name: (identifier) @name) @item (struct_item name: (type_identifier) @name) @item";
+
+        let (tree, query) = parse(code, &lang, query);
+        let symbols = extract_symbols(code, tree.root_node(), &query);
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "MyStruct");
+        assert_eq!(symbols[1].name, "my_func");
+    }
+
+    #[test]
+    fn test_rust_items() {
+        let source = "mod store;
+
+pub const LIMIT: usize = 10;
+static NAME: &str = \"acme\";
+
+pub enum Mode {
+    Fast,
+}
+
+union Bits {
+    word: u32,
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+pub trait Store {
+    fn get(&self, key: &str) -> Option<String>;
+
+    fn has(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+}
+";
+        let symbols = extract(source, Language::Rust).symbols;
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|sym| (sym.kind.as_str(), display_name(sym), sym.line))
+                .collect::<Vec<_>>(),
+            [
+                ("mod_item", "store".to_string(), 1),
+                ("const_item", "LIMIT".to_string(), 3),
+                ("static_item", "NAME".to_string(), 4),
+                ("enum_item", "Mode".to_string(), 6),
+                ("union_item", "Bits".to_string(), 10),
+                ("type_item", "Result".to_string(), 14),
+                ("trait_item", "Store".to_string(), 16),
+                ("function_signature_item", "Store > get".to_string(), 17),
+                ("function_item", "Store > has".to_string(), 19),
+            ]
+        );
+        assert_eq!(
+            symbols
+                .iter()
+                .map(Symbol::normalized_kind)
+                .collect::<Vec<_>>()[3..],
+            [
+                SymbolKind::Type,
+                SymbolKind::Type,
+                SymbolKind::Type,
+                SymbolKind::Interface,
+                SymbolKind::Method,
+                SymbolKind::Method,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_trait_impls() {
+        let source = "impl Config {
+    fn new() -> Self {
+        Config
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T> From<T> for Wrapper<T> {
+    fn from(value: T) -> Self {
+        Wrapper(value)
+    }
+}
+";
+        assert_eq!(
+            extract(source, Language::Rust)
+                .symbols
+                .iter()
+                .map(display_name)
+                .collect::<Vec<_>>(),
+            [
+                "Config > new",
+                "fmt::Display for Config > fmt",
+                "From<T> for Wrapper<T> > from",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_macros() {
+        let source = "macro_rules! hashmap {
+    () => {};
+}
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    input
+}
+
+/// Times the function it's on.
+#[proc_macro_attribute]
+pub fn timed(attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+pub fn helper() {}
+";
+        let symbols = extract(source, Language::Rust).symbols;
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|sym| (sym.kind.as_str(), sym.name.as_str(), sym.line))
+                .collect::<Vec<_>>(),
+            [
+                ("macro", "hashmap", 1),
+                ("macro", "derive_builder", 6),
+                ("macro", "timed", 12),
+                ("function_item", "helper", 16),
+            ]
+        );
+        assert_eq!(symbols[0].normalized_kind(), SymbolKind::Macro);
+    }
+
+    #[test]
+    fn test_rust_import_extraction() {
+        let code = "use std::path::Path;\nuse crate::parser;\nfn main() {}";
+        let lang = tree_sitter_rust::LANGUAGE.into();
+        let query = "(use_declaration argument: (_) @import)";
+
+        let (tree, query) = parse(code, &lang, query);
+        let imports = extract_imports(code, tree.root_node(), &query);
+
+        assert_eq!(imports.len(), 2);
+        assert!(imports.contains(&"std::path::Path".to_string()));
+        assert!(imports.contains(&"crate::parser".to_string()));
+    }
+
+    #[test]
+    fn test_typescript_import_extraction() {
+        let code = "import { foo } from './foo';\nimport React from 'react';";
+        let lang = tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into();
+        let query = "(import_statement source: (string) @import)";
+
+        let (tree, query) = parse(code, &lang, query);
+        let imports = extract_imports(code, tree.root_node(), &query);
+
+        assert_eq!(imports.len(), 2);
+        assert!(imports.contains(&"./foo".to_string()));
+        assert!(imports.contains(&"react".to_string()));
+    }
+
+    #[test]
+    fn test_markdown_setext_headings() {
+        let source = "Acme Store
+==========
+
+Intro paragraph.
+
+Getting
+Started
+-------
+
+## Usage
+
+---
+";
+        let symbols = extract(source, Language::Markdown).symbols;
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|sym| (format!("{} {}", sym.kind, sym.name), sym.line))
+                .collect::<Vec<_>>(),
+            [
+                ("h1 Acme Store".to_string(), 1),
+                ("h2 Getting Started".to_string(), 6),
+                ("h2 Usage".to_string(), 10),
+            ]
+        );
+        assert_eq!((symbols[0].end_line, symbols[1].end_line), (2, 8));
+    }
+
+    #[test]
+    fn test_markdown_front_matter() {
+        let source = r#"---
+title: "Getting Started: A Tour"
+description: >
+  How to install Acme
+  and map a first repo.
+tags: [rust, "cli"]
+categories:
+  - guides
+---
+
+# Installation
+"#;
+        let symbols = extract(source, Language::Markdown).symbols;
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|sym| (format!("{} {}", sym.kind, sym.name), sym.line, sym.end_line))
+                .collect::<Vec<_>>(),
+            [
+                ("title Getting Started: A Tour".to_string(), 2, 2),
+                (
+                    "description How to install Acme and map a first repo.".to_string(),
+                    3,
+                    5
+                ),
+                ("tag rust".to_string(), 6, 6),
+                ("tag cli".to_string(), 6, 6),
+                ("h1 Installation".to_string(), 11, 11),
+            ]
+        );
+
+        let listed = extract("---\ntags:\n  - rust\n  - cli\n---\n", Language::Markdown);
+        assert_eq!(
+            listed
+                .symbols
+                .iter()
+                .map(|sym| (sym.name.as_str(), sym.line))
+                .collect::<Vec<_>>(),
+            [("rust", 3), ("cli", 4)]
+        );
+    }
+
+    #[test]
+    fn test_outlined_kinds_are_normalized() {
+        let kinds = |source: &str, lang| -> Vec<SymbolKind> {
+            extract(source, lang)
+                .symbols
+                .iter()
+                .map(Symbol::normalized_kind)
+                .collect()
+        };
+
+        assert_eq!(
+            kinds(
+                "class Cart
+  def total
+  end
+end
+",
+                Language::Ruby
+            ),
+            [SymbolKind::Class, SymbolKind::Method]
+        );
+        assert_eq!(
+            kinds(
+                "defmodule Cart do
+  def total(c), do: 0
+end
+",
+                Language::Elixir
+            ),
+            [SymbolKind::Other, SymbolKind::Method]
+        );
+        assert_eq!(
+            kinds(
+                "struct Item {};
+enum class Color { Red };
+",
+                Language::Cpp
+            ),
+            [SymbolKind::Struct, SymbolKind::Type]
+        );
+    }
+}
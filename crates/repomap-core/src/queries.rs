@@ -0,0 +1,335 @@
+//! The tree-sitter queries symbols and imports are extracted with, and
+//! [`QueryRegistry`], which layers user-supplied queries over them.
+
+use crate::languages::{self, Language};
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, OnceLock};
+use tree_sitter::{Query, QueryError};
+
+/// The built-in `@name`/`@item`/`@parent` query for `lang`, if it has a
+/// tree-sitter grammar.
+pub fn builtin_symbol_query(lang: Language) -> Option<&'static str> {
+    let query = match lang {
+        Language::Rust => {
+            "(function_item name: (identifier) @name) @item
+             (struct_item name: (type_identifier) @name) @item
+             (enum_item name: (type_identifier) @name) @item
+             (union_item name: (type_identifier) @name) @item
+             (type_item name: (type_identifier) @name) @item
+             (trait_item name: (type_identifier) @name) @item
+             (const_item name: (identifier) @name) @item
+             (static_item name: (identifier) @name) @item
+             (mod_item name: (identifier) @name) @item
+             (macro_definition name: (identifier) @name) @item
+             (impl_item
+                !trait
+                type: (_) @parent
+                body: (declaration_list (function_item name: (identifier) @name) @item))
+             (impl_item
+                trait: (_) @trait
+                type: (_) @parent
+                body: (declaration_list (function_item name: (identifier) @name) @item))
+             (trait_item
+                name: (type_identifier) @parent
+                body: (declaration_list
+                   [(function_item name: (identifier) @name)
+                    (function_signature_item name: (identifier) @name)] @item))"
+        }
+        Language::Python => {
+            "(function_definition name: (identifier) @name) @item
+             (class_definition name: (identifier) @name) @item
+             (class_definition
+                name: (identifier) @parent
+                body: (block (function_definition name: (identifier) @name) @item))"
+        }
+        Language::Go => {
+            "(function_declaration name: (identifier) @name) @item
+             (type_spec name: (type_identifier) @name) @item
+             (method_declaration
+                receiver: (parameter_list (parameter_declaration type: (_) @parent))
+                name: (field_identifier) @name) @item"
+        }
+        Language::Javascript => {
+            "(function_declaration name: (identifier) @name) @item
+             (class_declaration name: (identifier) @name) @item
+             (class_declaration
+                name: (identifier) @parent
+                body: (class_body (method_definition name: (property_identifier) @name) @item))"
+        }
+        Language::Typescript | Language::Tsx => {
+            "(function_declaration name: (identifier) @name) @item
+             (class_declaration name: (type_identifier) @name) @item
+             (interface_declaration name: (type_identifier) @name) @item
+             (class_declaration
+                name: (type_identifier) @parent
+                body: (class_body (method_definition name: (property_identifier) @name) @item))"
+        }
+        Language::Markdown => {
+            "(atx_heading) @item
+             (setext_heading) @item"
+        }
+        // No grammar; see `languages::get_ts_language`
+        _ => return None,
+    };
+    Some(query)
+}
+
+/// The built-in `@import` query for `lang`, if it has imports.
+pub fn builtin_import_query(lang: Language) -> Option<&'static str> {
+    match lang {
+        Language::Rust => Some("(use_declaration argument: (_) @import)"),
+        Language::Python => Some(
+            "(import_statement name: (dotted_name) @import)
+             (import_from_statement module_name: (dotted_name) @import)
+             (import_from_statement module_name: (relative_import) @import)",
+        ),
+        Language::Go => Some("(import_spec path: (interpreted_string_literal) @import)"),
+        Language::Javascript | Language::Typescript | Language::Tsx => Some(
+            "(import_statement source: (string) @import)
+             (export_statement source: (string) @import)",
+        ),
+        _ => None,
+    }
+}
+
+/// The symbol and import queries for one language, compiled once.
+pub struct LanguageQueries {
+    pub symbols: Query,
+    pub imports: Option<Query>,
+}
+
+// One slot per language, filled the first time a file of that language is
+// parsed, so runs only pay to compile the queries they use
+static BUILTIN: LazyLock<HashMap<Language, OnceLock<LanguageQueries>>> = LazyLock::new(|| {
+    Language::value_variants()
+        .iter()
+        .map(|&lang| (lang, OnceLock::new()))
+        .collect()
+});
+
+/// The compiled built-in queries for `lang`, if it has a tree-sitter
+/// grammar.
+pub fn builtin(lang: Language) -> Option<&'static LanguageQueries> {
+    let symbols = builtin_symbol_query(lang)?;
+    Some(BUILTIN[&lang].get_or_init(|| {
+        let ts_lang = languages::get_ts_language(lang).expect("queried languages have a grammar");
+        LanguageQueries {
+            symbols: Query::new(&ts_lang, symbols).expect("built-in symbol query is valid"),
+            imports: builtin_import_query(lang)
+                .map(|q| Query::new(&ts_lang, q).expect("built-in import query is valid")),
+        }
+    }))
+}
+
+/// How a user query combines with the one already registered.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum QueryMode {
+    /// Adds patterns after the existing ones.
+    Extend,
+    /// Drops the existing patterns.
+    Replace,
+}
+
+/// Per-language query overrides. Languages without one use the built-in
+/// queries. A user query is matched for symbols with the same
+/// `@item`/`@name`/`@parent` captures the built-ins use, and for imports if
+/// it has an `@import` capture.
+#[derive(Clone, Default)]
+pub struct QueryRegistry {
+    custom: HashMap<Language, Arc<Custom>>,
+}
+
+struct Custom {
+    symbols: String,
+    imports: Option<String>,
+    compiled: LanguageQueries,
+}
+
+impl fmt::Debug for QueryRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut langs: Vec<_> = self.custom.keys().map(|lang| lang.to_string()).collect();
+        langs.sort();
+        f.debug_struct("QueryRegistry")
+            .field("custom", &langs)
+            .finish()
+    }
+}
+
+impl QueryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extends or replaces the queries for `lang` with `text`. Fails if the
+    /// query does not compile against the language's grammar.
+    pub fn add(&mut self, lang: Language, text: &str, mode: QueryMode) -> Result<()> {
+        let ts_lang = grammar(lang)?;
+        let custom = self
+            .combine(lang, &ts_lang, text, mode)
+            .with_context(|| format!("invalid {} query", lang))?;
+        self.custom.insert(lang, Arc::new(custom));
+        Ok(())
+    }
+
+    /// [`Self::add`] with the query read from a `.scm` file.
+    pub fn load(&mut self, lang: Language, path: &Path, mode: QueryMode) -> Result<()> {
+        let ts_lang = grammar(lang)?;
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read query file {}", path.display()))?;
+        let custom = self
+            .combine(lang, &ts_lang, &text, mode)
+            .with_context(|| format!("invalid {} query in {}", lang, path.display()))?;
+        self.custom.insert(lang, Arc::new(custom));
+        Ok(())
+    }
+
+    fn combine(
+        &self,
+        lang: Language,
+        ts_lang: &tree_sitter::Language,
+        text: &str,
+        mode: QueryMode,
+    ) -> Result<Custom, QueryError> {
+        let has_imports = Query::new(ts_lang, text)?
+            .capture_names()
+            .contains(&"import");
+
+        let (symbols, imports) = match mode {
+            QueryMode::Extend => (
+                format!("{}\n{}", self.symbol_query(lang).unwrap_or_default(), text),
+                match (self.import_query(lang), has_imports) {
+                    (Some(imports), true) => Some(format!("{}\n{}", imports, text)),
+                    (None, true) => Some(text.to_string()),
+                    (imports, false) => imports.map(str::to_string),
+                },
+            ),
+            QueryMode::Replace => (text.to_string(), has_imports.then(|| text.to_string())),
+        };
+        let compiled = LanguageQueries {
+            symbols: Query::new(ts_lang, &symbols)?,
+            imports: imports
+                .as_deref()
+                .map(|q| Query::new(ts_lang, q))
+                .transpose()?,
+        };
+        Ok(Custom {
+            symbols,
+            imports,
+            compiled,
+        })
+    }
+
+    /// Whether any language has a user query.
+    pub fn is_empty(&self) -> bool {
+        self.custom.is_empty()
+    }
+
+    /// The queries in effect for `lang`, if it has a tree-sitter grammar.
+    pub fn get(&self, lang: Language) -> Option<&LanguageQueries> {
+        match self.custom.get(&lang) {
+            Some(custom) => Some(&custom.compiled),
+            None => builtin(lang),
+        }
+    }
+
+    /// The source of the symbol query in effect for `lang`.
+    pub fn symbol_query(&self, lang: Language) -> Option<&str> {
+        match self.custom.get(&lang) {
+            Some(custom) => Some(&custom.symbols),
+            None => builtin_symbol_query(lang),
+        }
+    }
+
+    /// The source of the import query in effect for `lang`.
+    pub fn import_query(&self, lang: Language) -> Option<&str> {
+        match self.custom.get(&lang) {
+            Some(custom) => custom.imports.as_deref(),
+            None => builtin_import_query(lang),
+        }
+    }
+}
+
+/// The grammar a query for `lang` compiles against.
+fn grammar(lang: Language) -> Result<tree_sitter::Language> {
+    match languages::get_ts_language(lang) {
+        Some(ts_lang) => Ok(ts_lang),
+        None => bail!("{} has no tree-sitter grammar to query", lang),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    const FIELDS: &str = "(field_declaration name: (field_identifier) @name) @item";
+
+    fn names(registry: &QueryRegistry, source: &str) -> Vec<String> {
+        parser::extract_with(source, Language::Rust, registry)
+            .symbols
+            .into_iter()
+            .map(|sym| sym.name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_queries_compile() {
+        for &lang in Language::value_variants() {
+            builtin(lang);
+        }
+    }
+
+    #[test]
+    fn test_extend_and_replace() {
+        let source = "use std::fs;\nstruct Store {\n    items: Vec<u32>,\n}\nfn load() {}\n";
+        let mut registry = QueryRegistry::new();
+        assert_eq!(names(&registry, source), ["Store", "load"]);
+
+        registry
+            .add(Language::Rust, FIELDS, QueryMode::Extend)
+            .unwrap();
+        assert_eq!(names(&registry, source), ["Store", "items", "load"]);
+        assert!(
+            registry
+                .symbol_query(Language::Rust)
+                .unwrap()
+                .ends_with(FIELDS)
+        );
+        assert_eq!(
+            parser::extract_with(source, Language::Rust, &registry).imports,
+            ["std::fs"]
+        );
+
+        registry
+            .add(Language::Rust, FIELDS, QueryMode::Replace)
+            .unwrap();
+        assert_eq!(names(&registry, source), ["items"]);
+        assert!(registry.import_query(Language::Rust).is_none());
+        // Other languages keep the built-ins
+        assert_eq!(
+            registry.symbol_query(Language::Go),
+            builtin_symbol_query(Language::Go)
+        );
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        let err = QueryRegistry::new()
+            .add(Language::Rust, "(no_such_node) @item", QueryMode::Extend)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "invalid rust query");
+        let err = QueryRegistry::new()
+            .add(
+                Language::Java,
+                "(class_declaration) @item",
+                QueryMode::Extend,
+            )
+            .unwrap_err();
+        assert_eq!(err.to_string(), "java has no tree-sitter grammar to query");
+    }
+}
@@ -0,0 +1,206 @@
+use crate::formatter::FileSymbols;
+use crate::graph::{self, DependencyGraph};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-10;
+
+/// Importance multipliers for files in play. Each applies to the files at or
+/// under its path; a file under several takes the largest.
+#[derive(Clone, Debug, Default)]
+pub struct Weights(Vec<(PathBuf, f64)>);
+
+impl Weights {
+    pub fn add(&mut self, path: impl AsRef<Path>, weight: f64) {
+        self.0.push((graph::normalize(path.as_ref()), weight));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The multiplier for `path`, 1 when no weight covers it.
+    pub fn weight(&self, path: &str) -> f64 {
+        let path = graph::normalize(Path::new(path));
+        self.0
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix))
+            .map(|&(_, weight)| weight)
+            .reduce(f64::max)
+            .unwrap_or(1.0)
+    }
+}
+
+/// Scores each file by PageRank over a graph where a file points at the files
+/// it imports and at the files defining the identifiers it mentions, so the
+/// files everything else leans on score highest. `weights` bias the random
+/// jumps towards the files in play, which also lifts what they depend on.
+/// Scores sum to 1 and line up with `files`, mapped from `root`.
+pub fn rank_files(root: &str, files: &[FileSymbols], weights: &Weights) -> Vec<f64> {
+    let bias: Vec<f64> = files.iter().map(|f| weights.weight(&f.path)).collect();
+    pagerank(&reference_graph(root, files), &bias)
+}
+
+/// `edges[a][b]` is how strongly file `a` depends on file `b`. Each import
+/// counts 1; each mentioned identifier counts 1 split across the files that
+/// define it, so names defined everywhere (`new`, `run`) carry little weight.
+fn reference_graph(root: &str, files: &[FileSymbols]) -> Vec<HashMap<usize, f64>> {
+    let mut edges = vec![HashMap::new(); files.len()];
+
+    let imports = DependencyGraph::build(root, files);
+    for edge in imports.graph.raw_edges() {
+        let from = imports.graph[edge.source()];
+        let to = imports.graph[edge.target()];
+        *edges[from].entry(to).or_insert(0.0) += 1.0;
+    }
+
+    let mut definitions: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        for sym in &file.symbols {
+            let defined_in = definitions.entry(sym.name.as_str()).or_default();
+            if defined_in.last() != Some(&i) {
+                defined_in.push(i);
+            }
+        }
+    }
+
+    for (from, file) in files.iter().enumerate() {
+        for name in &file.references {
+            let Some(defined_in) = definitions.get(name.as_str()) else {
+                continue;
+            };
+            let weight = 1.0 / defined_in.len() as f64;
+            for &to in defined_in.iter().filter(|&&to| to != from) {
+                *edges[from].entry(to).or_insert(0.0) += weight;
+            }
+        }
+    }
+
+    edges
+}
+
+/// Weighted, personalized PageRank by power iteration. Random jumps land on
+/// each file in proportion to `bias`, and files with no outgoing edges spread
+/// their score the same way.
+fn pagerank(edges: &[HashMap<usize, f64>], bias: &[f64]) -> Vec<f64> {
+    let n = edges.len();
+    let bias_total: f64 = bias.iter().sum();
+    if n == 0 || bias_total <= 0.0 {
+        return vec![1.0 / n as f64; n];
+    }
+    let jump: Vec<f64> = bias.iter().map(|b| b / bias_total).collect();
+    let out_weight: Vec<f64> = edges.iter().map(|e| e.values().sum()).collect();
+
+    let mut ranks = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n)
+            .filter(|&i| out_weight[i] == 0.0)
+            .map(|i| ranks[i])
+            .sum();
+        let mut next: Vec<f64> = jump
+            .iter()
+            .map(|j| j * (1.0 - DAMPING + DAMPING * dangling))
+            .collect();
+        for (from, targets) in edges.iter().enumerate() {
+            for (&to, &weight) in targets {
+                next[to] += DAMPING * ranks[from] * weight / out_weight[from];
+            }
+        }
+
+        let delta: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if delta < TOLERANCE {
+            break;
+        }
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapOutput;
+
+    #[test]
+    fn test_shared_definitions_rank_highest() {
+        let map = MapOutput::from_sources(
+            ".",
+            [
+                ("a.py", "def handler():\n    return Config()\n"),
+                ("b.py", "def other():\n    return Config().load()\n"),
+                (
+                    "config.py",
+                    "class Config:\n    def load(self):\n        pass\n",
+                ),
+                ("lonely.py", "def alone():\n    pass\n"),
+            ],
+        );
+
+        let ranks = rank_files(&map.root, map.files(), &Weights::default());
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let best = (0..ranks.len())
+            .max_by(|&a, &b| ranks[a].total_cmp(&ranks[b]))
+            .unwrap();
+        assert_eq!(map.files()[best].path, "config.py");
+        assert!(ranks[0] < ranks[2] && ranks[3] < ranks[2]);
+    }
+
+    #[test]
+    fn test_keep_top_files_states_cutoff() {
+        let mut map = MapOutput::from_sources(
+            ".",
+            [
+                ("a.py", "def handler():\n    return Config()\n"),
+                ("b.py", "def other():\n    return Config().load()\n"),
+                (
+                    "config.py",
+                    "class Config:\n    def load(self):\n        pass\n",
+                ),
+            ],
+        );
+
+        assert_eq!(map.keep_top_files(5), 0);
+        assert_eq!(map.keep_top_files(1), 2);
+        assert_eq!(map.files()[0].path, "config.py");
+        assert_eq!(map.stats.file_count, 1);
+        assert!(map.to_markdown(false).contains(
+            "**Files:** 1\n\n> Only the 1 most important of 3 files are mapped (--max-files)."
+        ));
+    }
+
+    #[test]
+    fn test_pagerank_without_edges_is_uniform() {
+        let ranks = pagerank(&[HashMap::new(), HashMap::new()], &[1.0, 1.0]);
+        assert!(ranks.iter().all(|r| (r - 0.5).abs() < 1e-9));
+        assert!(pagerank(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_weights_favor_files_in_play_and_their_dependencies() {
+        let map = MapOutput::from_sources(
+            ".",
+            [
+                ("src/a.py", "def task():\n    return helper()\n"),
+                ("src/util.py", "def helper():\n    pass\n"),
+                ("src/b.py", "def other():\n    return Config()\n"),
+                ("src/config.py", "class Config:\n    pass\n"),
+            ],
+        );
+        let unweighted = rank_files(&map.root, map.files(), &Weights::default());
+
+        let mut weights = Weights::default();
+        weights.add("./src/a.py", 5.0);
+        assert_eq!(weights.weight("src/a.py"), 5.0);
+        assert_eq!(weights.weight("src/b.py"), 1.0);
+        let ranks = rank_files(&map.root, map.files(), &weights);
+        assert!(ranks[0] > unweighted[0]);
+        assert!(ranks[1] > unweighted[1]);
+        assert!(ranks[1] > ranks[3]);
+
+        let mut by_dir = Weights::default();
+        by_dir.add("src", 2.0);
+        assert_eq!(by_dir.weight("./src/config.py"), 2.0);
+    }
+}
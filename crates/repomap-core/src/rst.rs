@@ -0,0 +1,142 @@
+//! Section titles of reStructuredText documents, like a Sphinx project's
+//! docs, outlined the way Markdown headings are. A title is underlined, and
+//! maybe overlined too, with one punctuation character repeated; its level
+//! is not fixed by the character but by the order each style first appears
+//! in, so the first is `h1`, the next new one `h2`, and so on.
+
+use crate::parser::{Extracted, Symbol};
+use smol_str::{SmolStr, format_smolstr};
+
+/// The characters an adornment line may repeat.
+const ADORNMENT: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// An adornment style: its character, and whether the title is overlined.
+type Style = (char, bool);
+
+/// Outlines the section titles in `source`.
+pub fn extract(source: &str) -> Extracted {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut styles: Vec<Style> = Vec::new();
+    let mut symbols = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((name, style, end)) = title(&lines, i) else {
+            i += 1;
+            continue;
+        };
+        let level = match styles.iter().position(|&seen| seen == style) {
+            Some(at) => at + 1,
+            None => {
+                styles.push(style);
+                styles.len()
+            }
+        };
+        symbols.push(Symbol {
+            name: SmolStr::new(name),
+            parent: None,
+            line: i + 1,
+            kind: format_smolstr!("h{level}"),
+            end_line: end + 1,
+            test: false,
+        });
+        i = end + 1;
+    }
+    Extracted {
+        symbols,
+        ..Extracted::default()
+    }
+}
+
+/// The section title starting at line `i`, after a blank line, with its
+/// style and the line of its underline. Indented lines, like those of a
+/// literal block, are never titles, though an overlined title may be inset.
+fn title<'a>(lines: &[&'a str], i: usize) -> Option<(&'a str, Style, usize)> {
+    if i > 0 && !lines[i - 1].trim().is_empty() {
+        return None;
+    }
+    let (text, over, under) = match adornment(lines[i]) {
+        Some(over) => (lines.get(i + 1)?.trim(), Some(over), i + 2),
+        None if !lines[i].starts_with(char::is_whitespace) => (lines[i].trim_end(), None, i + 1),
+        None => return None,
+    };
+    let (c, width) = adornment(lines.get(under)?)?;
+    // Shorter than the title, a short line like `--` is just text
+    let long_enough = width >= text.chars().count() || width >= 4;
+    if text.is_empty() || !long_enough || over.is_some_and(|(over, _)| over != c) {
+        return None;
+    }
+    Some((text, (c, over.is_some()), under))
+}
+
+/// The character `line` repeats and how many times, if it is an adornment.
+fn adornment(line: &str) -> Option<(char, usize)> {
+    let line = line.trim_end();
+    let c = line.chars().next().filter(|&c| ADORNMENT.contains(c))?;
+    let width = line.chars().count();
+    (width >= 2 && line.chars().all(|next| next == c)).then_some((c, width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles_of(source: &str) -> Vec<(String, usize, usize)> {
+        extract(source)
+            .symbols
+            .into_iter()
+            .map(|sym| (format!("{} {}", sym.kind, sym.name), sym.line, sym.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_section_titles() {
+        let source = r#"==============
+ Acme Store
+==============
+
+An introduction.
+
+Installation
+============
+
+Run ``pip install acme``::
+
+    Not a title
+    ===========
+
+Usage
+-----
+
+Configuration
+~~~~~~~~~~~~~
+
+API
+===
+
+Short
+--
+
+----------
+
+Not a title either, after a transition
+"#;
+        assert_eq!(
+            titles_of(source),
+            [
+                ("h1 Acme Store".into(), 1, 3),
+                ("h2 Installation".into(), 7, 8),
+                ("h3 Usage".into(), 15, 16),
+                ("h4 Configuration".into(), 18, 19),
+                ("h2 API".into(), 21, 22),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        // A line of text right before an adornment continues a paragraph
+        assert!(titles_of("Some text\nmore text\n---------\n").is_empty());
+        assert!(titles_of("=====\nMismatched\n-----------\n").is_empty());
+        assert_eq!(titles_of("Title\n=====\n"), [("h1 Title".into(), 1, 2)]);
+    }
+}
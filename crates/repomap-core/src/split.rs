@@ -0,0 +1,242 @@
+use crate::formatter::{self, FileSymbols, RepoStats};
+use crate::parser::Symbol;
+use crate::tokens::Tokenizer;
+use std::io::{self, Write};
+
+/// Splits the map into parts whose Markdown, header included, each fits in
+/// `max_tokens`. Files stay whole where they fit; a file too large for any
+/// part is spread over consecutive parts as `path (continued)` sections.
+pub fn split_by_tokens(root: &str, stats: &RepoStats, max_tokens: usize) -> Vec<RepoStats> {
+    let tokenizer = stats.tokenizer;
+    let mut header = Vec::new();
+    write_part_header(&mut header, root, stats.file_count, 999, 999)
+        .expect("writing to a Vec cannot fail");
+    let available = max_tokens.saturating_sub(tokenizer.count(&String::from_utf8_lossy(&header)));
+
+    let mut parts: Vec<Vec<FileSymbols>> = Vec::new();
+    let mut current = Vec::new();
+    let mut used = 0;
+    let mut place = |section: FileSymbols, cost: usize| {
+        if used + cost > available && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+            used = 0;
+        }
+        current.push(section);
+        used += cost;
+    };
+    for file in &stats.files {
+        let cost = stats.file_tokens(file);
+        if cost <= available {
+            place(file.clone(), cost);
+        } else {
+            for chunk in split_file(file, available, tokenizer) {
+                let cost = tokenizer.count(&chunk.to_markdown());
+                place(chunk, cost);
+            }
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+
+    // Sections were measured one at a time, and tokens can merge across the
+    // boundary between them, so shift any overflow into the next part
+    let mut i = 0;
+    while i < parts.len() {
+        while parts[i].len() > 1 && part_tokens(root, &parts[i], tokenizer) > max_tokens {
+            let last = parts[i].pop().expect("part has more than one section");
+            if i + 1 == parts.len() {
+                parts.push(Vec::new());
+            }
+            parts[i + 1].insert(0, last);
+        }
+        i += 1;
+    }
+
+    let mut parts: Vec<RepoStats> = parts
+        .into_iter()
+        .map(|files| {
+            let mut part = RepoStats::with_tokenizer(tokenizer);
+            part.weights = stats.weights.clone();
+            for file in files {
+                part.add_file(file);
+            }
+            part
+        })
+        .collect();
+    // Collapsed directories and skipped files are one line each, so they
+    // close the map
+    if let Some(last) = parts.last_mut() {
+        last.collapsed_dirs = stats.collapsed_dirs.clone();
+        last.skipped = stats.skipped.clone();
+    }
+    parts
+}
+
+/// Writes part `index` (1-based) of `total` as a standalone Markdown map.
+pub fn write_part(
+    writer: &mut impl Write,
+    root: &str,
+    part: &RepoStats,
+    index: usize,
+    total: usize,
+) -> io::Result<()> {
+    write_part_header(writer, root, part.file_count, index, total)?;
+    for file in &part.files {
+        file.write_markdown(writer)?;
+    }
+    formatter::write_appendix(writer, part)
+}
+
+fn write_part_header(
+    writer: &mut impl Write,
+    root: &str,
+    file_count: usize,
+    index: usize,
+    total: usize,
+) -> io::Result<()> {
+    let title = format!("Repository Map (part {} of {})", index, total);
+    formatter::write_header(writer, &title, root, file_count)?;
+    writer.write_all(b"---\n")
+}
+
+fn part_tokens(root: &str, files: &[FileSymbols], tokenizer: Tokenizer) -> usize {
+    let mut buffer = Vec::new();
+    let mut stats = RepoStats::with_tokenizer(tokenizer);
+    stats.files = files.to_vec();
+    stats.file_count = files.len();
+    write_part(&mut buffer, root, &stats, 999, 999).expect("writing to a Vec cannot fail");
+    tokenizer.count(&String::from_utf8_lossy(&buffer))
+}
+
+/// Cuts a file's symbol list into sections of at most `available` tokens.
+/// Imports stay with the first section and the omitted count with the last.
+fn split_file(file: &FileSymbols, available: usize, tokenizer: Tokenizer) -> Vec<FileSymbols> {
+    let section = |first: bool| FileSymbols {
+        path: if first {
+            file.path.clone()
+        } else {
+            format!("{} (continued)", file.path)
+        },
+        imports: if first {
+            file.imports.clone()
+        } else {
+            Vec::new()
+        },
+        symbols: Vec::new(),
+        omitted: 0,
+        references: Vec::new(),
+        ..file.clone()
+    };
+    let fence = tokenizer.count(&format!(
+        "```{}\n```\n",
+        formatter::get_fence_tag(file.language)
+    ));
+    let overhead = |section: &FileSymbols| {
+        let mut heading = format!("\n## {}\n", section.path);
+        if !section.imports.is_empty() {
+            heading.push_str(&format!("imports: {}\n", section.imports.join(", ")));
+        }
+        tokenizer.count(&heading) + fence
+    };
+
+    let mut sections = Vec::new();
+    let mut current = section(true);
+    let mut used = overhead(&current);
+    for sym in &file.symbols {
+        let cost = line_tokens(sym, tokenizer);
+        if used + cost > available && !current.symbols.is_empty() {
+            sections.push(std::mem::replace(&mut current, section(false)));
+            used = overhead(&current);
+        }
+        current.symbols.push(sym.clone());
+        used += cost;
+    }
+    sections.push(current);
+
+    // Line counts can round down or merge, so check each section whole
+    let mut i = 0;
+    while i < sections.len() {
+        while sections[i].symbols.len() > 1
+            && tokenizer.count(&sections[i].to_markdown()) > available
+        {
+            let last = sections[i].symbols.pop().expect("more than one symbol");
+            if i + 1 == sections.len() {
+                sections.push(section(false));
+            }
+            sections[i + 1].symbols.insert(0, last);
+        }
+        i += 1;
+    }
+    if let Some(last) = sections.last_mut() {
+        last.omitted = file.omitted;
+    }
+    sections
+}
+
+fn line_tokens(sym: &Symbol, tokenizer: Tokenizer) -> usize {
+    let mut line = Vec::new();
+    formatter::write_symbol_line(&mut line, sym).expect("writing to a Vec cannot fail");
+    tokenizer.count(&String::from_utf8_lossy(&line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapOutput;
+
+    fn render(root: &str, parts: &[RepoStats]) -> Vec<String> {
+        parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                let mut buffer = Vec::new();
+                write_part(&mut buffer, root, part, i + 1, parts.len()).unwrap();
+                String::from_utf8(buffer).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parts_fit_and_cover_every_symbol() {
+        let sources: Vec<(String, String)> = (0..12)
+            .map(|i| {
+                let body: String = (0..8)
+                    .map(|j| format!("fn item_{i}_{j}() {{}}\n"))
+                    .collect();
+                (format!("src/file_{i}.rs"), body)
+            })
+            .collect();
+        let mut map = MapOutput::from_sources(".", sources);
+        map.stats.tokenizer = Tokenizer::Heuristic;
+
+        let parts = split_by_tokens(".", &map.stats, 400);
+        assert!(parts.len() > 1);
+        let rendered = render(".", &parts);
+        for text in &rendered {
+            assert!(Tokenizer::Heuristic.count(text) <= 400);
+        }
+        assert!(rendered[0].starts_with(&format!("# Repository Map (part 1 of {})", parts.len())));
+
+        let symbols: usize = parts
+            .iter()
+            .flat_map(|p| &p.files)
+            .map(|f| f.symbols.len())
+            .sum();
+        assert_eq!(symbols, 96);
+    }
+
+    #[test]
+    fn test_oversized_file_continues_across_parts() {
+        let body: String = (0..200).map(|i| format!("fn item_{i}() {{}}\n")).collect();
+        let mut map = MapOutput::from_sources(".", [("src/huge.rs", body.as_str())]);
+        map.stats.tokenizer = Tokenizer::Heuristic;
+
+        let parts = split_by_tokens(".", &map.stats, 500);
+        assert!(parts.len() > 2);
+        assert_eq!(parts[1].files[0].path, "src/huge.rs (continued)");
+        for text in render(".", &parts) {
+            assert!(Tokenizer::Heuristic.count(&text) <= 500);
+        }
+    }
+}
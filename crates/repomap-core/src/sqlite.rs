@@ -0,0 +1,134 @@
+use crate::formatter::RepoStats;
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use std::fs;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE files (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    language TEXT NOT NULL,
+    lines INTEGER NOT NULL
+);
+CREATE TABLE symbols (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files(id),
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    parent TEXT,
+    line INTEGER NOT NULL,
+    end_line INTEGER NOT NULL
+);
+CREATE TABLE imports (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files(id),
+    import TEXT NOT NULL
+);
+CREATE INDEX symbols_name ON symbols(name);
+CREATE INDEX imports_import ON imports(import);
+";
+
+/// Writes the map into a fresh SQLite database at `path`, replacing any
+/// existing file.
+pub fn write_database(path: &Path, stats: &RepoStats) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut insert_file =
+            tx.prepare("INSERT INTO files (path, language, lines) VALUES (?1, ?2, ?3)")?;
+        let mut insert_symbol = tx.prepare(
+            "INSERT INTO symbols (file_id, name, kind, parent, line, end_line)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let mut insert_import =
+            tx.prepare("INSERT INTO imports (file_id, import) VALUES (?1, ?2)")?;
+
+        for file in &stats.files {
+            insert_file.execute(params![
+                file.path,
+                file.language.to_string(),
+                file.lines as i64
+            ])?;
+            let file_id = tx.last_insert_rowid();
+
+            for sym in &file.symbols {
+                insert_symbol.execute(params![
+                    file_id,
+                    sym.name.as_str(),
+                    sym.kind.as_str(),
+                    sym.parent.as_deref(),
+                    sym.line as i64,
+                    sym.end_line as i64
+                ])?;
+            }
+            for import in &file.imports {
+                insert_import.execute(params![file_id, import])?;
+            }
+        }
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::FileSymbols;
+    use crate::languages::Language;
+    use crate::parser::Symbol;
+
+    #[test]
+    fn test_write_database_normalized_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("repo.db");
+
+        let mut stats = RepoStats::new();
+        stats.add_file(FileSymbols {
+            path: "src/main.rs".to_string(),
+            language: Language::Rust,
+            lines: 300,
+            imports: vec!["crate::parser".to_string()],
+            symbols: vec![Symbol {
+                name: "main".into(),
+                parent: None,
+                line: 1,
+                kind: "function_item".into(),
+                end_line: 250,
+                test: false,
+            }],
+            omitted: 0,
+            references: vec![],
+        });
+
+        // Writing twice must replace rather than append
+        write_database(&db_path, &stats).unwrap();
+        write_database(&db_path, &stats).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let (path, language, size): (String, String, i64) = conn
+            .query_row(
+                "SELECT f.path, f.language, s.end_line - s.line + 1
+                 FROM symbols s JOIN files f ON f.id = s.file_id
+                 WHERE s.name = 'main'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(path, "src/main.rs");
+        assert_eq!(language, "rust");
+        assert_eq!(size, 250);
+
+        let imports: i64 = conn
+            .query_row("SELECT COUNT(*) FROM imports", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(imports, 1);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::formatter::{RepoStats, StructuredMap};
+use anyhow::{Context as _, Result};
+use std::fs;
+use std::path::Path;
+use tera::{Context, Tera};
+
+/// Renders the map through a user-supplied Tera template. The template
+/// receives the same document the JSON format emits (`root`, `file_count`,
+/// and `files`).
+pub fn render_template(template_path: &Path, root: &str, stats: &RepoStats) -> Result<String> {
+    let source = fs::read_to_string(template_path)
+        .with_context(|| format!("failed to read template {}", template_path.display()))?;
+    render_str(&source, root, stats)
+}
+
+fn render_str(source: &str, root: &str, stats: &RepoStats) -> Result<String> {
+    let mut tera = Tera::new();
+    tera.add_raw_template("repomap", source)?;
+    let context = Context::from_serialize(&StructuredMap::new(root, stats))?;
+    Ok(tera.render("repomap", &context)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::FileSymbols;
+    use crate::languages::Language;
+    use crate::parser::Symbol;
+
+    #[test]
+    fn test_template_receives_map_data() {
+        let mut stats = RepoStats::new();
+        stats.add_file(FileSymbols {
+            path: "src/lib.rs".to_string(),
+            language: Language::Rust,
+            lines: 12,
+            imports: vec![],
+            symbols: vec![Symbol {
+                name: "run".into(),
+                parent: Some("App".into()),
+                line: 3,
+                kind: "function_item".into(),
+                end_line: 9,
+                test: false,
+            }],
+            omitted: 0,
+            references: vec![],
+        });
+
+        let template = "{{ root }} ({{ file_count }})\n\
+            {% for file in files %}{{ file.path }} [{{ file.language }}, {{ file.symbol_count }}]\n\
+            {% for sym in file.symbols %}- {{ sym.parent }}::{{ sym.name }} L{{ sym.line }}-{{ sym.end_line }}\n\
+            {% endfor %}{% endfor %}";
+
+        let output = render_str(template, ".", &stats).unwrap();
+
+        assert_eq!(output, ". (1)\nsrc/lib.rs [rust, 1]\n- App::run L3-9\n");
+    }
+
+    #[test]
+    fn test_template_syntax_error_is_reported() {
+        let stats = RepoStats::new();
+        assert!(render_str("{% for %}", ".", &stats).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::languages::Language;
+use crate::parser::Symbol;
+use smol_str::SmolStr;
+use std::path::Path;
+use tree_sitter::Node;
+
+/// Which code [`crate::RepoMapBuilder`] maps, by [`Symbol::test`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum TestFilter {
+    /// Tests and everything else.
+    #[default]
+    Include,
+    /// Leave out test files and test symbols.
+    Skip,
+    /// Only test symbols, for a test inventory; files without any are left
+    /// out.
+    Only,
+}
+
+/// Test runner calls whose first argument names a suite or case.
+const JS_TEST_CALLS: &[&str] = &["describe", "context", "suite", "it", "test"];
+
+/// Whether `path` looks like a test file or lives in a test directory.
+pub fn is_test_path(path: &str) -> bool {
+    let path = Path::new(path);
+    let in_test_dir = path.parent().is_some_and(|dir| {
+        dir.components().any(|c| {
+            matches!(
+                c.as_os_str().to_str(),
+                Some("test" | "tests" | "__tests__" | "spec" | "testdata")
+            )
+        })
+    });
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    in_test_dir
+        || stem.starts_with("test_")
+        || [
+            "_test", "_tests", "_spec", ".test", ".spec", "-test", "-spec",
+        ]
+        .iter()
+        .any(|suffix| stem.ends_with(suffix))
+}
+
+/// Sets [`Symbol::test`] on every symbol of a test file and on symbols named
+/// like tests: `test_*` anywhere, `Test*` in Go.
+pub(crate) fn mark_tests(path: &str, lang: Language, symbols: &mut [Symbol]) {
+    let test_file = is_test_path(path);
+    for sym in symbols {
+        sym.test |= test_file
+            || sym.name.starts_with("test_")
+            || lang == Language::Go && sym.name.starts_with("Test");
+    }
+}
+
+/// Sets [`Symbol::test`] on Rust symbols inside `#[cfg(test)]` items and on
+/// `#[test]` functions, including `#[tokio::test]` and the like.
+pub(crate) fn mark_rust_tests(source: &str, root: Node, symbols: &mut [Symbol]) {
+    let mut ranges = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if is_test_item(source, node) {
+            ranges.push((node.start_position().row + 1, node.end_position().row + 1));
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+
+    for sym in symbols {
+        sym.test |= ranges
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&sym.line));
+    }
+}
+
+/// Collects `describe("...")`, `it("...")`, and `test("...")` blocks from a
+/// JavaScript or TypeScript tree as test symbols, including `.only`, `.skip`
+/// and `.each` variants. Each case's parent is its innermost suite.
+pub(crate) fn extract_js_tests(source: &str, root: Node) -> Vec<Symbol> {
+    let mut tests = Vec::new();
+    let mut stack: Vec<(Node, Option<SmolStr>)> = vec![(root, None)];
+    while let Some((node, suite)) = stack.pop() {
+        let mut inner = suite.clone();
+        if let Some((kind, name)) = js_test_call(source, node) {
+            tests.push(Symbol {
+                name: name.clone(),
+                parent: suite,
+                line: node.start_position().row + 1,
+                kind,
+                end_line: node.end_position().row + 1,
+                test: true,
+            });
+            inner = Some(name);
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        stack.extend(
+            children
+                .into_iter()
+                .rev()
+                .map(|child| (child, inner.clone())),
+        );
+    }
+    tests
+}
+
+/// The runner function and case title of a test call like `it.only("...")`.
+fn js_test_call(source: &str, node: Node) -> Option<(SmolStr, SmolStr)> {
+    if node.kind() != "call_expression" {
+        return None;
+    }
+    let mut callee = node.child_by_field_name("function")?;
+    // `describe.each(table)("...")` calls the result of the inner call
+    if callee.kind() == "call_expression" {
+        callee = callee.child_by_field_name("function")?;
+    }
+    while callee.kind() == "member_expression" {
+        callee = callee.child_by_field_name("object")?;
+    }
+    let runner = &source[callee.byte_range()];
+    let runner = JS_TEST_CALLS.iter().find(|&&name| name == runner)?;
+
+    let title = node.child_by_field_name("arguments")?.named_child(0)?;
+    if !matches!(title.kind(), "string" | "template_string") {
+        return None;
+    }
+    let text = source[title.byte_range()].trim_matches(['"', '\'', '`']);
+    Some((SmolStr::new_static(runner), SmolStr::new(text)))
+}
+
+fn is_test_item(source: &str, node: Node) -> bool {
+    let mut sibling = node.prev_named_sibling();
+    while let Some(attr) = sibling.filter(|s| s.kind() == "attribute_item") {
+        let text: String = source[attr.byte_range()]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if text.contains("cfg(test)") || text == "#[test]" || text.ends_with("::test]") {
+            return true;
+        }
+        sibling = attr.prev_named_sibling();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapOutput;
+
+    #[test]
+    fn test_paths() {
+        assert!(is_test_path("tests/api.rs"));
+        assert!(is_test_path("src/parser_test.go"));
+        assert!(is_test_path("src/app.spec.ts"));
+        assert!(is_test_path("test_app.py"));
+        assert!(!is_test_path("src/contest.rs"));
+    }
+
+    #[test]
+    fn test_extracts_js_test_cases() {
+        let source = "describe('Parser', () => {\n  it('parses', () => {});\n  describe.each(cases)(`edge`, () => {\n    test.only(\"empty\", () => {});\n  });\n});\nhelper('not a test');\n";
+        let map = MapOutput::from_sources(".", [("src/parser.spec.ts", source)]);
+
+        let cases: Vec<String> = map.files()[0]
+            .symbols
+            .iter()
+            .map(|s| format!("{}:{}", s.kind, crate::formatter::display_name(s)))
+            .collect();
+        assert_eq!(
+            cases,
+            vec![
+                "describe:Parser",
+                "it:Parser > parses",
+                "describe:Parser > edge",
+                "test:edge > empty"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marks_rust_test_modules_and_functions() {
+        let source = "fn run() {}\n\
+                      #[tokio::test]\nasync fn runs_async() {}\n\
+                      #[cfg(test)]\n#[allow(unused)]\nmod tests {\n    fn helper() {}\n    #[test]\n    fn it_runs() {}\n}\n";
+        let map = MapOutput::from_sources(
+            ".",
+            [
+                ("src/lib.rs", source),
+                (
+                    "app.py",
+                    "def test_app():\n    pass\ndef app():\n    pass\n",
+                ),
+                ("spec/app.py", "def check():\n    pass\n"),
+            ],
+        );
+
+        let tests: Vec<(&str, bool)> = map
+            .files()
+            .iter()
+            .flat_map(|f| &f.symbols)
+            .map(|s| (s.name.as_str(), s.test))
+            .collect();
+        assert_eq!(
+            tests,
+            vec![
+                ("run", false),
+                ("runs_async", true),
+                ("tests", true),
+                ("helper", true),
+                ("it_runs", true),
+                ("test_app", true),
+                ("app", false),
+                ("check", true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use clap::ValueEnum;
+use std::io::{self, Write};
+
+/// How rendered output is turned into a token count.
+///
+/// The BPE tokenizers need the `tokenizers` feature. Without it every
+/// variant falls back to the bytes / 4 heuristic.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug, Default)]
+pub enum Tokenizer {
+    /// OpenAI's cl100k_base (GPT-4, GPT-3.5)
+    #[default]
+    Cl100k,
+    /// OpenAI's o200k_base (GPT-4o and later)
+    O200k,
+    /// Claude's tokenizer is not public; cl100k_base is a close proxy
+    ClaudeApprox,
+    /// Bytes / 4, the old estimate
+    Heuristic,
+}
+
+impl Tokenizer {
+    pub fn count(self, text: &str) -> usize {
+        #[cfg(feature = "tokenizers")]
+        match self {
+            Tokenizer::Cl100k | Tokenizer::ClaudeApprox => {
+                return tiktoken_rs::cl100k_base_singleton().count_ordinary(text);
+            }
+            Tokenizer::O200k => return tiktoken_rs::o200k_base_singleton().count_ordinary(text),
+            Tokenizer::Heuristic => {}
+        }
+        text.len() / 4
+    }
+
+    /// False when this build can only approximate the requested tokenizer.
+    pub fn is_available(self) -> bool {
+        self == Tokenizer::Heuristic || cfg!(feature = "tokenizers")
+    }
+}
+
+/// Context-window presets for `--model`, so the tokenizer and budget do not
+/// have to be looked up per provider.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum Model {
+    /// 200k context, Claude approximation
+    ClaudeOpus,
+    /// 200k context, Claude approximation
+    ClaudeSonnet,
+    /// 200k context, Claude approximation
+    ClaudeHaiku,
+    /// 128k context, o200k
+    #[value(name = "gpt-4o")]
+    Gpt4o,
+    /// 1M context, o200k
+    #[value(name = "gpt-4.1")]
+    Gpt41,
+    /// 128k context, cl100k
+    #[value(name = "gpt-4-turbo")]
+    Gpt4Turbo,
+    /// 200k context, o200k
+    O3,
+    /// 1M context, heuristic
+    GeminiPro,
+    /// 1M context, heuristic
+    GeminiFlash,
+}
+
+impl Model {
+    pub fn tokenizer(self) -> Tokenizer {
+        match self {
+            Model::ClaudeOpus | Model::ClaudeSonnet | Model::ClaudeHaiku => Tokenizer::ClaudeApprox,
+            Model::Gpt4o | Model::Gpt41 | Model::O3 => Tokenizer::O200k,
+            Model::Gpt4Turbo => Tokenizer::Cl100k,
+            // Gemini's tokenizer is not public either, and is closer to
+            // bytes / 4 than to either OpenAI vocabulary
+            Model::GeminiPro | Model::GeminiFlash => Tokenizer::Heuristic,
+        }
+    }
+
+    pub fn context_window(self) -> usize {
+        match self {
+            Model::ClaudeOpus | Model::ClaudeSonnet | Model::ClaudeHaiku | Model::O3 => 200_000,
+            Model::Gpt4o | Model::Gpt4Turbo => 128_000,
+            Model::Gpt41 | Model::GeminiPro | Model::GeminiFlash => 1_048_576,
+        }
+    }
+
+    /// A tenth of the context window, leaving the rest for the conversation.
+    pub fn default_budget(self) -> usize {
+        self.context_window() / 10
+    }
+}
+
+/// Passes writes through while counting tokens a line at a time, so output
+/// that is streamed instead of held as a string can still be measured.
+pub struct TokenCountingWriter<W> {
+    inner: W,
+    tokenizer: Tokenizer,
+    pending: Vec<u8>,
+    tokens: usize,
+}
+
+impl<W: Write> TokenCountingWriter<W> {
+    pub fn new(inner: W, tokenizer: Tokenizer) -> Self {
+        Self {
+            inner,
+            tokenizer,
+            pending: Vec::new(),
+            tokens: 0,
+        }
+    }
+
+    /// Tokens written so far, including any unfinished last line.
+    pub fn tokens(&mut self) -> usize {
+        self.count_pending(self.pending.len());
+        self.tokens
+    }
+
+    fn count_pending(&mut self, len: usize) {
+        if len > 0 {
+            let chunk: Vec<u8> = self.pending.drain(..len).collect();
+            self.tokens += self.tokenizer.count(&String::from_utf8_lossy(&chunk));
+        }
+    }
+}
+
+impl<W: Write> Write for TokenCountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write_all(buf)?;
+        self.pending.extend_from_slice(buf);
+        if let Some(last_newline) = self.pending.iter().rposition(|&b| b == b'\n') {
+            self.count_pending(last_newline + 1);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_count() {
+        assert_eq!(Tokenizer::Heuristic.count("12345678"), 2);
+        assert!(Tokenizer::Heuristic.is_available());
+    }
+
+    #[test]
+    fn test_model_presets() {
+        assert_eq!(Model::ClaudeSonnet.tokenizer(), Tokenizer::ClaudeApprox);
+        assert_eq!(Model::ClaudeSonnet.default_budget(), 20_000);
+        assert_eq!(Model::Gpt4o.tokenizer(), Tokenizer::O200k);
+        assert_eq!(Model::from_str("gpt-4o", false).unwrap(), Model::Gpt4o);
+    }
+
+    #[cfg(feature = "tokenizers")]
+    #[test]
+    fn test_bpe_count() {
+        assert!(Tokenizer::Cl100k.is_available());
+        // "hello world" is two tokens in both BPE vocabularies
+        assert_eq!(Tokenizer::Cl100k.count("hello world"), 2);
+        assert_eq!(Tokenizer::O200k.count("hello world"), 2);
+    }
+
+    #[cfg(feature = "tokenizers")]
+    #[test]
+    fn test_counting_writer_matches_whole_text() {
+        let text = "# Repository Map\n## src/lib.rs\nL1 | function_item | main\n";
+        let mut writer = TokenCountingWriter::new(Vec::new(), Tokenizer::Cl100k);
+        for chunk in text.as_bytes().chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+
+        assert_eq!(writer.tokens(), Tokenizer::Cl100k.count(text));
+        assert_eq!(writer.inner, text.as_bytes());
+    }
+}
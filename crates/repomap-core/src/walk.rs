@@ -0,0 +1,759 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{DirEntry, Match, WalkBuilder, WalkState};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Files shorter than this are never treated as minified.
+const MINIFIED_MIN_BYTES: usize = 2048;
+/// Average line length above which a file reads as minified.
+const MINIFIED_LINE_BYTES: usize = 300;
+
+/// Why a file the walk found was left out of the map.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    /// Minified or bundled code; see [`is_minified`].
+    Minified,
+    /// Larger than the configured maximum file size.
+    TooLarge,
+    /// Named like source code but binary; see [`is_binary_head`].
+    Binary,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Minified => "minified",
+            Self::TooLarge => "too large",
+            Self::Binary => "binary",
+        })
+    }
+}
+
+/// A file skipped during the walk, reported alongside the map.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Skipped {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+/// One line counting skipped files by reason, like `Skipped 3 files (2
+/// minified, 1 too large).`, or `None` when nothing was skipped.
+pub fn skipped_note(skipped: &[Skipped]) -> Option<String> {
+    let mut counts: Vec<(SkipReason, usize)> = Vec::new();
+    for file in skipped {
+        match counts.iter_mut().find(|(reason, _)| *reason == file.reason) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((file.reason, 1)),
+        }
+    }
+    if counts.is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = counts
+        .iter()
+        .map(|(reason, count)| format!("{} {}", count, reason))
+        .collect();
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    Some(format!(
+        "Skipped {} {} ({}).",
+        total,
+        if total == 1 { "file" } else { "files" },
+        parts.join(", ")
+    ))
+}
+
+/// Gitignore-syntax file, honored at any level of the tree, for exclusions
+/// that only matter to repomap.
+pub const IGNORE_FILENAME: &str = ".repomapignore";
+
+/// Directory names that hold installed dependencies or build output, skipped
+/// wherever they appear unless [`WalkOptions::include_vendored`] is set.
+pub const VENDORED_DIRS: &[&str] = &[
+    "node_modules",
+    "bower_components",
+    "jspm_packages",
+    "vendor",
+    ".venv",
+    "venv",
+    "__pycache__",
+    "target",
+    "dist",
+    "build",
+    "Pods",
+];
+
+/// How [`create_walker`] traverses the repository.
+#[derive(Clone, Debug, Default)]
+pub struct WalkOptions {
+    /// Limits traversal depth below the root.
+    pub depth: Option<usize>,
+    /// Gitignore-style globs, relative to the root, for files and
+    /// directories to leave out. They win over `includes`.
+    pub excludes: Vec<String>,
+    /// Gitignore-style globs, relative to the root. When any are given, only
+    /// files matching one of them are visited.
+    pub includes: Vec<String>,
+    /// Descends into [`VENDORED_DIRS`] too.
+    pub include_vendored: bool,
+    /// Visits files that `.gitignore`, `.git/info/exclude` or the global
+    /// git excludes file would hide.
+    pub no_gitignore: bool,
+    /// Visits dotfiles and dot directories.
+    pub hidden: bool,
+    /// Ignores `.gitignore` and `.ignore` files above the root.
+    pub no_ignore_parent: bool,
+    /// Descends through symlinked directories and maps symlinked files,
+    /// staying on the root's filesystem. Links back into an ancestor and
+    /// dangling links are skipped.
+    pub follow_symlinks: bool,
+}
+
+/// Creates a configured builder for traversing the repository.
+pub fn create_walker(root: &str, options: &WalkOptions) -> Result<WalkBuilder> {
+    let mut builder = WalkBuilder::new(root);
+    builder.add_custom_ignore_filename(IGNORE_FILENAME);
+
+    if !options.includes.is_empty() || !options.excludes.is_empty() {
+        builder.overrides(build_overrides(Path::new(root), options)?);
+    }
+
+    if let Some(d) = options.depth {
+        builder.max_depth(Some(d));
+    }
+
+    if !options.include_vendored {
+        builder.filter_entry(|entry| !is_vendored_dir(entry));
+    }
+
+    builder
+        .git_ignore(!options.no_gitignore)
+        .git_global(!options.no_gitignore)
+        .git_exclude(!options.no_gitignore)
+        .hidden(!options.hidden)
+        .parents(!options.no_ignore_parent)
+        .follow_links(options.follow_symlinks)
+        .same_file_system(options.follow_symlinks);
+    Ok(builder)
+}
+
+/// The `--include` and `--exclude` globs as one override set. Overrides use
+/// gitignore precedence, last match wins, so excludes go after includes. An
+/// exclude is a `!` override.
+fn build_overrides(root: &Path, options: &WalkOptions) -> Result<Override> {
+    let mut overrides = OverrideBuilder::new(root);
+    for pattern in &options.includes {
+        overrides
+            .add(pattern)
+            .with_context(|| format!("invalid --include glob '{}'", pattern))?;
+    }
+    for pattern in &options.excludes {
+        overrides
+            .add(&format!("!{}", pattern))
+            .with_context(|| format!("invalid --exclude glob '{}'", pattern))?;
+    }
+    Ok(overrides.build()?)
+}
+
+/// The walk rule that keeps a file from being visited; see [`explain`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum WalkRule {
+    /// The path doesn't exist.
+    Missing,
+    /// The path isn't under the root.
+    OutsideRoot,
+    /// Deeper below the root than [`WalkOptions::depth`] allows.
+    TooDeep { depth: usize, max: usize },
+    /// Matches an `--exclude` glob.
+    Excluded(String),
+    /// `--include` globs were given and none matches.
+    NotIncluded,
+    /// Matches a pattern in an ignore file: `.repomapignore`, `.ignore`,
+    /// `.gitignore`, `.git/info/exclude` or the global git excludes file.
+    Ignored {
+        pattern: String,
+        source: Option<PathBuf>,
+    },
+    /// This dotfile or dot directory, relative to the root, is hidden.
+    Hidden(PathBuf),
+    /// Under this [`VENDORED_DIRS`] directory, relative to the root.
+    Vendored(PathBuf),
+}
+
+impl fmt::Display for WalkRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "no such file"),
+            Self::OutsideRoot => write!(f, "outside the root"),
+            Self::TooDeep { depth, max } => {
+                write!(f, "{} levels deep, below --depth {}", depth, max)
+            }
+            Self::Excluded(pattern) => write!(f, "matches --exclude '{}'", pattern),
+            Self::NotIncluded => write!(f, "matches no --include glob"),
+            Self::Ignored {
+                pattern,
+                source: Some(source),
+            } => write!(f, "ignored by '{}' in {}", pattern, source.display()),
+            Self::Ignored {
+                pattern,
+                source: None,
+            } => write!(f, "ignored by '{}'", pattern),
+            Self::Hidden(path) => write!(f, "{} is hidden (see --hidden)", path.display()),
+            Self::Vendored(path) => write!(
+                f,
+                "{} is a vendored directory (see --include-vendored)",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Which rule, if any, keeps the walk from visiting the file at `path`.
+/// Like the walker, checks each directory on the way down from the root,
+/// then the file, so the rule that prunes the outermost directory wins.
+pub fn explain(root: &str, path: &Path, options: &WalkOptions) -> Result<Option<WalkRule>> {
+    let root = Path::new(root)
+        .canonicalize()
+        .with_context(|| format!("cannot read root '{}'", root))?;
+    let Ok(path) = path.canonicalize() else {
+        return Ok(Some(WalkRule::Missing));
+    };
+    let Ok(relative) = path.strip_prefix(&root) else {
+        return Ok(Some(WalkRule::OutsideRoot));
+    };
+
+    let depth = relative.components().count();
+    if let Some(max) = options.depth
+        && depth > max
+    {
+        return Ok(Some(WalkRule::TooDeep { depth, max }));
+    }
+
+    let overrides = build_overrides(&root, options)?;
+    let mut entry = root.clone();
+    for (index, component) in relative.components().enumerate() {
+        entry.push(component);
+        let is_dir = index + 1 < depth;
+        if let Some(rule) = entry_rule(&root, &entry, is_dir, options, &overrides) {
+            return Ok(Some(rule));
+        }
+    }
+    Ok(None)
+}
+
+/// The rule that prunes `entry` itself, in the walker's order: overrides,
+/// then ignore files, then hidden names, then vendored directories.
+fn entry_rule(
+    root: &Path,
+    entry: &Path,
+    is_dir: bool,
+    options: &WalkOptions,
+    overrides: &Override,
+) -> Option<WalkRule> {
+    let relative = entry.strip_prefix(root).unwrap_or(entry);
+    let name = entry.file_name()?.to_str().unwrap_or_default();
+    let vendored = is_dir && !options.include_vendored && VENDORED_DIRS.contains(&name);
+
+    match overrides.matched(entry, is_dir) {
+        // An override match settles everything but vendored directories
+        Match::Whitelist(_) => return vendored.then(|| WalkRule::Vendored(relative.into())),
+        Match::Ignore(_) => {
+            let pattern = options.excludes.iter().rev().find(|pattern| {
+                let mut single = OverrideBuilder::new(root);
+                single.add(&format!("!{}", pattern)).is_ok()
+                    && single
+                        .build()
+                        .is_ok_and(|o| o.matched(entry, is_dir).is_ignore())
+            });
+            return Some(match pattern {
+                Some(pattern) => WalkRule::Excluded(pattern.clone()),
+                None => WalkRule::NotIncluded,
+            });
+        }
+        Match::None => {}
+    }
+    if let Some(rule) = ignore_file_rule(root, entry, is_dir, options) {
+        return Some(rule);
+    }
+    if !options.hidden && name.starts_with('.') {
+        return Some(WalkRule::Hidden(relative.into()));
+    }
+    vendored.then(|| WalkRule::Vendored(relative.into()))
+}
+
+/// The ignore file pattern matching `entry`, with the walker's precedence:
+/// `.repomapignore`, then `.ignore`, then git's files, each from the nearest
+/// directory up. Git's files only count inside a repository and stop at
+/// its top.
+fn ignore_file_rule(
+    root: &Path,
+    entry: &Path,
+    is_dir: bool,
+    options: &WalkOptions,
+) -> Option<WalkRule> {
+    let dirs: Vec<&Path> = entry
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| !options.no_ignore_parent || dir.starts_with(root))
+        .collect();
+    let repo_top = dirs.iter().position(|dir| dir.join(".git").exists());
+
+    let mut matchers: Vec<Gitignore> = Vec::new();
+    for name in [IGNORE_FILENAME, ".ignore"] {
+        matchers.extend(dirs.iter().map(|dir| ignore_file(dir, &dir.join(name))));
+    }
+    if let Some(top) = repo_top.filter(|_| !options.no_gitignore) {
+        let in_repo = &dirs[..=top];
+        matchers.extend(
+            in_repo
+                .iter()
+                .map(|dir| ignore_file(dir, &dir.join(".gitignore"))),
+        );
+        let top = in_repo[top];
+        matchers.push(ignore_file(top, &top.join(".git/info/exclude")));
+        matchers.push(Gitignore::global().0);
+    }
+
+    matchers
+        .iter()
+        .map(|matcher| matcher.matched(entry, is_dir))
+        .find(|m| !m.is_none())
+        .and_then(|m| match m {
+            Match::Ignore(glob) => Some(WalkRule::Ignored {
+                pattern: glob.original().to_string(),
+                source: glob.from().map(Path::to_path_buf),
+            }),
+            _ => None,
+        })
+}
+
+/// `file`'s patterns rooted at `dir`; a missing file matches nothing.
+fn ignore_file(dir: &Path, file: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(dir);
+    if file.is_file() {
+        builder.add(file);
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Whether `entry` is a [`VENDORED_DIRS`] directory below the root.
+fn is_vendored_dir(entry: &DirEntry) -> bool {
+    entry.depth() > 0
+        && entry.file_type().is_some_and(|t| t.is_dir())
+        && entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| VENDORED_DIRS.contains(&name))
+}
+
+/// Traverses the repository across all cores and returns every entry sorted
+/// by path, so output order does not depend on thread scheduling.
+pub fn collect_entries(root: &str, options: &WalkOptions) -> Result<Vec<DirEntry>> {
+    let results = Mutex::new(Vec::new());
+
+    create_walker(root, options)?.build_parallel().run(|| {
+        let results = &results;
+        Box::new(move |result| {
+            results.lock().expect("walk results lock").push(result);
+            WalkState::Continue
+        })
+    });
+
+    let mut entries = results
+        .into_inner()
+        .expect("walk results lock")
+        .into_iter()
+        .filter(|result| !result.as_ref().is_err_and(is_broken_link))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(entries)
+}
+
+/// Whether `err` is a symlink cycle or a link to nothing, which only occur
+/// when following symlinks and shouldn't fail the whole walk.
+fn is_broken_link(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            is_broken_link(err)
+        }
+        ignore::Error::Io(io) => io.kind() == std::io::ErrorKind::NotFound,
+        _ => false,
+    }
+}
+
+/// The first `len` bytes of the file at `path`, or `None` if it cannot be
+/// read.
+pub fn read_head(path: &Path, len: usize) -> Option<Vec<u8>> {
+    let mut head = Vec::with_capacity(len);
+    File::open(path)
+        .ok()?
+        .take(len as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(head)
+}
+
+/// Whether `head`, the start of a file, looks binary.
+pub fn is_binary_head(head: &[u8]) -> bool {
+    head[..head.len().min(1024)].contains(&0)
+}
+
+/// Whether `content` looks minified or bundled rather than written by hand:
+/// a `.min.js`-style name, or lines far longer than anyone types.
+pub fn is_minified(path: &Path, content: &str) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    if name.contains(".min.") || name.contains("-min.") {
+        return true;
+    }
+    if content.len() < MINIFIED_MIN_BYTES {
+        return false;
+    }
+    let lines = content.lines().count().max(1);
+    let longest = content.lines().map(str::len).max().unwrap_or(0);
+    content.len() / lines > MINIFIED_LINE_BYTES || longest * 2 > content.len()
+}
+
+pub fn is_binary(path: &Path) -> bool {
+    read_head(path, 1024).is_none_or(|head| is_binary_head(&head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_collect_entries_is_sorted() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["b/z.rs", "b/a.rs", "a.rs", "c.rs"] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let entries =
+            collect_entries(dir.path().to_str().unwrap(), &WalkOptions::default()).unwrap();
+        let paths: Vec<_> = entries
+            .iter()
+            .map(|e| e.path().strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        let expected: Vec<std::path::PathBuf> = ["", "a.rs", "b", "b/a.rs", "b/z.rs", "c.rs"]
+            .iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_vendored_dirs_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["src/app.js", "node_modules/lib/index.js", "src/vendor/x.go"] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let files = |options: &WalkOptions| -> Vec<String> {
+            collect_entries(dir.path().to_str().unwrap(), options)
+                .unwrap()
+                .iter()
+                .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(files(&WalkOptions::default()), vec!["app.js"]);
+        let everything = WalkOptions {
+            include_vendored: true,
+            ..Default::default()
+        };
+        assert_eq!(files(&everything), vec!["index.js", "app.js", "x.go"]);
+    }
+
+    #[test]
+    fn test_includes_restrict_the_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "src/a/lib.rs",
+            "src/main.py",
+            "crates/x/src/y.rs",
+            "web/app.ts",
+        ] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let options = WalkOptions {
+            includes: vec!["src/**/*.rs".into(), "crates/*/src/**".into()],
+            ..Default::default()
+        };
+        let files: Vec<String> = collect_entries(dir.path().to_str().unwrap(), &options)
+            .unwrap()
+            .iter()
+            .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(files, vec!["y.rs", "lib.rs"]);
+
+        let bad = WalkOptions {
+            includes: vec!["src/{".into()],
+            ..Default::default()
+        };
+        assert!(collect_entries(dir.path().to_str().unwrap(), &bad).is_err());
+    }
+
+    #[test]
+    fn test_excludes_are_gitignore_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "src/api.gen.ts",
+            "src/app.ts",
+            "fixtures/case.ts",
+            "lib/fixtures.ts",
+        ] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let files = |options: &WalkOptions| -> Vec<String> {
+            collect_entries(dir.path().to_str().unwrap(), options)
+                .unwrap()
+                .iter()
+                .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let options = WalkOptions {
+            excludes: vec!["**/*.gen.ts".into(), "fixtures/".into()],
+            ..Default::default()
+        };
+        assert_eq!(files(&options), vec!["fixtures.ts", "app.ts"]);
+
+        let options = WalkOptions {
+            includes: vec!["src/**".into()],
+            excludes: vec!["*.gen.ts".into()],
+            ..Default::default()
+        };
+        assert_eq!(files(&options), vec!["app.ts"]);
+    }
+
+    #[test]
+    fn test_ignore_toggles() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "repo/.git/HEAD",
+            "repo/src/app.rs",
+            "repo/sdk/client.rs",
+            "repo/.config/tool.rs",
+            "repo/src/scratch.rs",
+        ] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(dir.path().join("repo/.gitignore"), "sdk/\n").unwrap();
+        std::fs::write(dir.path().join("repo/.ignore"), "scratch.rs\n").unwrap();
+        let root = dir.path().join("repo/src");
+        let files = |options: &WalkOptions| -> Vec<String> {
+            let root = if options.hidden {
+                dir.path().join("repo")
+            } else {
+                root.clone()
+            };
+            collect_entries(root.to_str().unwrap(), options)
+                .unwrap()
+                .iter()
+                .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(files(&WalkOptions::default()), vec!["app.rs"]);
+        let parents_ignored = WalkOptions {
+            no_ignore_parent: true,
+            ..Default::default()
+        };
+        assert_eq!(files(&parents_ignored), vec!["app.rs", "scratch.rs"]);
+
+        let hidden = WalkOptions {
+            hidden: true,
+            ..Default::default()
+        };
+        let mut found = files(&hidden);
+        found.retain(|name| name.ends_with(".rs"));
+        assert_eq!(found, vec!["tool.rs", "app.rs"]);
+        let everything = WalkOptions {
+            hidden: true,
+            no_gitignore: true,
+            ..Default::default()
+        };
+        let mut found = files(&everything);
+        found.retain(|name| name.ends_with(".rs"));
+        assert_eq!(found, vec!["tool.rs", "client.rs", "app.rs"]);
+    }
+
+    #[test]
+    fn test_explain_names_the_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for name in [
+            ".git/HEAD",
+            "src/app.rs",
+            "src/gen/api.rs",
+            "sdk/client.rs",
+            "node_modules/x/index.js",
+            ".config/tool.rs",
+            "fixtures/case.rs",
+        ] {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "sdk/\n").unwrap();
+        std::fs::write(root.join("src").join(IGNORE_FILENAME), "gen/\n").unwrap();
+        let options = WalkOptions {
+            excludes: vec!["fixtures/".into()],
+            ..Default::default()
+        };
+        let explain = |name: &str, options: &WalkOptions| {
+            explain(root.to_str().unwrap(), &root.join(name), options).unwrap()
+        };
+
+        assert_eq!(explain("src/app.rs", &options), None);
+        assert_eq!(
+            explain("sdk/client.rs", &options),
+            Some(WalkRule::Ignored {
+                pattern: "sdk/".into(),
+                source: Some(root.canonicalize().unwrap().join(".gitignore")),
+            })
+        );
+        assert!(matches!(
+            explain("src/gen/api.rs", &options),
+            Some(WalkRule::Ignored { pattern, .. }) if pattern == "gen/"
+        ));
+        assert_eq!(
+            explain("node_modules/x/index.js", &options),
+            Some(WalkRule::Vendored("node_modules".into()))
+        );
+        assert_eq!(
+            explain(".config/tool.rs", &options),
+            Some(WalkRule::Hidden(".config".into()))
+        );
+        assert_eq!(
+            explain("fixtures/case.rs", &options),
+            Some(WalkRule::Excluded("fixtures/".into()))
+        );
+        assert_eq!(explain("nope.rs", &options), Some(WalkRule::Missing));
+
+        let narrow = WalkOptions {
+            depth: Some(1),
+            includes: vec!["*.md".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            explain("src/app.rs", &narrow),
+            Some(WalkRule::TooDeep { depth: 2, max: 1 })
+        );
+        assert_eq!(explain(".gitignore", &narrow), Some(WalkRule::NotIncluded));
+    }
+
+    #[test]
+    fn test_repomapignore() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["src/app.rs", "src/gen/api.rs", "docs/notes.rs", "lib.rs"] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(dir.path().join(IGNORE_FILENAME), "docs/\n").unwrap();
+        std::fs::write(dir.path().join("src").join(IGNORE_FILENAME), "gen/\n").unwrap();
+
+        let files: Vec<String> =
+            collect_entries(dir.path().to_str().unwrap(), &WalkOptions::default())
+                .unwrap()
+                .iter()
+                .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect();
+        assert_eq!(files, vec!["lib.rs", "app.rs"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["store/pkg/index.ts", "repo/src/app.ts"] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let repo = dir.path().join("repo");
+        symlink(dir.path().join("store/pkg"), repo.join("pkg")).unwrap();
+        symlink(&repo, repo.join("src/loop")).unwrap();
+        symlink(repo.join("missing.ts"), repo.join("dangling.ts")).unwrap();
+        let files = |options: &WalkOptions| -> Vec<String> {
+            collect_entries(repo.to_str().unwrap(), options)
+                .unwrap()
+                .iter()
+                .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(files(&WalkOptions::default()), vec!["app.ts"]);
+        let follow = WalkOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        assert_eq!(files(&follow), vec!["index.ts", "app.ts"]);
+    }
+
+    #[test]
+    fn test_is_minified() {
+        let bundle = format!("!function(e){{{}}}();\n", "var a=1;".repeat(600));
+        assert!(is_minified(Path::new("dist/app.js"), &bundle));
+        assert!(is_minified(Path::new("vendor/jquery.min.js"), "x"));
+
+        let handwritten = "function add(a, b) {\n  return a + b;\n}\n".repeat(100);
+        assert!(!is_minified(Path::new("src/add.js"), &handwritten));
+        assert!(!is_minified(Path::new("src/short.js"), "var a=1;var b=2;"));
+
+        let skipped = |reason| Skipped {
+            path: "a.js".into(),
+            reason,
+        };
+        assert_eq!(skipped_note(&[]), None);
+        assert_eq!(
+            skipped_note(&[skipped(SkipReason::Minified), skipped(SkipReason::Minified)]).unwrap(),
+            "Skipped 2 files (2 minified)."
+        );
+        assert_eq!(
+            skipped_note(&[skipped(SkipReason::Binary), skipped(SkipReason::Minified)]).unwrap(),
+            "Skipped 2 files (1 binary, 1 minified)."
+        );
+    }
+
+    #[test]
+    fn test_is_binary() {
+        let mut text_file = NamedTempFile::new().unwrap();
+        writeln!(text_file, "This is just some text").unwrap();
+        assert!(!is_binary(text_file.path()));
+
+        let mut bin_file = NamedTempFile::new().unwrap();
+        bin_file.write_all(&[0, 155, 20, 0, 255]).unwrap();
+        assert!(is_binary(bin_file.path()));
+    }
+}
@@ -0,0 +1,332 @@
+//! Summaries of YAML files, which hold configuration rather than
+//! definitions: a GitHub Actions workflow lists its name, triggers, and jobs,
+//! a Kubernetes manifest its `kind` and `metadata.name`, and any other
+//! document its top-level keys. Read line by line, like [`crate::outline`],
+//! so every entry keeps the lines it spans.
+
+use crate::parser::{Extracted, Symbol};
+use regex::Regex;
+use smol_str::SmolStr;
+use std::sync::LazyLock;
+
+/// A mapping key at the start of a line, quoted or not, and its value.
+static KEY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(?P<key>"[^"]*"|'[^']*'|[^\s#:'"-][^:#]*?)\s*:(?:\s+(?P<value>.*))?$"#)
+        .expect("built-in pattern is valid")
+});
+
+/// One `key: value` line of a document.
+pub(crate) struct Entry<'a> {
+    pub(crate) key: &'a str,
+    pub(crate) value: &'a str,
+    /// Zero-based, like the lines it spans.
+    pub(crate) line: usize,
+    pub(crate) end: usize,
+}
+
+/// Summarizes every document in `source`.
+pub fn extract(source: &str) -> Extracted {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut symbols = Vec::new();
+    for (start, end) in documents(&lines) {
+        let top = entries(&lines, start, end, 0);
+        let (Some(first), Some(last)) = (top.first(), top.last()) else {
+            continue;
+        };
+        let (start, end) = (first.line, last.end);
+        let find = |key: &str| top.iter().find(|entry| entry.key == key);
+        if let (Some(jobs), Some(triggers)) = (find("jobs"), find("on")) {
+            workflow(&lines, find("name"), triggers, jobs, end, &mut symbols);
+        } else if let (Some(kind), Some(_)) = (find("kind"), find("apiVersion")) {
+            let name = find("metadata")
+                .and_then(|metadata| {
+                    children(&lines, metadata)
+                        .into_iter()
+                        .find(|e| e.key == "name")
+                })
+                .map(|entry| scalar(entry.value))
+                .filter(|name| !name.is_empty());
+            symbols.push(match name {
+                Some(name) => symbol(name, Some(scalar(kind.value)), "resource", start, end),
+                None => symbol(scalar(kind.value), None, "resource", start, end),
+            });
+        } else {
+            symbols.extend(
+                top.iter()
+                    .map(|entry| symbol(entry.key, None, "key", entry.line, entry.end)),
+            );
+        }
+    }
+    Extracted {
+        symbols,
+        ..Extracted::default()
+    }
+}
+
+/// A workflow's name, what triggers it, and its jobs.
+fn workflow(
+    lines: &[&str],
+    name: Option<&Entry>,
+    triggers: &Entry,
+    jobs: &Entry,
+    end: usize,
+    symbols: &mut Vec<Symbol>,
+) {
+    if let Some(name) = name {
+        symbols.push(symbol(scalar(name.value), None, "workflow", name.line, end));
+    }
+    let value = scalar(triggers.value);
+    if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        symbols.extend(
+            list.split(',')
+                .map(scalar)
+                .filter(|event| !event.is_empty())
+                .map(|event| symbol(event, None, "trigger", triggers.line, triggers.line)),
+        );
+    } else if !value.is_empty() {
+        symbols.push(symbol(value, None, "trigger", triggers.line, triggers.line));
+    } else {
+        let events = children(lines, triggers);
+        if events.is_empty() {
+            // `on:` as a list of events
+            for (j, line) in lines
+                .iter()
+                .enumerate()
+                .take(triggers.end + 1)
+                .skip(triggers.line + 1)
+            {
+                if let Some(event) = line.trim_start().strip_prefix("- ") {
+                    symbols.push(symbol(scalar(event), None, "trigger", j, j));
+                }
+            }
+        }
+        symbols.extend(
+            events
+                .iter()
+                .map(|entry| symbol(entry.key, None, "trigger", entry.line, entry.end)),
+        );
+    }
+    symbols.extend(
+        children(lines, jobs)
+            .iter()
+            .map(|entry| symbol(entry.key, None, "job", entry.line, entry.end)),
+    );
+}
+
+fn symbol(name: &str, parent: Option<&str>, kind: &'static str, line: usize, end: usize) -> Symbol {
+    Symbol {
+        name: SmolStr::new(name),
+        parent: parent.map(SmolStr::new),
+        line: line + 1,
+        kind: SmolStr::new_static(kind),
+        end_line: end + 1,
+        test: false,
+    }
+}
+
+/// The first and last line of each `---`-separated document with any
+/// content.
+fn documents(lines: &[&str]) -> Vec<(usize, usize)> {
+    let mut documents = Vec::new();
+    let mut start = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("---") || line.starts_with("...") {
+            if i > start {
+                documents.push((start, i - 1));
+            }
+            start = i + 1;
+        }
+    }
+    if start < lines.len() {
+        documents.push((start, lines.len() - 1));
+    }
+    documents
+}
+
+/// The keys indented by `indent` between `start` and `end`, each running
+/// to the last line of content before the next.
+pub(crate) fn entries<'a>(
+    lines: &[&'a str],
+    start: usize,
+    end: usize,
+    indent: usize,
+) -> Vec<Entry<'a>> {
+    let mut entries: Vec<Entry> = Vec::new();
+    for (i, &line) in lines.iter().enumerate().take(end + 1).skip(start) {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let depth = line.len() - trimmed.len();
+        if depth < indent {
+            break;
+        }
+        let caps = KEY.captures(line).filter(|_| depth == indent);
+        let Some(caps) = caps else {
+            // Nested, or a list item like `- push` belonging to the key above
+            if let Some(last) = entries.last_mut() {
+                last.end = i;
+            }
+            continue;
+        };
+        entries.push(Entry {
+            key: unquote(caps.name("key").map_or("", |key| key.as_str())),
+            value: caps.name("value").map_or("", |value| value.as_str()),
+            line: i,
+            end: i,
+        });
+    }
+    entries
+}
+
+/// The keys of the mapping nested under `entry`.
+fn children<'a>(lines: &[&'a str], entry: &Entry) -> Vec<Entry<'a>> {
+    let first = lines
+        .iter()
+        .enumerate()
+        .take(entry.end + 1)
+        .skip(entry.line + 1)
+        .find(|(_, line)| {
+            let trimmed = line.trim_start();
+            !trimmed.is_empty() && !trimmed.starts_with('#')
+        });
+    match first {
+        Some((i, line)) => {
+            let indent = line.len() - line.trim_start().len();
+            entries(lines, i, entry.end, indent)
+        }
+        None => Vec::new(),
+    }
+}
+
+/// A scalar value without its quotes or a trailing comment.
+pub(crate) fn scalar(value: &str) -> &str {
+    let value = value.trim();
+    let value = match value.find(" #") {
+        Some(at) if !value.starts_with(['"', '\'']) => value[..at].trim_end(),
+        _ => value,
+    };
+    unquote(value)
+}
+
+fn unquote(text: &str) -> &str {
+    text.trim().trim_matches(['"', '\''])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary_of(source: &str) -> Vec<(String, Option<String>, usize, usize)> {
+        extract(source)
+            .symbols
+            .into_iter()
+            .map(|sym| {
+                (
+                    format!("{} {}", sym.kind, sym.name),
+                    sym.parent.map(|p| p.to_string()),
+                    sym.line,
+                    sym.end_line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_workflow() {
+        let source = r#"name: CI
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+  # Only on main
+  deploy:
+    needs: test
+    runs-on: ubuntu-latest
+"#;
+        assert_eq!(
+            summary_of(source),
+            [
+                ("workflow CI".into(), None, 1, 16),
+                ("trigger push".into(), None, 4, 5),
+                ("trigger pull_request".into(), None, 6, 6),
+                ("job test".into(), None, 9, 12),
+                ("job deploy".into(), None, 14, 16),
+            ]
+        );
+        assert_eq!(
+            summary_of("on:\n  - push\n  - release\njobs:\n  lint:\n    runs-on: x\n"),
+            [
+                ("trigger push".into(), None, 2, 2),
+                ("trigger release".into(), None, 3, 3),
+                ("job lint".into(), None, 5, 6),
+            ]
+        );
+        assert_eq!(
+            summary_of("on: [push, 'workflow_dispatch']\njobs:\n  lint:\n    runs-on: x\n"),
+            [
+                ("trigger push".into(), None, 1, 1),
+                ("trigger workflow_dispatch".into(), None, 1, 1),
+                ("job lint".into(), None, 3, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_manifests() {
+        let source = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  labels:
+    app: web
+spec:
+  replicas: 2
+---
+apiVersion: v1
+kind: Service
+metadata:
+  labels:
+    name: not-this
+  name: "web"
+---
+apiVersion: v1
+kind: Namespace
+"#;
+        assert_eq!(
+            summary_of(source),
+            [
+                ("resource web".into(), Some("Deployment".into()), 1, 8),
+                ("resource web".into(), Some("Service".into()), 10, 15),
+                ("resource Namespace".into(), None, 17, 18),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_level_keys() {
+        let source = r#"# Settings
+version: 2
+updates:
+  - package-ecosystem: cargo
+    directory: "/"
+
+"registries": {}
+"#;
+        assert_eq!(
+            summary_of(source),
+            [
+                ("key version".into(), None, 2, 2),
+                ("key updates".into(), None, 3, 5),
+                ("key registries".into(), None, 7, 7),
+            ]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+#ifndef REPOMAP_H
+#define REPOMAP_H
+
+/* Generated by cbindgen from crates/repomap-ffi. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Generates a repository map for `root`. `options_json` may be NULL or a
+// JSON object with any of `format`, `language`, `depth`, `exclude`, and
+// `summary`.
+//
+// Returns a newly allocated string that must be released with
+// `repomap_string_free`, or NULL on failure (see `repomap_last_error`).
+//
+// # Safety
+//
+// `root` must be a valid NUL-terminated string. `options_json` must be NULL
+// or a valid NUL-terminated string.
+char *repomap_generate(const char *root, const char *options_json);
+
+// Releases a string returned by `repomap_generate`. Passing NULL is a no-op.
+//
+// # Safety
+//
+// `map` must be NULL or a pointer returned by `repomap_generate` that has
+// not already been freed.
+void repomap_string_free(char *map);
+
+// Returns the error message from the most recent failed call on this
+// thread, or NULL. The pointer stays valid until the next failing call.
+const char *repomap_last_error(void);
+
+#endif  /* REPOMAP_H */
@@ -0,0 +1,169 @@
+//! C ABI for embedding repomap in C, C++, Zig, and other native tools.
+//!
+//! The header lives at `include/repomap.h`; regenerate it with
+//! `cbindgen --config cbindgen.toml --output include/repomap.h` from this
+//! crate's directory.
+
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use repomap_core::{Language, OutputFormat, RepoMapBuilder};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Options {
+    format: Option<String>,
+    language: Option<String>,
+    depth: Option<usize>,
+    exclude: Vec<String>,
+    include: Vec<String>,
+    summary: bool,
+}
+
+fn generate(root: &str, options_json: Option<&str>) -> Result<String> {
+    let options: Options = match options_json {
+        Some(json) if !json.trim().is_empty() => {
+            serde_json::from_str(json).context("invalid options JSON")?
+        }
+        _ => Options::default(),
+    };
+
+    let format = match options.format.as_deref() {
+        Some(name) => OutputFormat::from_str(name, true).map_err(|e| anyhow!(e))?,
+        None => OutputFormat::Markdown,
+    };
+    let language = options
+        .language
+        .as_deref()
+        .map(|name| Language::from_str(name, true).map_err(|e| anyhow!(e)))
+        .transpose()?;
+
+    let map = RepoMapBuilder::new(root)
+        .depth(options.depth)
+        .excludes(options.exclude)
+        .includes(options.include)
+        .language(language)
+        .build()?;
+
+    if format == OutputFormat::Markdown {
+        Ok(map.to_markdown(options.summary))
+    } else {
+        map.render(format)
+    }
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Generates a repository map for `root`. `options_json` may be NULL or a
+/// JSON object with any of `format`, `language`, `depth`, `exclude`,
+/// `include`, and `summary`.
+///
+/// Returns a newly allocated string that must be released with
+/// `repomap_string_free`, or NULL on failure (see `repomap_last_error`).
+///
+/// # Safety
+///
+/// `root` must be a valid NUL-terminated string. `options_json` must be NULL
+/// or a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn repomap_generate(
+    root: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    if root.is_null() {
+        set_last_error("root must not be NULL".to_string());
+        return ptr::null_mut();
+    }
+
+    // SAFETY: the caller guarantees both pointers are NUL-terminated when non-null.
+    let result = unsafe {
+        let root = CStr::from_ptr(root)
+            .to_str()
+            .context("root is not valid UTF-8");
+        let options = if options_json.is_null() {
+            Ok(None)
+        } else {
+            CStr::from_ptr(options_json)
+                .to_str()
+                .map(Some)
+                .context("options_json is not valid UTF-8")
+        };
+        root.and_then(|root| generate(root, options?))
+    };
+
+    match result.and_then(|map| CString::new(map).context("map contains a NUL byte")) {
+        Ok(map) => map.into_raw(),
+        Err(e) => {
+            set_last_error(format!("{:#}", e));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a string returned by `repomap_generate`. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `map` must be NULL or a pointer returned by `repomap_generate` that has
+/// not already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn repomap_string_free(map: *mut c_char) {
+    if !map.is_null() {
+        // SAFETY: the pointer came from CString::into_raw in repomap_generate.
+        drop(unsafe { CString::from_raw(map) });
+    }
+}
+
+/// Returns the error message from the most recent failed call on this
+/// thread, or NULL. The pointer stays valid until the next failing call.
+#[unsafe(no_mangle)]
+pub extern "C" fn repomap_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_generate_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn exported() {}").unwrap();
+
+        let root = CString::new(dir.path().to_str().unwrap()).unwrap();
+        let options = CString::new(r#"{"format": "json"}"#).unwrap();
+
+        unsafe {
+            let map = repomap_generate(root.as_ptr(), options.as_ptr());
+            assert!(!map.is_null());
+            let json: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(map).to_str().unwrap()).unwrap();
+            assert_eq!(json["files"][0]["symbols"][0]["name"], "exported");
+            repomap_string_free(map);
+        }
+    }
+
+    #[test]
+    fn test_generate_reports_bad_options() {
+        let root = CString::new(".").unwrap();
+        let options = CString::new(r#"{"format": "pdf"}"#).unwrap();
+
+        unsafe {
+            let map = repomap_generate(root.as_ptr(), options.as_ptr());
+            assert!(map.is_null());
+            let error = CStr::from_ptr(repomap_last_error()).to_str().unwrap();
+            assert!(error.contains("pdf"));
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! WebAssembly bindings: map an in-memory file tree without a server.
+//!
+//! Build with `wasm-pack build crates/repomap-wasm --target web`.
+
+use clap::ValueEnum;
+use repomap_core::{MapOutput, OutputFormat};
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+/// Generates a map from `files`, a plain object of `{ path: contents }`.
+/// `format` accepts the same names as `--format` (markdown when omitted),
+/// except `sqlite`, which needs a filesystem.
+#[wasm_bindgen(js_name = generateMap)]
+pub fn generate_map(root: &str, files: JsValue, format: Option<String>) -> Result<String, JsError> {
+    let files: BTreeMap<String, String> = serde_wasm_bindgen::from_value(files)?;
+    let format = match format.as_deref() {
+        Some(name) => OutputFormat::from_str(name, true).map_err(|e| JsError::new(&e))?,
+        None => OutputFormat::Markdown,
+    };
+
+    let map = MapOutput::from_sources(root, files);
+    map.render(format)
+        .map_err(|e| JsError::new(&format!("{:#}", e)))
+}
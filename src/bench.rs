@@ -0,0 +1,191 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Results of `repomap bench`, also the format of saved baselines.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BenchReport {
+    pub version: String,
+    pub runs: usize,
+    pub files: usize,
+    pub symbols: usize,
+    pub mean_ms: f64,
+    pub best_ms: f64,
+    pub files_per_sec: f64,
+    pub symbols_per_sec: f64,
+    pub peak_memory_kb: Option<u64>,
+}
+
+/// Maps `root` `runs` times without the cache and measures each run.
+pub fn run(root: &str, runs: usize) -> Result<BenchReport> {
+    if runs == 0 {
+        bail!("--runs must be at least 1");
+    }
+
+    let builder = crate::new_builder(root);
+    let mut timings = Vec::with_capacity(runs);
+    let mut files = 0;
+    let mut symbols = 0;
+
+    for _ in 0..runs {
+        let start = Instant::now();
+        let map = builder.build()?;
+        timings.push(start.elapsed().as_secs_f64() * 1000.0);
+
+        files = map.stats.file_count;
+        symbols = map.files().iter().map(|f| f.symbols.len()).sum();
+    }
+
+    let mean_ms = timings.iter().sum::<f64>() / runs as f64;
+    let best_ms = timings.iter().copied().fold(f64::INFINITY, f64::min);
+    let per_sec = |count: usize| count as f64 / (mean_ms / 1000.0);
+
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        runs,
+        files,
+        symbols,
+        mean_ms,
+        best_ms,
+        files_per_sec: per_sec(files),
+        symbols_per_sec: per_sec(symbols),
+        peak_memory_kb: peak_memory_kb(),
+    })
+}
+
+pub fn load_baseline(path: &Path) -> Result<BenchReport> {
+    let json = fs::read_to_string(path)
+        .with_context(|| format!("failed to read baseline {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("invalid baseline {}", path.display()))
+}
+
+pub fn save_baseline(path: &Path, report: &BenchReport) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(report)?)?;
+    Ok(())
+}
+
+/// Reads one value out of a report, if it was measured.
+type Metric = fn(&BenchReport) -> Option<f64>;
+
+/// Renders the report as a table, with a change column when a baseline is
+/// given. Positive changes are regressions for time and memory.
+pub fn format_report(report: &BenchReport, baseline: Option<&BenchReport>) -> String {
+    let peak_mib = |r: &BenchReport| r.peak_memory_kb.map(|kb| kb as f64 / 1024.0);
+    let metrics: [(&str, &str, usize, Metric); 7] = [
+        ("Files", "", 0, |r| Some(r.files as f64)),
+        ("Symbols", "", 0, |r| Some(r.symbols as f64)),
+        ("Mean time", " ms", 1, |r| Some(r.mean_ms)),
+        ("Best time", " ms", 1, |r| Some(r.best_ms)),
+        ("Files/sec", "", 0, |r| Some(r.files_per_sec)),
+        ("Symbols/sec", "", 0, |r| Some(r.symbols_per_sec)),
+        ("Peak memory", " MiB", 1, peak_mib),
+    ];
+
+    let mut output = format!(
+        "repomap {} - {} run{}\n",
+        report.version,
+        report.runs,
+        if report.runs == 1 { "" } else { "s" }
+    );
+    if let Some(baseline) = baseline {
+        output.push_str(&format!(
+            "Baseline: repomap {} - {} runs\n",
+            baseline.version, baseline.runs
+        ));
+    }
+    output.push('\n');
+
+    for (label, unit, decimals, metric) in metrics {
+        let Some(value) = metric(report) else {
+            continue;
+        };
+        let current = format!("{:.*}{}", decimals, value, unit);
+        match baseline.and_then(metric) {
+            Some(previous) => output.push_str(&format!(
+                "{:<12} {:>14}   {:>14}   {}\n",
+                label,
+                current,
+                format!("{:.*}{}", decimals, previous, unit),
+                percent_change(previous, value)
+            )),
+            None => output.push_str(&format!("{:<12} {:>14}\n", label, current)),
+        }
+    }
+    output
+}
+
+fn percent_change(previous: f64, current: f64) -> String {
+    if previous == 0.0 {
+        return "n/a".to_string();
+    }
+    format!("{:+.1}%", (current - previous) / previous * 100.0)
+}
+
+/// High-water mark of resident memory for this process, where the platform
+/// reports it.
+fn peak_memory_kb() -> Option<u64> {
+    parse_vm_hwm(&fs::read_to_string("/proc/self/status").ok()?)
+}
+
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(mean_ms: f64) -> BenchReport {
+        BenchReport {
+            version: "0.2.0".to_string(),
+            runs: 3,
+            files: 10,
+            symbols: 100,
+            mean_ms,
+            best_ms: mean_ms,
+            files_per_sec: 10.0 / (mean_ms / 1000.0),
+            symbols_per_sec: 100.0 / (mean_ms / 1000.0),
+            peak_memory_kb: Some(2048),
+        }
+    }
+
+    #[test]
+    fn test_parse_vm_hwm() {
+        let status = "Name:\trepomap\nVmPeak:\t  9000 kB\nVmHWM:\t    5120 kB\n";
+        assert_eq!(parse_vm_hwm(status), Some(5120));
+        assert_eq!(parse_vm_hwm("Name:\trepomap\n"), None);
+    }
+
+    #[test]
+    fn test_format_report_against_baseline() {
+        let output = format_report(&report(110.0), Some(&report(100.0)));
+        assert!(output.contains("Mean time"));
+        assert!(output.contains("+10.0%"));
+        assert!(output.contains("2.0 MiB"));
+        assert!(!format_report(&report(100.0), None).contains('%'));
+    }
+
+    #[test]
+    fn test_run_and_baseline_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+
+        let result = run(dir.path().to_str().unwrap(), 2).unwrap();
+        assert_eq!((result.files, result.symbols, result.runs), (1, 2, 2));
+        assert!(run(dir.path().to_str().unwrap(), 0).is_err());
+
+        let baseline = dir.path().join("baseline.json");
+        save_baseline(&baseline, &result).unwrap();
+        let loaded = load_baseline(&baseline).unwrap();
+        assert_eq!(
+            (loaded.files, loaded.symbols),
+            (result.files, result.symbols)
+        );
+        assert!((loaded.mean_ms - result.mean_ms).abs() < 1e-9);
+    }
+}
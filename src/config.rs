@@ -0,0 +1,350 @@
+use anyhow::{Context, Result, bail};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{ArgAction, Command, CommandFactory, FromArgMatches, Parser};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Args, Command as Subcommand};
+
+/// Project config, read from the root being mapped.
+pub const PROJECT_FILE: &str = ".repomap.toml";
+
+/// The table of `[profile.NAME]` tables.
+const PROFILES_KEY: &str = "profile";
+
+/// Subcommands that take the map flags and so read config too.
+const MAP_SUBCOMMANDS: &[&str] = &["watch", "explain"];
+
+/// Map flag defaults from the user and project config files. Keys are long
+/// flag names, like `max-tokens = 8000` or `exclude = ["fixtures/"]`; the
+/// project file wins over the user file, and flags on the command line win
+/// over both. `[profile.NAME]` tables hold settings that apply over the rest
+/// when that profile is selected.
+#[derive(Default, Debug)]
+pub struct Config {
+    settings: Vec<Setting>,
+    profiles: Vec<Profile>,
+}
+
+#[derive(Debug)]
+struct Profile {
+    name: String,
+    settings: Vec<Setting>,
+}
+
+#[derive(Debug)]
+struct Setting {
+    key: String,
+    values: Vec<String>,
+    source: PathBuf,
+}
+
+impl Config {
+    /// Reads the user config, then `root`'s [`PROJECT_FILE`]; either may be
+    /// missing.
+    pub fn load(root: &str) -> Result<Self> {
+        let mut config = Self::default();
+        if let Some(path) = user_config_path() {
+            config.read(&path)?;
+        }
+        config.read(&Path::new(root).join(PROJECT_FILE))?;
+        Ok(config)
+    }
+
+    fn read(&mut self, path: &Path) -> Result<()> {
+        match fs::read_to_string(path) {
+            Ok(text) => self.merge(&text, path),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Adds the settings in `text`, read from `source`, over the current ones.
+    fn merge(&mut self, text: &str, source: &Path) -> Result<()> {
+        let mut table: toml::Table = text
+            .parse()
+            .with_context(|| format!("invalid TOML in {}", source.display()))?;
+        if let Some(profiles) = table.remove(PROFILES_KEY) {
+            let toml::Value::Table(profiles) = profiles else {
+                bail!("'{}' in {} must be a table", PROFILES_KEY, source.display());
+            };
+            for (name, profile) in profiles {
+                let toml::Value::Table(profile) = profile else {
+                    bail!("[profile.{}] in {} must be a table", name, source.display());
+                };
+                let index = match self.profiles.iter().position(|p| p.name == name) {
+                    Some(index) => index,
+                    None => {
+                        self.profiles.push(Profile {
+                            name,
+                            settings: Vec::new(),
+                        });
+                        self.profiles.len() - 1
+                    }
+                };
+                merge_settings(&mut self.profiles[index].settings, profile, source)?;
+            }
+        }
+        merge_settings(&mut self.settings, table, source)
+    }
+
+    /// Puts the settings of the profile `name` over the others.
+    pub fn select(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.profiles.iter().position(|p| p.name == name) else {
+            let names: Vec<&str> = self.profiles.iter().map(|p| p.name.as_str()).collect();
+            if names.is_empty() {
+                bail!(
+                    "no profile '{}': no [profile.*] tables are configured",
+                    name
+                );
+            }
+            bail!("no profile '{}' (configured: {})", name, names.join(", "));
+        };
+        for setting in self.profiles.swap_remove(index).settings {
+            self.settings.retain(|s| s.key != setting.key);
+            self.settings.push(setting);
+        }
+        Ok(())
+    }
+
+    /// Checks every setting against `cmd`'s flags and makes it their default,
+    /// in `cmd` and in [`MAP_SUBCOMMANDS`].
+    pub fn apply(&self, mut cmd: Command) -> Result<Command> {
+        for setting in &self.settings {
+            setting.check(&cmd)?;
+        }
+        for setting in &self.settings {
+            cmd = setting.apply(cmd);
+            for &name in MAP_SUBCOMMANDS {
+                cmd = cmd.mut_subcommand(name, |sub| setting.apply(sub));
+            }
+        }
+        Ok(cmd)
+    }
+}
+
+impl Setting {
+    fn id(&self) -> String {
+        self.key.replace('-', "_")
+    }
+
+    /// Fails unless the key names a flag and each value parses as one of its
+    /// values.
+    fn check(&self, cmd: &Command) -> Result<()> {
+        let id = self.id();
+        let source = self.source.display();
+        let Some(arg) = cmd
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str() && arg.get_long().is_some())
+        else {
+            bail!("unknown key '{}' in {}", self.key, source);
+        };
+        if self.values.len() != 1 && !matches!(arg.get_action(), ArgAction::Append) {
+            bail!("'{}' in {} takes a single value", self.key, source);
+        }
+        let long = arg.get_long().expect("found by its long name");
+        for value in &self.values {
+            if matches!(arg.get_action(), ArgAction::SetTrue) {
+                if value != "true" && value != "false" {
+                    bail!("'{}' in {} must be true or false", self.key, source);
+                }
+                continue;
+            }
+            // Parse it as the flag would be, ignoring complaints about other
+            // flags it needs
+            let flag = format!("--{}={}", long, value);
+            if let Err(e) = cmd.clone().try_get_matches_from(["repomap", flag.as_str()])
+                && matches!(
+                    e.kind(),
+                    ErrorKind::InvalidValue | ErrorKind::ValueValidation
+                )
+            {
+                bail!("'{}' in {}: {}", self.key, source, invalid_value(value, &e));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, cmd: Command) -> Command {
+        cmd.mut_arg(self.id(), |arg| arg.default_values(self.values.clone()))
+    }
+}
+
+/// Clap's complaint about `value`, without its command-line framing.
+fn invalid_value(value: &str, err: &clap::Error) -> String {
+    if let Some(source) = std::error::Error::source(err) {
+        return format!("invalid value '{}': {}", value, source);
+    }
+    match err.get(ContextKind::ValidValue) {
+        Some(ContextValue::Strings(valid)) => format!(
+            "invalid value '{}' (expected one of: {})",
+            value,
+            valid.join(", ")
+        ),
+        _ => format!("invalid value '{}'", value),
+    }
+}
+
+/// Adds `table`'s keys to `settings`, replacing any already there.
+fn merge_settings(settings: &mut Vec<Setting>, table: toml::Table, source: &Path) -> Result<()> {
+    for (key, value) in table {
+        let values = match &value {
+            toml::Value::Array(items) => items.iter().map(scalar).collect(),
+            // `{ pyi = "python" }` for flags taking `KEY=VALUE` pairs
+            toml::Value::Table(pairs) => pairs
+                .iter()
+                .map(|(k, v)| scalar(v).map(|v| format!("{}={}", k, v)))
+                .collect(),
+            value => scalar(value).map(|v| vec![v]),
+        }
+        .with_context(|| format!("'{}' in {}", key, source.display()))?;
+        settings.retain(|s| s.key != key);
+        settings.push(Setting {
+            key,
+            values,
+            source: source.to_path_buf(),
+        });
+    }
+    Ok(())
+}
+
+/// A TOML value as it would be written on the command line.
+fn scalar(value: &toml::Value) -> Result<String> {
+    match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Integer(i) => Ok(i.to_string()),
+        toml::Value::Float(f) => Ok(f.to_string()),
+        toml::Value::Boolean(b) => Ok(b.to_string()),
+        _ => bail!("expected a string, number, boolean, or array of them"),
+    }
+}
+
+/// `$XDG_CONFIG_HOME/repomap/config.toml`, falling back to `~/.config`.
+fn user_config_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("repomap").join("config.toml"))
+}
+
+/// Parses the command line with config values as defaults for the map flags.
+/// The root to read [`PROJECT_FILE`] from comes from a first parse.
+pub fn parse_args() -> Result<Args> {
+    let args = Args::parse();
+    let map = match &args.command {
+        Some(Subcommand::Watch { map, .. } | Subcommand::Explain { map, .. }) => map,
+        _ => &args.map,
+    };
+    let mut config = Config::load(&map.root)?;
+    if let Some(profile) = &map.profile {
+        config.select(profile)?;
+    }
+    if config.settings.is_empty() {
+        return Ok(args);
+    }
+    let matches = config.apply(Args::command())?.get_matches();
+    Args::from_arg_matches(&matches).map_err(|e| e.exit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use repomap_core::OutputFormat;
+
+    fn parse(config: &Config, argv: &[&str]) -> Args {
+        let cmd = config.apply(Args::command()).unwrap();
+        let argv = std::iter::once("repomap").chain(argv.iter().copied());
+        Args::from_arg_matches(&cmd.try_get_matches_from(argv).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_config_sets_defaults_under_the_command_line() {
+        let mut config = Config::default();
+        let user = Path::new("config.toml");
+        config
+            .merge("depth = 5\nformat = \"json\"\nsummary = true\n", user)
+            .unwrap();
+        let project = Path::new(PROJECT_FILE);
+        config
+            .merge(
+                "depth = 2\nexclude = [\"fixtures/\", \"*.snap\"]\n",
+                project,
+            )
+            .unwrap();
+
+        let args = parse(&config, &[]).map;
+        assert_eq!(args.depth, Some(2));
+        assert_eq!(args.format, OutputFormat::Json);
+        assert!(args.summary);
+        assert_eq!(args.exclude, vec!["fixtures/", "*.snap"]);
+
+        let args = parse(&config, &["-d", "1", "-e", "gen/"]).map;
+        assert_eq!(args.depth, Some(1));
+        assert_eq!(args.exclude, vec!["gen/"]);
+
+        let Some(Subcommand::Watch { map, .. }) = parse(&config, &["watch"]).command else {
+            panic!("expected watch");
+        };
+        assert_eq!(map.depth, Some(2));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = Config::default();
+        let text = "depth = 3\n\
+                    [profile.backend]\ninclude = [\"server/**\"]\ndepth = 5\n\
+                    [profile.frontend]\ninclude = [\"web/**\"]\nformat = \"json\"\n";
+        config.merge(text, Path::new(PROJECT_FILE)).unwrap();
+        config
+            .merge("[profile.backend]\ndepth = 6\n", Path::new(PROJECT_FILE))
+            .unwrap();
+
+        let mut frontend = Config::default();
+        frontend.merge(text, Path::new(PROJECT_FILE)).unwrap();
+        frontend.select("frontend").unwrap();
+        let args = parse(&frontend, &[]).map;
+        assert_eq!(args.include, vec!["web/**"]);
+        assert_eq!(args.depth, Some(3));
+        assert_eq!(args.format, OutputFormat::Json);
+
+        config.select("backend").unwrap();
+        let args = parse(&config, &[]).map;
+        assert_eq!(args.include, vec!["server/**"]);
+        assert_eq!(args.depth, Some(6));
+        assert_eq!(args.format, OutputFormat::Markdown);
+
+        let mut unknown = Config::default();
+        unknown.merge(text, Path::new(PROJECT_FILE)).unwrap();
+        let err = unknown.select("docs").unwrap_err().to_string();
+        assert_eq!(err, "no profile 'docs' (configured: backend, frontend)");
+    }
+
+    #[test]
+    fn test_bad_settings_name_the_file() {
+        let check = |text: &str| {
+            let mut config = Config::default();
+            config.merge(text, Path::new(PROJECT_FILE))?;
+            config.apply(Args::command()).map(drop)
+        };
+        assert!(check("max-tokens = 8000\nkinds = [\"function\"]\n").is_ok());
+        assert!(check("map_extension = { pyi = \"python\", mjs = \"javascript\" }\n").is_ok());
+        let err = format!(
+            "{:#}",
+            check("map-extension = { pyi = \"cobol\" }\n").unwrap_err()
+        );
+        assert!(err.starts_with("'map-extension' in .repomap.toml: invalid value 'pyi=cobol': "));
+
+        let err = format!("{:#}", check("dept = 2\n").unwrap_err());
+        assert_eq!(err, "unknown key 'dept' in .repomap.toml");
+        let err = format!("{:#}", check("format = \"xml\"\n").unwrap_err());
+        assert!(err.starts_with(
+            "'format' in .repomap.toml: invalid value 'xml' (expected one of: markdown, json"
+        ));
+        let err = format!("{:#}", check("max-file-size = \"2tb\"\n").unwrap_err());
+        assert!(err.starts_with("'max-file-size' in .repomap.toml: invalid value '2tb': "));
+        assert!(check("depth = [1, 2]\n").is_err());
+        assert!(check("root = \"src\"\n").is_err());
+        assert!(check("[table]\nkey = 1\n").is_err());
+        assert!(check("depth = { a = { b = 1 } }\n").is_err());
+    }
+}
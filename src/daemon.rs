@@ -0,0 +1,244 @@
+use anyhow::{Context, Result, anyhow, bail};
+use notify::{Event, RecursiveMode, Watcher};
+use repomap_core::{MapOutput, RepoMapBuilder};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Keeps the map of `root` in memory, refreshes it as files change, and
+/// answers one JSON request per connection on a Unix socket.
+#[cfg(unix)]
+pub fn serve(root: &str, socket: &Path) -> Result<()> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail!("a daemon is already listening on {}", socket.display());
+        }
+        // Left behind by a daemon that did not shut down cleanly
+        std::fs::remove_file(socket)?;
+    }
+
+    let builder = crate::new_builder(root);
+    let map = Arc::new(Mutex::new(builder.build()?));
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher
+        .watch(Path::new(root), RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch {}", root))?;
+
+    let refreshed = Arc::clone(&map);
+    thread::spawn(move || {
+        if let Err(e) = refresh(&builder, &refreshed, &rx) {
+            eprintln!("Stopped refreshing the map: {:#}", e);
+        }
+    });
+
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("failed to bind {}", socket.display()))?;
+    eprintln!("Serving {} on {}", root, socket.display());
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let map = Arc::clone(&map);
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(&map, stream) {
+                eprintln!("Query failed: {:#}", e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Applies each batch of file changes to the shared map until the watcher
+/// stops. A batch that fails to rebuild is reported and the map is served
+/// as it was, to be caught up by the next one.
+#[cfg(unix)]
+fn refresh(
+    builder: &RepoMapBuilder,
+    map: &Mutex<MapOutput>,
+    rx: &Receiver<notify::Result<Event>>,
+) -> Result<()> {
+    loop {
+        let changed: HashSet<PathBuf> = crate::watch::next_batch(rx, REFRESH_DEBOUNCE)?
+            .into_iter()
+            .filter_map(|path| path.canonicalize().ok())
+            .collect();
+        let previous = map
+            .lock()
+            .map_err(|_| anyhow!("map lock poisoned"))?
+            .clone();
+        match builder.rebuild(previous, &changed) {
+            Ok(rebuilt) => *map.lock().map_err(|_| anyhow!("map lock poisoned"))? = rebuilt,
+            Err(e) => eprintln!("Failed to refresh the map: {:#}", e),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn serve(_root: &str, _socket: &Path) -> Result<()> {
+    bail!("the query daemon requires Unix domain sockets, which this platform does not support")
+}
+
+/// Sends one query to a running daemon and returns the matching symbols.
+#[cfg(unix)]
+pub fn query(socket: &Path, symbol: &str, kind: Option<&str>) -> Result<Vec<Value>> {
+    let mut stream = UnixStream::connect(socket).with_context(|| {
+        format!(
+            "no daemon listening on {} (start one with 'repomap serve --socket')",
+            socket.display()
+        )
+    })?;
+    serde_json::to_writer(&mut stream, &json!({ "query": symbol, "kind": kind }))?;
+    writeln!(stream)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let response: Value = serde_json::from_str(&line)?;
+    if let Some(error) = response["error"].as_str() {
+        bail!("{}", error);
+    }
+    response["matches"]
+        .as_array()
+        .cloned()
+        .ok_or_else(|| anyhow!("malformed daemon response: {}", line.trim_end()))
+}
+
+#[cfg(not(unix))]
+pub fn query(_socket: &Path, _symbol: &str, _kind: Option<&str>) -> Result<Vec<Value>> {
+    bail!("the query daemon requires Unix domain sockets, which this platform does not support")
+}
+
+/// Renders a query match as `path:line | kind | Parent > name`.
+pub fn format_match(m: &Value) -> String {
+    let field = |key: &str| m[key].as_str().unwrap_or_default().to_string();
+    let name = match m["parent"].as_str() {
+        Some(parent) => format!("{} > {}", parent, field("name")),
+        None => field("name"),
+    };
+    format!(
+        "{}:{} | {} | {}",
+        field("path"),
+        m["line"],
+        field("kind"),
+        name
+    )
+}
+
+#[cfg(unix)]
+fn handle_connection(map: &Mutex<MapOutput>, stream: UnixStream) -> Result<()> {
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let response = {
+        let map = map.lock().map_err(|_| anyhow!("map lock poisoned"))?;
+        respond(&map, &line)
+    };
+
+    let mut stream = &stream;
+    serde_json::to_writer(&mut stream, &response)?;
+    writeln!(stream)?;
+    Ok(())
+}
+
+/// Answers a `{"query": ..., "kind": ...}` request line.
+fn respond(map: &MapOutput, line: &str) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return json!({ "error": format!("invalid request: {}", e) }),
+    };
+    let Some(query) = request["query"].as_str() else {
+        return json!({ "error": "missing required field: query" });
+    };
+
+    let matches: Vec<Value> = map
+        .find_symbols(query, request["kind"].as_str())
+        .into_iter()
+        .map(|(file, sym)| crate::http::symbol_json(file, sym))
+        .collect();
+    json!({ "matches": matches })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_map() -> MapOutput {
+        MapOutput::from_sources(
+            ".",
+            [("src/lib.rs", "struct Cache {}\nfn cache_get() {}\n")],
+        )
+    }
+
+    #[test]
+    fn test_respond() {
+        let map = sample_map();
+
+        let found = respond(&map, r#"{"query": "cache"}"#);
+        assert_eq!(found["matches"].as_array().unwrap().len(), 2);
+
+        let filtered = respond(&map, r#"{"query": "cache", "kind": "struct_item"}"#);
+        assert_eq!(filtered["matches"][0]["name"], "Cache");
+
+        assert!(respond(&map, "{}")["error"].is_string());
+        assert!(respond(&map, "not json")["error"].is_string());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_refresh_keeps_the_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("lib.rs");
+        std::fs::write(&lib, "fn kept() {}").unwrap();
+        let builder = RepoMapBuilder::new(dir.path().to_str().unwrap());
+        let map = Mutex::new(builder.build().unwrap());
+
+        // Rebuilding fails on the pattern until it's fixed
+        let attributes = dir.path().join(".gitattributes");
+        std::fs::write(&attributes, "[ linguist-generated\n").unwrap();
+        let (tx, rx) = mpsc::channel();
+        tx.send(Ok(Event::default().add_path(attributes.clone())))
+            .unwrap();
+        tx.send(Err(notify::Error::generic("dropped events")))
+            .unwrap();
+        drop(tx);
+        assert!(refresh(&builder, &map, &rx).is_err());
+        assert_eq!(map.lock().unwrap().files()[0].symbols[0].name, "kept");
+
+        std::fs::remove_file(&attributes).unwrap();
+        std::fs::write(&lib, "fn edited() {}").unwrap();
+        let (tx, rx) = mpsc::channel();
+        tx.send(Ok(Event::default().add_path(lib.clone()))).unwrap();
+        drop(tx);
+        assert!(refresh(&builder, &map, &rx).is_err());
+        assert_eq!(map.lock().unwrap().files()[0].symbols[0].name, "edited");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_connection_round_trip() {
+        let (mut client, server) = UnixStream::pair().unwrap();
+        let map = Mutex::new(sample_map());
+
+        writeln!(client, r#"{{"query": "get"}}"#).unwrap();
+        handle_connection(&map, server).unwrap();
+
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["matches"][0]["path"], "src/lib.rs");
+        assert_eq!(
+            format_match(&response["matches"][0]),
+            "src/lib.rs:2 | function_item | cache_get"
+        );
+    }
+}
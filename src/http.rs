@@ -0,0 +1,152 @@
+use anyhow::{Result, anyhow};
+use repomap_core::formatter::{self, StructuredFile, StructuredMap};
+use repomap_core::{FileSymbols, Symbol, languages};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use tiny_http::{Header, Method, Response, Server};
+use url::Url;
+
+/// One `/symbols` match; also the shape of `repomap query` results.
+pub fn symbol_json(file: &FileSymbols, sym: &Symbol) -> Value {
+    json!({
+        "path": file.path,
+        "name": sym.name,
+        "kind": sym.kind,
+        "parent": sym.parent,
+        "line": sym.line,
+        "end_line": sym.end_line,
+    })
+}
+
+/// Read-only JSON API over HTTP. Each request maps the repository fresh.
+pub struct HttpServer {
+    root: String,
+}
+
+impl HttpServer {
+    pub fn new(root: impl Into<String>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn run(&self, addr: &str) -> Result<()> {
+        let server = Server::http(addr).map_err(|e| anyhow!("failed to bind {}: {}", addr, e))?;
+        eprintln!("Serving {} on http://{}", self.root, addr);
+
+        let content_type =
+            Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+
+        for request in server.incoming_requests() {
+            let (status, body) = if *request.method() == Method::Get {
+                self.handle(request.url())
+            } else {
+                (405, json!({ "error": "only GET is supported" }))
+            };
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(content_type.clone());
+            if let Err(e) = request.respond(response) {
+                eprintln!("Failed to send response: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Routes a request target such as `/symbols?q=parse` to a status code
+    /// and JSON body.
+    pub fn handle(&self, target: &str) -> (u16, Value) {
+        let Ok(url) = Url::parse("http://localhost").and_then(|base| base.join(target)) else {
+            return (400, json!({ "error": "invalid request target" }));
+        };
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+        let result = match url.path() {
+            "/map" => self.map(&query),
+            "/file" => self.file(&query),
+            "/symbols" => self.symbols(&query),
+            _ => {
+                return (
+                    404,
+                    json!({ "error": format!("no route for {}", url.path()) }),
+                );
+            }
+        };
+
+        match result {
+            Ok(body) => (200, body),
+            Err(e) => (400, json!({ "error": format!("{:#}", e) })),
+        }
+    }
+
+    fn map(&self, query: &HashMap<String, String>) -> Result<Value> {
+        let root = crate::resolve_in_root(&self.root, query.get("path").map(String::as_str))?;
+        let depth = query.get("depth").map(|d| d.parse()).transpose()?;
+
+        let map = crate::new_builder(&root).depth(depth).build()?;
+        Ok(serde_json::to_value(StructuredMap::new(
+            &map.root, &map.stats,
+        ))?)
+    }
+
+    fn file(&self, query: &HashMap<String, String>) -> Result<Value> {
+        let relative = query
+            .get("path")
+            .ok_or_else(|| anyhow!("missing required parameter: path"))?;
+        let path = crate::resolve_in_root(&self.root, Some(relative))?;
+        let lang = languages::infer_language(Path::new(&path))
+            .ok_or_else(|| anyhow!("unsupported file type: {}", relative))?;
+
+        let file = formatter::process_file(Path::new(&path), lang)?;
+        Ok(serde_json::to_value(StructuredFile::from(&file))?)
+    }
+
+    fn symbols(&self, query: &HashMap<String, String>) -> Result<Value> {
+        let q = query
+            .get("q")
+            .ok_or_else(|| anyhow!("missing required parameter: q"))?;
+
+        let map = crate::new_builder(&self.root).build()?;
+        let matches: Vec<Value> = map
+            .find_symbols(q, query.get("kind").map(String::as_str))
+            .into_iter()
+            .map(|(file, sym)| symbol_json(file, sym))
+            .collect();
+        Ok(Value::Array(matches))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("api")).unwrap();
+        fs::write(
+            dir.path().join("api/handlers.go"),
+            "package api\n\nfunc GetUser() {}\nfunc ListUsers() {}\n",
+        )
+        .unwrap();
+        let server = HttpServer::new(dir.path().to_str().unwrap());
+
+        let (status, map) = server.handle("/map");
+        assert_eq!(status, 200);
+        assert_eq!(map["file_count"], 1);
+
+        let (status, file) = server.handle("/file?path=api%2Fhandlers.go");
+        assert_eq!(status, 200);
+        assert_eq!(file["symbol_count"], 2);
+
+        let (status, symbols) = server.handle("/symbols?q=user&kind=function_declaration");
+        assert_eq!(status, 200);
+        assert_eq!(symbols.as_array().unwrap().len(), 2);
+        assert_eq!(symbols[0]["line"], 3);
+
+        assert_eq!(server.handle("/file").0, 400);
+        assert_eq!(server.handle("/file?path=../secret.go").0, 400);
+        assert_eq!(server.handle("/nope").0, 404);
+    }
+}
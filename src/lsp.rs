@@ -0,0 +1,340 @@
+use anyhow::{Context, Result, anyhow};
+use repomap_core::{FileSymbols, MapOutput, Symbol, SymbolKind, formatter, languages};
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// LSP `SymbolKind` values for repomap's normalized symbol kinds, told
+/// apart by the node kind where LSP has a closer match, like enums and
+/// constants.
+fn lsp_kind(sym: &Symbol) -> u8 {
+    const MODULE: u8 = 2;
+    const CLASS: u8 = 5;
+    const METHOD: u8 = 6;
+    const ENUM: u8 = 10;
+    const INTERFACE: u8 = 11;
+    const FUNCTION: u8 = 12;
+    const VARIABLE: u8 = 13;
+    const CONSTANT: u8 = 14;
+    const STRUCT: u8 = 23;
+    const TYPE_PARAMETER: u8 = 26;
+
+    let kind = sym.kind.as_str();
+    match sym.normalized_kind() {
+        SymbolKind::Function | SymbolKind::Macro | SymbolKind::Test => FUNCTION,
+        SymbolKind::Method => METHOD,
+        SymbolKind::Struct => STRUCT,
+        SymbolKind::Class => CLASS,
+        SymbolKind::Interface => INTERFACE,
+        SymbolKind::Type | SymbolKind::Other if kind.starts_with("enum") => ENUM,
+        SymbolKind::Type if kind.starts_with("union") => STRUCT,
+        // Type aliases and the like; LSP has no closer kind for them
+        SymbolKind::Type => TYPE_PARAMETER,
+        SymbolKind::Heading => MODULE,
+        SymbolKind::Other if kind == "constant" || kind.starts_with("const_") => CONSTANT,
+        SymbolKind::Other
+            if kind == "mod_item"
+                || kind == "ns"
+                || ["module", "namespace", "package"]
+                    .iter()
+                    .any(|scope| kind.contains(scope)) =>
+        {
+            MODULE
+        }
+        SymbolKind::Other => VARIABLE,
+    }
+}
+
+fn symbol_information(uri: &Url, sym: &Symbol) -> Value {
+    // LSP positions are zero-based; repomap lines are one-based
+    let mut info = json!({
+        "name": sym.name,
+        "kind": lsp_kind(sym),
+        "location": {
+            "uri": uri.as_str(),
+            "range": {
+                "start": { "line": sym.line - 1, "character": 0 },
+                "end": { "line": sym.end_line - 1, "character": 0 },
+            },
+        },
+    });
+    if let Some(parent) = &sym.parent {
+        info["containerName"] = json!(parent);
+    }
+    info
+}
+
+fn file_uri(file: &FileSymbols) -> Option<Url> {
+    let path = Path::new(&file.path).canonicalize().ok()?;
+    Url::from_file_path(path).ok()
+}
+
+/// Answers `workspace/symbol` and `textDocument/documentSymbol` from the
+/// tree-sitter extraction. The workspace map is built lazily and dropped
+/// whenever the client reports a saved or changed file.
+pub struct LspServer {
+    root: PathBuf,
+    map: Option<MapOutput>,
+    shutdown: bool,
+}
+
+impl LspServer {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            map: None,
+            shutdown: false,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.serve(&mut io::stdin().lock(), &mut io::stdout().lock())
+    }
+
+    /// Answers the messages read from `reader` on `writer` until `exit` or
+    /// the end of input. A message that isn't JSON gets a parse error.
+    fn serve(&mut self, reader: &mut impl BufRead, writer: &mut impl Write) -> Result<()> {
+        while let Some(message) = read_message(reader)? {
+            let response = match serde_json::from_str::<Value>(&message) {
+                Ok(request) if request["method"] == "exit" => break,
+                Ok(request) => self.handle(&request),
+                Err(e) => Some(error_response(Value::Null, -32700, &e.to_string())),
+            };
+            if let Some(response) = response {
+                write_message(writer, &response)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles one JSON-RPC message. Notifications get no response.
+    pub fn handle(&mut self, request: &Value) -> Option<Value> {
+        let method = request["method"].as_str().unwrap_or_default();
+        let params = &request["params"];
+
+        if matches!(
+            method,
+            "textDocument/didSave" | "textDocument/didChange" | "workspace/didChangeWatchedFiles"
+        ) {
+            self.map = None;
+        }
+
+        let id = request.get("id").cloned()?;
+        let result = if self.shutdown && method != "shutdown" {
+            Err((-32600, "server is shutting down".to_string()))
+        } else {
+            match method {
+                "initialize" => Ok(self.initialize(params)),
+                "shutdown" => {
+                    self.shutdown = true;
+                    Ok(Value::Null)
+                }
+                "workspace/symbol" => self
+                    .workspace_symbols(params["query"].as_str().unwrap_or_default())
+                    .map_err(|e| (-32603, format!("{:#}", e))),
+                "textDocument/documentSymbol" => self
+                    .document_symbols(params["textDocument"]["uri"].as_str().unwrap_or_default())
+                    .map_err(|e| (-32603, format!("{:#}", e))),
+                _ => Err((-32601, format!("method not found: {}", method))),
+            }
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        if let Some(root) = params["rootUri"]
+            .as_str()
+            .and_then(|uri| Url::parse(uri).ok())
+            .and_then(|uri| uri.to_file_path().ok())
+        {
+            self.root = root;
+            self.map = None;
+        }
+
+        json!({
+            "capabilities": {
+                "workspaceSymbolProvider": true,
+                "documentSymbolProvider": true,
+                "textDocumentSync": { "openClose": false, "save": true },
+            },
+            "serverInfo": { "name": "repomap", "version": env!("CARGO_PKG_VERSION") },
+        })
+    }
+
+    fn workspace_symbols(&mut self, query: &str) -> Result<Value> {
+        if self.map.is_none() {
+            let root = self.root.to_str().context("workspace root is not UTF-8")?;
+            self.map = Some(crate::new_builder(root).build()?);
+        }
+        let map = self.map.as_ref().expect("map built above");
+
+        let symbols = map
+            .find_symbols(query, None)
+            .into_iter()
+            .filter_map(|(file, sym)| Some(symbol_information(&file_uri(file)?, sym)))
+            .collect();
+        Ok(Value::Array(symbols))
+    }
+
+    fn document_symbols(&self, uri: &str) -> Result<Value> {
+        let uri = Url::parse(uri)?;
+        let path = uri
+            .to_file_path()
+            .map_err(|_| anyhow!("not a file URI: {}", uri))?;
+
+        let Some(lang) = languages::infer_language(&path) else {
+            return Ok(json!([]));
+        };
+        let file = formatter::process_file(&path, lang)?;
+        Ok(Value::Array(
+            file.symbols
+                .iter()
+                .map(|sym| symbol_information(&uri, sym))
+                .collect(),
+        ))
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Reads one `Content-Length` framed message. Returns `None` at end of input.
+fn read_message(reader: &mut impl BufRead) -> Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            content_length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+
+    let length = content_length.context("message is missing a Content-Length header")?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(String::from_utf8(body)?))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_message_framing_round_trip() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({ "id": 1 })).unwrap();
+        write_message(&mut buffer, &json!({ "id": 2 })).unwrap();
+
+        let mut reader = io::Cursor::new(buffer);
+        assert_eq!(read_message(&mut reader).unwrap().unwrap(), r#"{"id":1}"#);
+        assert_eq!(read_message(&mut reader).unwrap().unwrap(), r#"{"id":2}"#);
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_malformed_message_gets_a_parse_error() {
+        let mut input = Vec::new();
+        write!(input, "Content-Length: 8\r\n\r\nnot json").unwrap();
+        write_message(&mut input, &json!({ "id": 1, "method": "shutdown" })).unwrap();
+        write_message(&mut input, &json!({ "method": "exit" })).unwrap();
+
+        let mut output = Vec::new();
+        LspServer::new(".")
+            .serve(&mut io::Cursor::new(input), &mut output)
+            .unwrap();
+        let mut output = io::Cursor::new(output);
+        let error: Value =
+            serde_json::from_str(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        assert_eq!(error["error"]["code"], -32700);
+        assert_eq!(error["id"], Value::Null);
+        let shutdown: Value =
+            serde_json::from_str(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        assert_eq!(shutdown["id"], 1);
+        assert!(read_message(&mut output).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_workspace_and_document_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("shapes.ts");
+        fs::write(&file, "class Circle {\n  area() {\n    return 1;\n  }\n}\n").unwrap();
+        let uri = Url::from_file_path(file.canonicalize().unwrap()).unwrap();
+
+        let mut server = LspServer::new(dir.path());
+
+        let workspace = server
+            .handle(
+                &json!({ "id": 1, "method": "workspace/symbol", "params": { "query": "AREA" } }),
+            )
+            .unwrap();
+        let symbols = workspace["result"].as_array().unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0]["name"], "area");
+        assert_eq!(symbols[0]["kind"], 6);
+        assert_eq!(symbols[0]["containerName"], "Circle");
+        assert_eq!(symbols[0]["location"]["uri"], uri.as_str());
+        assert_eq!(symbols[0]["location"]["range"]["start"]["line"], 1);
+
+        let document = server
+            .handle(&json!({
+                "id": 2,
+                "method": "textDocument/documentSymbol",
+                "params": { "textDocument": { "uri": uri.as_str() } },
+            }))
+            .unwrap();
+        assert_eq!(document["result"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_symbol_kinds() {
+        let map = repomap_core::MapOutput::from_sources(
+            ".",
+            [(
+                "lib.rs",
+                "enum Mode { A }\ntype Id = u32;\nconst MAX: u32 = 1;\nstatic NAME: &str = \"\";\n\
+                 mod store {}\nunion Bits { a: u8 }\n",
+            )],
+        );
+        let kinds: Vec<(&str, u8)> = map.files()[0]
+            .symbols
+            .iter()
+            .map(|sym| (sym.name.as_str(), lsp_kind(sym)))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Mode", 10),
+                ("Id", 26),
+                ("MAX", 14),
+                ("NAME", 13),
+                ("store", 2),
+                ("Bits", 23)
+            ]
+        );
+    }
+}
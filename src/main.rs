@@ -1,18 +1,58 @@
-mod formatter;
-mod languages;
-mod parser;
-mod walk;
+mod bench;
+mod config;
+mod daemon;
+mod http;
+mod lsp;
+mod mcp;
+mod watch;
 
-use anyhow::Result;
-use clap::Parser;
-use std::path::PathBuf;
+use anyhow::{Result, bail};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use formatter::RepoStats;
-use languages::Language;
+use repomap_core::formatter;
+use repomap_core::rank::Weights;
+use repomap_core::tokens::{Model, TokenCountingWriter, Tokenizer};
+use repomap_core::walk;
+use repomap_core::{
+    Language, ListedFile, MapOutput, OutputFormat, QueryMode, QueryRegistry, RepoMapBuilder,
+    RepoStats, SymbolKind, TestFilter,
+};
+
+// Files to always exclude (generated by repomap)
+const EXCLUDED_FILES: &[&str] = &["repomap.md", "CLAUDE.md"];
+
+const CACHE_DIR: &str = ".repomap/cache";
+
+const DEFAULT_SOCKET: &str = ".repomap.sock";
+
+// More parts than this means --split-tokens is set far too low
+const MAX_PARTS: usize = 100;
+
+// Weight for a --weight path given without one
+const FOCUS_WEIGHT: f64 = 10.0;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about = "Generate a repository map for AI context")]
+#[command(
+    author,
+    version,
+    about = "Generate a repository map for AI context",
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    map: MapArgs,
+}
+
+/// Options shared by a one-off run and `repomap watch`.
+#[derive(clap::Args, Debug)]
+struct MapArgs {
     #[arg(
         short,
         long,
@@ -26,28 +66,624 @@ struct Args {
     #[arg(short, long, default_missing_value = "repomap.md", num_args = 0..=1)]
     output: Option<PathBuf>,
 
-    #[arg(short, long)]
+    #[arg(
+        short,
+        long,
+        value_name = "GLOB",
+        help = "Leave out files matching GLOB (gitignore syntax, e.g. \"**/*.gen.ts\" or \"fixtures/\"); repeatable"
+    )]
     exclude: Vec<String>,
 
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Only map files matching GLOB (gitignore syntax, relative to the root); repeatable"
+    )]
+    include: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "EXT=LANG",
+        value_parser = parse_extension_mapping,
+        help = "Parse files ending in .EXT as LANG, e.g. pyi=python; repeatable"
+    )]
+    map_extension: Vec<(String, Language)>,
+
+    #[arg(
+        long,
+        value_name = "LANG=FILE",
+        value_parser = parse_query_file,
+        help = "Also extract symbols matching the tree-sitter query in FILE for LANG; repeatable"
+    )]
+    query: Vec<(Language, PathBuf)>,
+
+    #[arg(
+        long,
+        value_name = "LANG=FILE",
+        value_parser = parse_query_file,
+        help = "Use the tree-sitter query in FILE instead of the built-in one for LANG"
+    )]
+    replace_query: Vec<(Language, PathBuf)>,
+
+    #[arg(
+        long,
+        help = "Guess the language of files with no extension (and of .ts files) from their content"
+    )]
+    detect_content: bool,
+
     #[arg(short, long)]
     depth: Option<usize>,
 
     #[arg(short, long)]
     summary: bool,
 
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Markdown,
+        help = "Output format for the generated map"
+    )]
+    format: OutputFormat,
+
     #[arg(
         long,
         help = "Output to CLAUDE.md with smart update (append or replace)"
     )]
     claude: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "template",
+        help = "Append a Mermaid diagram of file import relationships (markdown only)"
+    )]
+    mermaid: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "format",
+        help = "Render the map with a Tera template instead of a built-in format"
+    )]
+    template: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Leave out test files and test code (#[cfg(test)] modules, test_* functions)"
+    )]
+    skip_tests: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "skip_tests",
+        help = "Map only test code, listing each test case (#[test] fns, it()/describe() blocks, test_* functions)"
+    )]
+    tests_only: bool,
+
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = parse_size,
+        help = "Skip files larger than SIZE (e.g. 512kb, 2mb) and list them at the end of the map"
+    )]
+    max_file_size: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Map dependency and build directories (node_modules, vendor, target, dist, build, ...)"
+    )]
+    include_vendored: bool,
+
+    #[arg(
+        long,
+        help = "Map files ignored by .gitignore, .git/info/exclude and the global git excludes"
+    )]
+    no_gitignore: bool,
+
+    #[arg(long, help = "Map hidden files and directories (dotfiles)")]
+    hidden: bool,
+
+    #[arg(
+        long,
+        help = "Don't apply .gitignore and .ignore files from above the root"
+    )]
+    no_ignore_parent: bool,
+
+    #[arg(
+        long,
+        help = "Follow symlinks, skipping cycles and links to other filesystems"
+    )]
+    follow_symlinks: bool,
+
+    #[arg(
+        long,
+        help = "Map generated code (linguist-generated in .gitattributes, @generated headers, *.pb.go, ...)"
+    )]
+    include_generated: bool,
+
+    #[arg(
+        long,
+        help = "Count Markdown links to other files in the repo as imports"
+    )]
+    doc_links: bool,
+
+    #[arg(
+        long,
+        help = "Extract symbols from fenced code blocks in Markdown, like ```rust examples"
+    )]
+    code_blocks: bool,
+
+    #[arg(
+        long,
+        help = "Parse every file instead of reusing results from .repomap/cache"
+    )]
+    no_cache: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Apply the [profile.NAME] settings from .repomap.toml"
+    )]
+    profile: Option<String>,
+
+    #[arg(
+        long,
+        help = "List the files that would be mapped, with their language, and why the others wouldn't be, without parsing anything"
+    )]
+    list: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Tokenizer used for the token estimate [default: cl100k]"
+    )]
+    tokenizer: Option<Tokenizer>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Trim the map to fit N tokens, dropping low-priority symbols first"
+    )]
+    max_tokens: Option<usize>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Use this model's tokenizer and a budget of 10% of its context window"
+    )]
+    model: Option<Model>,
+
+    #[arg(
+        long,
+        help = "Order files by importance (PageRank over imports and references) instead of path"
+    )]
+    rank: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH[=W]",
+        value_parser = parse_weight,
+        help = "Rank files at or under PATH W times higher (default 10) for --rank and --max-tokens"
+    )]
+    weight: Vec<(PathBuf, f64)>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Full detail for PATH, top-level symbols for its import neighbors, headlines elsewhere"
+    )]
+    focus: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "KINDS",
+        value_delimiter = ',',
+        help = "Only list symbols of these kinds, e.g. functions,classes,interfaces"
+    )]
+    kinds: Vec<SymbolKind>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "List at most N symbols per file, favoring top-level, public, and longer ones"
+    )]
+    max_symbols_per_file: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Map only the N most important files by rank and say so in the header"
+    )]
+    max_files: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Fold each directory with more than N files into one summary line"
+    )]
+    collapse_dirs: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Keep full detail for paths matching GLOB under --collapse-dirs"
+    )]
+    expand: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["claude", "summary", "mermaid", "template", "max_tokens"],
+        help = "Write <output>.part1.md, .part2.md, ... each under N tokens"
+    )]
+    split_tokens: Option<usize>,
+}
+
+impl MapArgs {
+    /// `--tokenizer`, else the `--model` preset's.
+    fn tokenizer(&self) -> Tokenizer {
+        self.tokenizer
+            .or(self.model.map(Model::tokenizer))
+            .unwrap_or_default()
+    }
+
+    /// `--max-tokens`, else the `--model` preset's budget for Markdown maps.
+    fn max_tokens(&self) -> Option<usize> {
+        let budgeted = self.format == OutputFormat::Markdown
+            && self.template.is_none()
+            && self.split_tokens.is_none();
+        self.max_tokens
+            .or(self.model.filter(|_| budgeted).map(Model::default_budget))
+    }
+
+    fn tests(&self) -> TestFilter {
+        if self.skip_tests {
+            TestFilter::Skip
+        } else if self.tests_only {
+            TestFilter::Only
+        } else {
+            TestFilter::Include
+        }
+    }
+
+    /// Whether the map is reordered or trimmed after it is built.
+    fn reshapes(&self) -> bool {
+        self.rank
+            || self.max_files.is_some()
+            || self.collapse_dirs.is_some()
+            || !self.kinds.is_empty()
+            || self.max_symbols_per_file.is_some()
+            || !self.focus.is_empty()
+            || self.max_tokens().is_some()
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve fresh maps to other tools
+    #[command(group(ArgGroup::new("mode").required(true)))]
+    Serve {
+        #[arg(
+            long,
+            group = "mode",
+            help = "Speak the Model Context Protocol over stdio"
+        )]
+        mcp: bool,
+
+        #[arg(
+            long,
+            group = "mode",
+            help = "Run a symbol-only Language Server over stdio"
+        )]
+        lsp: bool,
+
+        #[arg(
+            long,
+            group = "mode",
+            value_name = "ADDR",
+            help = "Serve a JSON API over HTTP, e.g. 127.0.0.1:7070"
+        )]
+        http: Option<String>,
+
+        #[arg(
+            long,
+            group = "mode",
+            value_name = "PATH",
+            default_missing_value = DEFAULT_SOCKET,
+            num_args = 0..=1,
+            help = "Keep the map warm and answer 'repomap query' on a Unix socket"
+        )]
+        socket: Option<PathBuf>,
+
+        #[arg(default_value = ".")]
+        root: String,
+    },
+
+    /// Time a full uncached run and report throughput and peak memory
+    Bench {
+        #[arg(default_value = ".")]
+        root: String,
+
+        #[arg(long, default_value_t = 5, help = "Number of timed runs")]
+        runs: usize,
+
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Compare against a report saved with --save"
+        )]
+        baseline: Option<PathBuf>,
+
+        #[arg(long, value_name = "PATH", help = "Save this report as a baseline")]
+        save: Option<PathBuf>,
+    },
+
+    /// Look up symbols in a running 'serve --socket' daemon
+    Query {
+        /// Substring of the symbol name (case-insensitive)
+        symbol: String,
+
+        #[arg(short, long, help = "Only match this symbol kind")]
+        kind: Option<String>,
+
+        #[arg(long, value_name = "PATH", default_value = DEFAULT_SOCKET)]
+        socket: PathBuf,
+
+        #[arg(long, help = "Print matches as JSON")]
+        json: bool,
+    },
+
+    /// Explain why a file is or isn't in the map
+    Explain {
+        /// The file to explain, relative to the current directory
+        path: PathBuf,
+
+        #[command(flatten)]
+        map: Box<MapArgs>,
+    },
+
+    /// Regenerate the map whenever files change, re-parsing only those files
+    Watch {
+        #[command(flatten)]
+        map: Box<MapArgs>,
+
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 300,
+            help = "Wait this long for edits to settle before regenerating"
+        )]
+        debounce: u64,
+    },
+}
+
+/// Builder for `root` that skips repomap's own generated files.
+fn new_builder(root: &str) -> RepoMapBuilder {
+    let mut builder = RepoMapBuilder::new(root);
+    for name in EXCLUDED_FILES {
+        builder = builder.skip_file_name(*name);
+    }
+    builder
+}
+
+/// Parses `PATH=W` or a bare `PATH`, which gets [`FOCUS_WEIGHT`].
+fn parse_weight(spec: &str) -> Result<(PathBuf, f64), String> {
+    let Some((path, weight)) = spec.rsplit_once('=') else {
+        return Ok((PathBuf::from(spec), FOCUS_WEIGHT));
+    };
+    match weight.parse::<f64>() {
+        Ok(weight) if weight.is_finite() && weight >= 0.0 => Ok((PathBuf::from(path), weight)),
+        _ => Err(format!(
+            "weight must be a non-negative number, got '{}'",
+            weight
+        )),
+    }
+}
+
+/// Parses an `EXT=LANG` extension mapping like `pyi=python`.
+fn parse_extension_mapping(spec: &str) -> Result<(String, Language), String> {
+    let Some((ext, lang)) = spec.split_once('=') else {
+        return Err(format!("expected EXT=LANG, got '{}'", spec));
+    };
+    let lang = Language::from_str(lang.trim(), true)?;
+    Ok((ext.trim().to_string(), lang))
+}
+
+/// Parses a `LANG=FILE` query override like `rust=queries/rust.scm`.
+fn parse_query_file(spec: &str) -> Result<(Language, PathBuf), String> {
+    let Some((lang, path)) = spec.split_once('=') else {
+        return Err(format!("expected LANG=FILE, got '{}'", spec));
+    };
+    let lang = Language::from_str(lang.trim(), true)?;
+    Ok((lang, PathBuf::from(path.trim())))
+}
+
+/// Parses a size like `512kb`, `2MB`, or `4096` (bytes). Units are powers
+/// of 1024.
+fn parse_size(spec: &str) -> Result<u64, String> {
+    let spec = spec.trim().to_ascii_lowercase();
+    let split = spec
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(spec.len());
+    let (number, unit) = spec.split_at(split);
+    let scale: u64 = match unit.trim() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => {
+            return Err(format!(
+                "unknown size unit '{}', use b, kb, mb, or gb",
+                unit
+            ));
+        }
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .ok_or_else(|| format!("invalid size '{}'", spec))
+}
+
+/// Joins a client-supplied relative path onto `root`, rejecting anything
+/// that could escape it.
+fn resolve_in_root(root: &str, relative: Option<&str>) -> Result<String> {
+    let Some(relative) = relative else {
+        return Ok(root.to_string());
+    };
+    let path = Path::new(relative);
+    if path.is_absolute() || relative.split(['/', '\\']).any(|c| c == "..") {
+        bail!("path must be relative to the repository root: {}", relative);
+    }
+    Ok(Path::new(root).join(path).display().to_string())
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
-    let mut stats = RepoStats::new();
+    let args = config::parse_args()?;
+
+    match &args.command {
+        Some(Command::Serve {
+            mcp,
+            lsp,
+            http,
+            socket,
+            root,
+        }) => {
+            if let Some(socket) = socket {
+                return daemon::serve(root, socket);
+            }
+            if let Some(addr) = http {
+                return http::HttpServer::new(root).run(addr);
+            }
+            if *lsp {
+                return lsp::LspServer::new(root).run();
+            }
+            if *mcp {
+                return mcp::McpServer::new(root).run();
+            }
+        }
+        Some(Command::Query {
+            symbol,
+            kind,
+            socket,
+            json,
+        }) => {
+            let matches = daemon::query(socket, symbol, kind.as_deref())?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&matches)?);
+            } else {
+                for m in &matches {
+                    println!("{}", daemon::format_match(m));
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Bench {
+            root,
+            runs,
+            baseline,
+            save,
+        }) => {
+            let baseline = baseline.as_deref().map(bench::load_baseline).transpose()?;
+            let report = bench::run(root, *runs)?;
+            print!("{}", bench::format_report(&report, baseline.as_ref()));
+            if let Some(path) = save {
+                bench::save_baseline(path, &report)?;
+                eprintln!("Baseline saved to: {}", path.display());
+            }
+            return Ok(());
+        }
+        Some(Command::Explain { path, map }) => {
+            let file = configure_builder(map)?.explain(path)?;
+            match (&file.exclusion, file.language) {
+                (Some(exclusion), _) => println!("{}: not mapped, {}", path.display(), exclusion),
+                (None, Some(language)) => {
+                    println!("{}: mapped as {}", path.display(), language)
+                }
+                (None, None) => unreachable!("files without a language are excluded"),
+            }
+            return Ok(());
+        }
+        Some(Command::Watch { map, debounce }) => {
+            if map.list {
+                bail!("--list can't be used with watch");
+            }
+            return watch::run(map, Duration::from_millis(*debounce));
+        }
+        None => {}
+    }
+
+    let args = args.map;
+    let builder = configure_builder(&args)?;
+    if args.list {
+        return print_listing(&args.root, &builder.list()?);
+    }
 
-    // Files to always exclude (generated by repomap)
-    const EXCLUDED_FILES: &[&str] = &["repomap.md", "CLAUDE.md"];
+    // NDJSON is written as each file is parsed instead of assembled at the end
+    if args.format == OutputFormat::Ndjson && !args.reshapes() {
+        let mut writer: Box<dyn Write> = match &args.output {
+            Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+            None => Box::new(io::stdout().lock()),
+        };
+        let count =
+            builder.for_each_file(|file| formatter::write_ndjson_line(&mut writer, &file))?;
+
+        eprintln!("----------------------------------------");
+        eprintln!("Streamed {} files.", count);
+        eprintln!("----------------------------------------");
+        return Ok(());
+    }
+
+    let map = builder.build()?;
+    write_map(&args, &map)
+}
+
+/// Validates flag combinations and configures the walk for `args`.
+fn configure_builder(args: &MapArgs) -> Result<RepoMapBuilder> {
+    if args.claude && args.format != OutputFormat::Markdown {
+        bail!("--claude only supports the markdown format");
+    }
+    if args.mermaid && args.format != OutputFormat::Markdown {
+        bail!("--mermaid only supports the markdown format");
+    }
+    if args.format == OutputFormat::Sqlite && args.output.is_none() {
+        bail!("--format sqlite requires --output <path>");
+    }
+    if args.max_tokens.is_some()
+        && (args.format != OutputFormat::Markdown || args.template.is_some())
+    {
+        bail!("--max-tokens only supports the markdown format");
+    }
+    if args.split_tokens.is_some() && args.format != OutputFormat::Markdown {
+        bail!("--split-tokens only supports the markdown format");
+    }
+    // Not a clap `requires`, which a .repomap.toml default wouldn't satisfy
+    if !args.expand.is_empty() && args.collapse_dirs.is_none() {
+        bail!("--expand requires --collapse-dirs");
+    }
+
+    let mut builder = new_builder(&args.root)
+        .depth(args.depth)
+        .excludes(&args.exclude)
+        .includes(&args.include)
+        .language(args.language)
+        .map_extensions(args.map_extension.iter().map(|(ext, lang)| (ext, *lang)))
+        .detect_content(args.detect_content)
+        .queries(queries(args)?)
+        .tokenizer(args.tokenizer())
+        .weights(weights(args))
+        .tests(args.tests())
+        .include_generated(args.include_generated)
+        .doc_links(args.doc_links)
+        .code_blocks(args.code_blocks)
+        .include_vendored(args.include_vendored)
+        .no_gitignore(args.no_gitignore)
+        .hidden(args.hidden)
+        .no_ignore_parent(args.no_ignore_parent)
+        .follow_symlinks(args.follow_symlinks)
+        .max_file_size(args.max_file_size);
+    if !args.no_cache {
+        builder = builder.cache_dir(Some(Path::new(&args.root).join(CACHE_DIR)));
+    }
 
     // Also exclude the specific output path if provided
     let output_path = if args.claude {
@@ -59,48 +695,121 @@ fn main() -> Result<()> {
     } else {
         args.output.clone()
     };
-    let output_canonical = output_path.as_ref().and_then(|p| p.canonicalize().ok());
-
-    for result in walk::create_walker(&args.root, args.depth, &args.exclude) {
-        let entry = result?;
-        let path = entry.path();
-
-        // Skip generated files (repomap.md, CLAUDE.md) and the output file
-        if let Some(file_name) = path.file_name().and_then(|n| n.to_str())
-            && EXCLUDED_FILES.contains(&file_name)
-        {
-            continue;
-        }
-        if let Some(ref out_path) = output_canonical
-            && let Ok(canonical) = path.canonicalize()
-            && canonical == *out_path
-        {
-            continue;
-        }
-
-        if path.is_file() && !walk::is_binary(path) {
-            let target_lang = args.language.or_else(|| languages::infer_language(path));
-            if let Some(lang) = target_lang
-                && let Ok((file_map, sym_count, line_count)) =
-                    formatter::process_file_with_stats(path, lang)
-                && !file_map.is_empty()
-            {
-                stats.add_file(path, file_map, sym_count, line_count);
-            }
+    if let Some(path) = &output_path {
+        builder = builder.skip_path(path);
+    }
+    if args.split_tokens.is_some() {
+        let base = split_base(args);
+        for index in 1..=MAX_PARTS {
+            builder = builder.skip_path(part_path(&base, index));
         }
     }
 
-    let final_output = formatter::assemble_final_map(&args.root, &stats, args.summary);
-    let token_estimate = stats.estimate_tokens(&final_output);
+    Ok(builder)
+}
 
-    eprintln!("----------------------------------------");
-    eprintln!("Processed {} files.", stats.file_count);
-    eprintln!("Estimated Tokens: ~{}", token_estimate);
-    eprintln!("----------------------------------------");
+/// `--replace-query` files, then `--query` files on top.
+fn queries(args: &MapArgs) -> Result<QueryRegistry> {
+    let mut registry = QueryRegistry::new();
+    for (lang, path) in &args.replace_query {
+        registry.load(*lang, &resolve_path(args, path), QueryMode::Replace)?;
+    }
+    for (lang, path) in &args.query {
+        registry.load(*lang, &resolve_path(args, path), QueryMode::Extend)?;
+    }
+    Ok(registry)
+}
+
+/// `--weight`, `--focus` and query paths are taken relative to the root when they
+/// exist there, and as given otherwise.
+fn resolve_path(args: &MapArgs, path: &Path) -> PathBuf {
+    let under_root = Path::new(&args.root).join(path);
+    if under_root.exists() {
+        under_root
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// `--weight` paths, plus each `--focus` path at [`FOCUS_WEIGHT`].
+fn weights(args: &MapArgs) -> Weights {
+    let mut weights = Weights::default();
+    for (path, weight) in &args.weight {
+        weights.add(resolve_path(args, path), *weight);
+    }
+    for path in &args.focus {
+        weights.add(resolve_path(args, path), FOCUS_WEIGHT);
+    }
+    weights
+}
+
+/// Renders `map` as requested by `args` and writes it to its destination.
+fn write_map(args: &MapArgs, map: &MapOutput) -> Result<()> {
+    // Reorder and trim a copy, so watch mode keeps rebuilding from the full map
+    let shaped;
+    let mut note = None;
+    let map = if args.reshapes() {
+        let mut copy = map.clone();
+        if args.rank {
+            copy.sort_by_rank();
+        }
+        if let Some(max) = args.max_files {
+            copy.keep_top_files(max);
+        }
+        if let Some(threshold) = args.collapse_dirs {
+            copy.collapse_dirs(threshold, &args.expand)?;
+        }
+        if !args.kinds.is_empty() {
+            copy.retain_kinds(&args.kinds);
+        }
+        if let Some(max) = args.max_symbols_per_file {
+            copy.cap_symbols(max);
+        }
+        if !args.focus.is_empty() {
+            let paths: Vec<PathBuf> = args.focus.iter().map(|p| resolve_path(args, p)).collect();
+            copy.focus(&paths);
+        }
+        if let Some(max_tokens) = args.max_tokens() {
+            note = copy
+                .fit_to_budget(max_tokens, args.summary)
+                .note(max_tokens);
+        }
+        shaped = copy;
+        &shaped
+    } else {
+        map
+    };
+    let stats = &map.stats;
+
+    if let Some(max_tokens) = args.split_tokens {
+        return write_parts(args, map, max_tokens);
+    }
+
+    if args.format == OutputFormat::Sqlite {
+        let db_path = args.output.as_ref().expect("checked before the walk");
+        map.write_sqlite(db_path)?;
+        eprintln!("----------------------------------------");
+        eprintln!("Processed {} files.", stats.file_count);
+        eprintln!("----------------------------------------");
+        eprintln!("Database successfully written to: {}", db_path.display());
+        return Ok(());
+    }
 
     if args.claude {
         // --claude flag: wrap and smart update CLAUDE.md (or custom path)
-        let output_path = args.output.unwrap_or_else(|| PathBuf::from("CLAUDE.md"));
+        let mut buffer = Vec::new();
+        write_rendered(args, map, note.as_deref(), &mut buffer)?;
+        let final_output = String::from_utf8(buffer)?;
+        let token_estimate = args.tokenizer().count(&final_output);
+        print_stats(stats, token_estimate);
+        if let Some(note) = &note {
+            eprintln!("{}", note.trim_start_matches("> "));
+        }
+
+        let output_path = args
+            .output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("CLAUDE.md"));
         let wrapped =
             formatter::wrap_for_claude_md(&final_output, stats.file_count, token_estimate);
 
@@ -113,13 +822,141 @@ fn main() -> Result<()> {
 
         std::fs::write(&output_path, &final_content)?;
         eprintln!("Map successfully written to: {}", output_path.display());
-    } else if let Some(output_path) = &args.output {
-        // Plain -o flag: simple overwrite
-        std::fs::write(output_path, &final_output)?;
+        return Ok(());
+    }
+
+    // Everything else streams straight to its destination
+    let sink: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = TokenCountingWriter::new(sink, args.tokenizer());
+    write_rendered(args, map, note.as_deref(), &mut writer)?;
+    if args.output.is_none() {
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    print_stats(stats, writer.tokens());
+    if let Some(note) = &note {
+        eprintln!("{}", note.trim_start_matches("> "));
+    }
+
+    if let Some(output_path) = &args.output {
         eprintln!("Map successfully written to: {}", output_path.display());
+    }
+
+    Ok(())
+}
+
+/// The output path the part files are named after.
+fn split_base(args: &MapArgs) -> PathBuf {
+    args.output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("repomap.md"))
+}
+
+/// `map.md` becomes `map.part1.md`, `map.part2.md`, ...
+fn part_path(base: &Path, index: usize) -> PathBuf {
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match base.extension() {
+        Some(ext) => format!("{}.part{}.{}", stem, index, ext.to_string_lossy()),
+        None => format!("{}.part{}", stem, index),
+    };
+    base.with_file_name(name)
+}
+
+/// Writes `map` as numbered parts of at most `max_tokens` each.
+fn write_parts(args: &MapArgs, map: &MapOutput, max_tokens: usize) -> Result<()> {
+    let parts = map.split(max_tokens);
+    if parts.len() > MAX_PARTS {
+        bail!(
+            "--split-tokens {} would write {} parts; the limit is {}",
+            max_tokens,
+            parts.len(),
+            MAX_PARTS
+        );
+    }
+
+    let base = split_base(args);
+    let mut total_tokens = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let path = part_path(&base, i + 1);
+        let sink = BufWriter::new(File::create(&path)?);
+        let mut writer = TokenCountingWriter::new(sink, args.tokenizer());
+        part.write_part(&mut writer, i + 1, parts.len())?;
+        writer.flush()?;
+
+        let tokens = writer.tokens();
+        total_tokens += tokens;
+        eprintln!("Wrote {} (~{} tokens)", path.display(), tokens);
+    }
+    print_stats(&map.stats, total_tokens);
+    Ok(())
+}
+
+/// Writes the map in the format, template, or Markdown variant `args` asks
+/// for, followed by `note` when the map was trimmed to a budget.
+fn write_rendered(
+    args: &MapArgs,
+    map: &MapOutput,
+    note: Option<&str>,
+    writer: &mut impl Write,
+) -> Result<()> {
+    if let Some(template_path) = &args.template {
+        writer.write_all(map.render_template(template_path)?.as_bytes())?;
+    } else if args.format == OutputFormat::Markdown {
+        map.write_markdown(writer, args.summary)?;
+        if args.mermaid {
+            writer.write_all(b"\n## Dependency Graph\n")?;
+            writer.write_all(map.to_mermaid().as_bytes())?;
+        }
+        if let Some(note) = note {
+            write!(writer, "\n{}\n", note)?;
+        }
     } else {
-        println!("{}", final_output);
+        map.write(args.format, writer)?;
+    }
+    Ok(())
+}
+
+/// Prints one line per file for `--list`: its path relative to the root,
+/// then its language or why it would be skipped.
+fn print_listing(root: &str, files: &[ListedFile]) -> Result<()> {
+    let paths: Vec<String> = files
+        .iter()
+        .map(|file| {
+            let path = file.path.strip_prefix(root).unwrap_or(&file.path);
+            path.display().to_string()
+        })
+        .collect();
+    let width = paths.iter().map(String::len).max().unwrap_or(0);
+
+    let mut out = io::stdout().lock();
+    let mut mapped = 0;
+    for (file, path) in files.iter().zip(&paths) {
+        let status = match (&file.exclusion, file.language) {
+            (Some(exclusion), _) => format!("skipped ({})", exclusion),
+            (None, Some(language)) => {
+                mapped += 1;
+                language.to_string()
+            }
+            (None, None) => unreachable!("files without a language are excluded"),
+        };
+        writeln!(out, "{:width$}  {}", path, status)?;
     }
 
+    eprintln!("----------------------------------------");
+    eprintln!("{} of {} files would be mapped.", mapped, files.len());
+    eprintln!("----------------------------------------");
     Ok(())
 }
+
+fn print_stats(stats: &RepoStats, token_estimate: usize) {
+    eprintln!("----------------------------------------");
+    eprintln!("Processed {} files.", stats.file_count);
+    if let Some(note) = walk::skipped_note(&stats.skipped) {
+        eprintln!("{}", note);
+    }
+    eprintln!("Estimated Tokens: ~{}", token_estimate);
+    eprintln!("----------------------------------------");
+}
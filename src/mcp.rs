@@ -0,0 +1,273 @@
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use repomap_core::{OutputFormat, formatter, languages};
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Minimal Model Context Protocol server over stdio. Every tool call maps
+/// the repository fresh, so clients never see a stale map.
+pub struct McpServer {
+    root: String,
+}
+
+impl McpServer {
+    pub fn new(root: impl Into<String>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let stdin = io::stdin();
+        let mut stdout = io::stdout().lock();
+
+        for line in stdin.lock().lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => self.handle(&request),
+                Err(e) => Some(error_response(Value::Null, -32700, &e.to_string())),
+            };
+            if let Some(response) = response {
+                serde_json::to_writer(&mut stdout, &response)?;
+                writeln!(stdout)?;
+                stdout.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles one JSON-RPC message. Notifications get no response.
+    pub fn handle(&self, request: &Value) -> Option<Value> {
+        let id = request.get("id").cloned()?;
+        let method = request["method"].as_str().unwrap_or_default();
+        let params = &request["params"];
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "repomap", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => Ok(self.call_tool(params)),
+            _ => Err((-32601, format!("method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn call_tool(&self, params: &Value) -> Value {
+        let name = params["name"].as_str().unwrap_or_default();
+        let arguments = &params["arguments"];
+
+        let result = match name {
+            "get_repo_map" => self.get_repo_map(arguments),
+            "get_file_outline" => self.get_file_outline(arguments),
+            "find_symbol" => self.find_symbol(arguments),
+            _ => Err(anyhow!("unknown tool: {}", name)),
+        };
+
+        match result {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": format!("{:#}", e) }],
+                "isError": true,
+            }),
+        }
+    }
+
+    fn get_repo_map(&self, arguments: &Value) -> Result<String> {
+        let root = crate::resolve_in_root(&self.root, arguments["path"].as_str())?;
+        let format = match arguments["format"].as_str() {
+            Some(name) => OutputFormat::from_str(name, true).map_err(|e| anyhow!(e))?,
+            None => OutputFormat::Markdown,
+        };
+        let depth = arguments["depth"].as_u64().map(|d| d as usize);
+
+        let map = crate::new_builder(&root).depth(depth).build()?;
+        if format == OutputFormat::Markdown {
+            Ok(map.to_markdown(arguments["summary"].as_bool().unwrap_or(false)))
+        } else {
+            map.render(format)
+        }
+    }
+
+    fn get_file_outline(&self, arguments: &Value) -> Result<String> {
+        let relative = arguments["path"]
+            .as_str()
+            .ok_or_else(|| anyhow!("missing required argument: path"))?;
+        let path = crate::resolve_in_root(&self.root, Some(relative))?;
+        let lang = languages::infer_language(Path::new(&path))
+            .ok_or_else(|| anyhow!("unsupported file type: {}", relative))?;
+
+        let file = formatter::process_file(Path::new(&path), lang)?;
+        if file.is_empty() {
+            Ok(format!("No symbols or imports found in {}", relative))
+        } else {
+            Ok(file.to_markdown())
+        }
+    }
+
+    fn find_symbol(&self, arguments: &Value) -> Result<String> {
+        let query = arguments["query"]
+            .as_str()
+            .ok_or_else(|| anyhow!("missing required argument: query"))?;
+
+        let map = crate::new_builder(&self.root).build()?;
+        let matches: Vec<String> = map
+            .find_symbols(query, arguments["kind"].as_str())
+            .into_iter()
+            .map(|(file, sym)| {
+                format!(
+                    "{}:{} | {} | {}",
+                    file.path,
+                    sym.line,
+                    sym.kind,
+                    formatter::display_name(sym)
+                )
+            })
+            .collect();
+
+        if matches.is_empty() {
+            Ok(format!("No symbols matching '{}'", query))
+        } else {
+            Ok(matches.join("\n"))
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "get_repo_map",
+            "description": "Generate a fresh map of the repository (or a subdirectory) listing files, symbols, and imports.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Subdirectory relative to the repository root" },
+                    "format": { "type": "string", "description": "Output format, e.g. markdown, json, aider" },
+                    "depth": { "type": "integer", "description": "Maximum traversal depth" },
+                    "summary": { "type": "boolean", "description": "Include the summary table (markdown only)" }
+                }
+            }
+        },
+        {
+            "name": "get_file_outline",
+            "description": "List the symbols and imports of a single file.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File path relative to the repository root" }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "find_symbol",
+            "description": "Find symbols whose name contains the query (case-insensitive).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Substring to search for" },
+                    "kind": { "type": "string", "description": "Only match this symbol kind, e.g. function, class, or a raw node kind" }
+                },
+                "required": ["query"]
+            }
+        }
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn call(server: &McpServer, name: &str, arguments: Value) -> Value {
+        server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": arguments },
+            }))
+            .unwrap()["result"]
+            .clone()
+    }
+
+    #[test]
+    fn test_initialize_and_list_tools() {
+        let server = McpServer::new(".");
+
+        let init = server
+            .handle(&json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} }))
+            .unwrap();
+        assert_eq!(init["result"]["serverInfo"]["name"], "repomap");
+
+        assert!(
+            server
+                .handle(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+                .is_none()
+        );
+
+        let tools = server
+            .handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+            .unwrap();
+        let names: Vec<&str> = tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["get_repo_map", "get_file_outline", "find_symbol"]
+        );
+    }
+
+    #[test]
+    fn test_tool_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("app.py"),
+            "class Server:\n    def start(self):\n        pass\n",
+        )
+        .unwrap();
+        let server = McpServer::new(dir.path().to_str().unwrap());
+
+        let outline = call(&server, "get_file_outline", json!({ "path": "app.py" }));
+        assert!(
+            outline["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("Server > start")
+        );
+
+        let found = call(&server, "find_symbol", json!({ "query": "STA" }));
+        let text = found["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("app.py:2 | function_definition | Server > start"));
+
+        let escaped = call(
+            &server,
+            "get_file_outline",
+            json!({ "path": "../etc/passwd" }),
+        );
+        assert_eq!(escaped["isError"], true);
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{Context, Result, bail};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use crate::MapArgs;
+
+/// Writes the map once, then keeps it up to date as files change. Bursts of
+/// events are coalesced until `debounce` passes without another one.
+pub fn run(args: &MapArgs, debounce: Duration) -> Result<()> {
+    if args.output.is_none() && !args.claude {
+        bail!("watch requires --output or --claude");
+    }
+
+    let builder = crate::configure_builder(args)?;
+    let mut map = builder.build()?;
+    crate::write_map(args, &map)?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher
+        .watch(Path::new(&args.root), RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch {}", args.root))?;
+    eprintln!("Watching {} for changes (Ctrl-C to stop)", args.root);
+
+    loop {
+        let batch = next_batch(&rx, debounce)?;
+        let paths: Vec<PathBuf> = batch
+            .into_iter()
+            .filter(|path| !builder.is_skipped(path))
+            .collect();
+        // Our own writes to the output file land here too
+        if paths.is_empty() {
+            continue;
+        }
+
+        let changed: HashSet<PathBuf> = paths
+            .iter()
+            .filter_map(|path| path.canonicalize().ok())
+            .collect();
+        let before = serde_json::to_value(map.files())?;
+        // Rebuilt from a copy, so a failure, like a half-saved
+        // .gitattributes, leaves the previous map for the next change
+        map = match builder.rebuild(map.clone(), &changed) {
+            Ok(rebuilt) => rebuilt,
+            Err(e) => {
+                eprintln!("Failed to rebuild map: {:#}", e);
+                continue;
+            }
+        };
+
+        // Edits to ignored or unsupported files leave the map untouched
+        if serde_json::to_value(map.files())? == before {
+            continue;
+        }
+        if let Err(e) = crate::write_map(args, &map) {
+            eprintln!("Failed to write map: {:#}", e);
+        }
+    }
+}
+
+/// Blocks for the next filesystem event, then drains everything that follows
+/// within `debounce` of the previous one. Returns the paths touched, or an
+/// error once the watcher has stopped; errors it reports along the way are
+/// printed and skipped.
+pub fn next_batch(
+    rx: &Receiver<notify::Result<Event>>,
+    debounce: Duration,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut collect = |event: notify::Result<Event>| match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => paths.extend(event.paths),
+        Ok(_) => {}
+        Err(e) => eprintln!("File watcher error: {}", e),
+    };
+
+    collect(rx.recv().context("file watcher stopped")?);
+    while let Ok(event) = rx.recv_timeout(debounce) {
+        collect(event);
+    }
+
+    Ok(paths)
+}